@@ -0,0 +1,80 @@
+//! Generates `ERROR_TABLE` and the `ToStr` messages of `UniPinoNftErr` from the
+//! `#[error("...")]` attributes of the enum in `src/error.rs`, so every message is
+//! written once and the enum stays plain source for shank to read.
+
+use std::fmt::Write;
+use std::{env, fs, path::Path};
+
+const ERROR_SOURCE: &str = "src/error.rs";
+
+fn main() {
+    println!("cargo:rerun-if-changed={}", ERROR_SOURCE);
+
+    let source = fs::read_to_string(ERROR_SOURCE).expect("read src/error.rs");
+    let errors = parse_errors(&source);
+    assert!(!errors.is_empty(), "no `#[error]` variants in src/error.rs");
+
+    let mut table = String::new();
+    let mut messages = String::new();
+    for (variant, message) in &errors {
+        writeln!(
+            table,
+            "    (UniPinoNftErr::{variant} as u32, \"{variant}\", \"{message}\"),"
+        )
+        .unwrap();
+        writeln!(
+            messages,
+            "            Self::{variant} => \"ERROR: {message}\","
+        )
+        .unwrap();
+    }
+
+    let generated = format!(
+        "/// `(code, name, message)` of every custom error, for clients decoding
+/// `ProgramError::Custom` codes
+pub const ERROR_TABLE: &[(u32, &str, &str)] = &[
+{table}];
+
+impl ToStr for UniPinoNftErr {{
+    fn to_str<E>(&self) -> &'static str
+    where
+        E: 'static + ToStr + TryFrom<u32>,
+    {{
+        match self {{
+{messages}        }}
+    }}
+}}
+"
+    );
+
+    let out_dir = env::var("OUT_DIR").expect("OUT_DIR is set by cargo");
+    fs::write(Path::new(&out_dir).join("error_table.rs"), generated).expect("write error_table.rs");
+}
+
+/// `(variant, message)` pairs of the `UniPinoNftErr` enum, in declaration order;
+/// messages keep their source escapes since they are written back as literals
+fn parse_errors(source: &str) -> Vec<(String, String)> {
+    let mut lines = source
+        .lines()
+        .map(str::trim)
+        .skip_while(|line| !line.starts_with("pub enum UniPinoNftErr"))
+        .skip(1)
+        .take_while(|line| *line != "}");
+
+    let mut errors = Vec::new();
+    while let Some(line) = lines.next() {
+        let Some(message) = line
+            .strip_prefix("#[error(\"")
+            .and_then(|rest| rest.strip_suffix("\")]"))
+        else {
+            continue;
+        };
+
+        let variant = lines
+            .next()
+            .and_then(|line| line.strip_suffix(','))
+            .unwrap_or_else(|| panic!("no variant after #[error(\"{message}\")]"));
+        errors.push((variant.to_string(), message.to_string()));
+    }
+    errors
+}
@@ -0,0 +1,128 @@
+use std::fs;
+
+use UniPinoNft::instructions::allowlist::{self, MAX_PROOF_NODES};
+use solana_pubkey::Pubkey;
+
+use crate::args::parse_number;
+
+/// Merkle tree of the user PDAs on a collection allowlist, hashed with the program's own
+/// `allowlist::leaf` and `allowlist::node`
+pub struct Allowlist {
+    /// Leaves first, the root level last; an odd node moves up unpaired
+    levels: Vec<Vec<[u8; 32]>>,
+}
+
+impl Allowlist {
+    pub fn new(users: &[Pubkey]) -> Result<Self, String> {
+        if users.is_empty() {
+            return Err("the allowlist is empty".to_string());
+        }
+
+        let mut levels = vec![users.iter().map(leaf).collect::<Vec<_>>()];
+        while let Some(level) = levels.last().filter(|level| level.len() > 1) {
+            let parents = level
+                .chunks(2)
+                .map(|pair| match pair {
+                    [a, b] => allowlist::node(a, b),
+                    [a] => *a,
+                    _ => unreachable!("chunks of two"),
+                })
+                .collect();
+            levels.push(parents);
+        }
+
+        if levels.len() > MAX_PROOF_NODES + 1 {
+            return Err(format!(
+                "the allowlist is deeper than {} levels",
+                MAX_PROOF_NODES
+            ));
+        }
+        Ok(Self { levels })
+    }
+
+    pub fn root(&self) -> [u8; 32] {
+        self.levels[self.levels.len() - 1][0]
+    }
+
+    /// Proof of `user`, the sibling of its node on every level that has one
+    pub fn proof(&self, user: &Pubkey) -> Result<Vec<[u8; 32]>, String> {
+        let mut index = self.levels[0]
+            .iter()
+            .position(|hash| *hash == leaf(user))
+            .ok_or_else(|| format!("user {} is not on the allowlist", user))?;
+
+        let mut proof = Vec::new();
+        for level in &self.levels[..self.levels.len() - 1] {
+            if let Some(sibling) = level.get(index ^ 1) {
+                proof.push(*sibling);
+            }
+            index /= 2;
+        }
+        Ok(proof)
+    }
+}
+
+fn leaf(user: &Pubkey) -> [u8; 32] {
+    allowlist::leaf(&user.to_bytes())
+}
+
+/// User uuids of an allowlist file, separated by whitespace
+pub fn read_uuids(path: &str) -> Result<Vec<u128>, String> {
+    let text = fs::read_to_string(path).map_err(|err| format!("{}: {}", path, err))?;
+    text.split_whitespace()
+        .map(|uuid| parse_number(uuid).map_err(|err| format!("{}: {}", path, err)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn users(count: usize) -> Vec<Pubkey> {
+        (0..count).map(|_| Pubkey::new_unique()).collect()
+    }
+
+    #[test]
+    fn every_proof_verifies_against_the_root() {
+        // odd sizes leave unpaired nodes on some levels
+        for count in [1, 2, 3, 5, 8, 13] {
+            let users = users(count);
+            let tree = Allowlist::new(&users).unwrap();
+            for user in &users {
+                let proof = tree.proof(user).unwrap();
+                assert!(allowlist::verify(&tree.root(), leaf(user), &proof));
+            }
+        }
+    }
+
+    #[test]
+    fn proofs_do_not_verify_for_other_users() {
+        let users = users(5);
+        let tree = Allowlist::new(&users).unwrap();
+        let proof = tree.proof(&users[0]).unwrap();
+
+        assert!(!allowlist::verify(&tree.root(), leaf(&users[1]), &proof));
+        assert!(!allowlist::verify(
+            &tree.root(),
+            leaf(&Pubkey::new_unique()),
+            &proof
+        ));
+        assert!(tree.proof(&Pubkey::new_unique()).is_err());
+    }
+
+    #[test]
+    fn inner_nodes_do_not_pass_as_leaves() {
+        let users = users(4);
+        let tree = Allowlist::new(&users).unwrap();
+        let parent = allowlist::node(&leaf(&users[0]), &leaf(&users[1]));
+        let sibling = allowlist::node(&leaf(&users[2]), &leaf(&users[3]));
+
+        // the parent hash used as a user PDA is hashed as a leaf and misses the root
+        assert!(allowlist::verify(&tree.root(), parent, &[sibling]));
+        assert!(!allowlist::verify(
+            &tree.root(),
+            allowlist::leaf(&parent),
+            &[sibling]
+        ));
+    }
+}
@@ -0,0 +1,118 @@
+use std::str::FromStr;
+
+use solana_pubkey::Pubkey;
+
+/// Options that take no value
+const SWITCHES: &[&str] = &["offline", "session", "clear-fee-shares", "clear-fee-tiers"];
+
+/// Command line split into positional arguments, `--name value` options and switches
+pub struct Args {
+    positional: Vec<String>,
+    options: Vec<(String, String)>,
+    switches: Vec<String>,
+}
+
+impl Args {
+    pub fn parse(args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Self {
+            positional: Vec::new(),
+            options: Vec::new(),
+            switches: Vec::new(),
+        };
+
+        let mut args = args.peekable();
+        while let Some(arg) = args.next() {
+            let Some(name) = arg.strip_prefix("--") else {
+                parsed.positional.push(arg);
+                continue;
+            };
+            if SWITCHES.contains(&name) {
+                parsed.switches.push(name.to_string());
+                continue;
+            }
+            let value = args
+                .next()
+                .ok_or_else(|| format!("missing value for --{}", name))?;
+            parsed.options.push((name.to_string(), value));
+        }
+
+        Ok(parsed)
+    }
+
+    /// Take the next positional argument
+    pub fn next(&mut self, name: &str) -> Result<String, String> {
+        if self.positional.is_empty() {
+            return Err(format!("missing <{}>", name));
+        }
+        Ok(self.positional.remove(0))
+    }
+
+    /// Take all remaining positional arguments
+    pub fn rest(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.positional)
+    }
+
+    pub fn next_pubkey(&mut self, name: &str) -> Result<Pubkey, String> {
+        parse_pubkey(&self.next(name)?)
+    }
+
+    pub fn next_number<T: FromStr>(&mut self, name: &str) -> Result<T, String> {
+        parse_number(&self.next(name)?)
+    }
+
+    pub fn option(&self, name: &str) -> Option<&str> {
+        self.options
+            .iter()
+            .rev()
+            .find(|(option, _)| option == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Every value of a repeatable option, in command line order
+    pub fn options(&self, name: &str) -> Vec<&str> {
+        self.options
+            .iter()
+            .filter(|(option, _)| option == name)
+            .map(|(_, value)| value.as_str())
+            .collect()
+    }
+
+    pub fn switch(&self, name: &str) -> bool {
+        self.switches.iter().any(|switch| switch == name)
+    }
+
+    pub fn pubkey_option(&self, name: &str) -> Result<Option<Pubkey>, String> {
+        self.option(name).map(parse_pubkey).transpose()
+    }
+
+    pub fn number_option<T: FromStr>(&self, name: &str) -> Result<Option<T>, String> {
+        self.option(name).map(parse_number).transpose()
+    }
+
+    /// Fail on positional arguments no command consumed
+    pub fn finish(&self) -> Result<(), String> {
+        match self.positional.first() {
+            Some(arg) => Err(format!("unexpected argument `{}`", arg)),
+            None => Ok(()),
+        }
+    }
+}
+
+pub fn parse_pubkey(text: &str) -> Result<Pubkey, String> {
+    Pubkey::from_str(text).map_err(|_| format!("invalid address `{}`", text))
+}
+
+pub fn parse_number<T: FromStr>(text: &str) -> Result<T, String> {
+    text.parse()
+        .map_err(|_| format!("invalid number `{}`", text))
+}
+
+/// NUL padded fixed size text field
+pub fn fixed<const N: usize>(text: &str) -> Result<[u8; N], String> {
+    let mut field = [0; N];
+    field
+        .get_mut(..text.len())
+        .ok_or_else(|| format!("`{}` is longer than {} bytes", text, N))?
+        .copy_from_slice(text.as_bytes());
+    Ok(field)
+}
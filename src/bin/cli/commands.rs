@@ -0,0 +1,782 @@
+use UniPinoNft::instructions::{
+    CollectionAllowlistArgs, CollectionConfigArgs, NftMetaArgs, RoleArgs, SessionKeyArgs,
+    SetPauseFlagsArgs, SetUserTierArgs, UpdatePlatformArgs, VerifyOwnershipArgs,
+    WithdrawTreasuryArgs, collection::*, delegate::*, migrate::*, multisig::*, nft::*,
+    ownership::*, platform::*, role::*, session::*, timelock::*, transfer::*, treasury::*, user::*,
+};
+use UniPinoNft::state::admin_multisig::MAX_ADMIN_SIGNERS;
+use UniPinoNft::state::fee_schedule::{FeeShare, MAX_FEE_SHARES};
+use UniPinoNft::state::platform::{self as platform_state, FeeTier, MAX_FEE_TIERS, Platform};
+use UniPinoNft::state::role::{
+    ROLE_FEE_MANAGER, ROLE_METADATA_EDITOR, ROLE_MINTER, ROLE_PROGRAM_MINTER,
+};
+use bytemuck::{Pod, Zeroable, bytes_of};
+use solana_pubkey::Pubkey;
+
+use crate::allowlist::{self, Allowlist};
+use crate::args::{Args, fixed, parse_number, parse_pubkey};
+use crate::pda;
+use crate::rpc::Rpc;
+use crate::transaction::{AccountMeta, Instruction};
+
+/// Instruction subcommands and the discriminator each one sends
+pub const COMMANDS: &[(&str, u8)] = &[
+    ("init-platform", *InitPlatform::DISCRIMINATOR),
+    (
+        "update-platform-config",
+        *UpdatePlatformConfig::DISCRIMINATOR,
+    ),
+    ("create-user", *CreateUser::DISCRIMINATOR),
+    ("mint-nft", *MintNft::DISCRIMINATOR),
+    ("update-metadata", *UpdateNFTMetadata::DISCRIMINATOR),
+    ("burn-nft", *BurnNft::DISCRIMINATOR),
+    ("withdraw-treasury", *WithdrawTreasury::DISCRIMINATOR),
+    ("set-collection-config", *SetCollectionConfig::DISCRIMINATOR),
+    (
+        "set-collection-allowlist",
+        *SetCollectionAllowlist::DISCRIMINATOR,
+    ),
+    ("set-pause-flags", *SetPauseFlags::DISCRIMINATOR),
+    ("grant-role", *GrantRole::DISCRIMINATOR),
+    ("revoke-role", *RevokeRole::DISCRIMINATOR),
+    ("set-admin-multisig", *SetAdminMultisig::DISCRIMINATOR),
+    ("queue-config-change", *QueueConfigChange::DISCRIMINATOR),
+    ("execute-config-change", *ExecuteConfigChange::DISCRIMINATOR),
+    ("cancel-config-change", *CancelConfigChange::DISCRIMINATOR),
+    (
+        "queue-collection-config",
+        *QueueCollectionConfig::DISCRIMINATOR,
+    ),
+    (
+        "queue-collection-allowlist",
+        *QueueCollectionAllowlist::DISCRIMINATOR,
+    ),
+    (
+        "execute-collection-change",
+        *ExecuteCollectionChange::DISCRIMINATOR,
+    ),
+    (
+        "cancel-collection-change",
+        *CancelCollectionChange::DISCRIMINATOR,
+    ),
+    ("close-user", *CloseUser::DISCRIMINATOR),
+    ("close-platform", *ClosePlatform::DISCRIMINATOR),
+    ("migrate-account", *MigrateAccount::DISCRIMINATOR),
+    ("link-user-wallet", *LinkUserWallet::DISCRIMINATOR),
+    ("unlink-user-wallet", *UnlinkUserWallet::DISCRIMINATOR),
+    ("create-session-key", *CreateSessionKey::DISCRIMINATOR),
+    ("revoke-session-key", *RevokeSessionKey::DISCRIMINATOR),
+    ("approve-nft-delegate", *ApproveNftDelegate::DISCRIMINATOR),
+    ("revoke-nft-delegate", *RevokeNftDelegate::DISCRIMINATOR),
+    ("verify-ownership", *VerifyOwnership::DISCRIMINATOR),
+    ("transfer-nft", *TransferNftInternal::DISCRIMINATOR),
+    ("set-user-tier", *SetUserTier::DISCRIMINATOR),
+    ("rotate-administrator", *RotateAdministrator::DISCRIMINATOR),
+];
+
+/// Signer and platform every instruction is built for
+pub struct Context {
+    pub authority: Pubkey,
+    pub administrator: Pubkey,
+    pub platform: Pubkey,
+    pub platform_bump: u8,
+    /// `--remaining` accounts, appended where the instruction takes its optional accounts
+    pub remaining: Vec<AccountMeta>,
+    pub rpc: Option<Rpc>,
+}
+
+impl Context {
+    fn is_administrator(&self) -> bool {
+        self.authority == self.administrator
+    }
+
+    fn user(&self, user_uuid: u128) -> Pubkey {
+        pda::user(&self.platform, self.platform_bump, user_uuid).0
+    }
+
+    /// Allowlist of the users listed by uuid in the file at `path`
+    fn allowlist(&self, path: &str) -> Result<Allowlist, String> {
+        let users: Vec<Pubkey> = allowlist::read_uuids(path)?
+            .into_iter()
+            .map(|user_uuid| self.user(user_uuid))
+            .collect();
+        Allowlist::new(&users)
+    }
+
+    /// Role PDA the authority passes when it is not the administrator
+    fn role(&self, role_id: u8) -> Vec<AccountMeta> {
+        if self.is_administrator() {
+            return Vec::new();
+        }
+        let (role, _) = pda::role(&self.platform, role_id, &self.authority);
+        vec![AccountMeta::readonly(role)]
+    }
+
+    fn platform_state(&self) -> Result<Platform, String> {
+        let rpc = self
+            .rpc
+            .as_ref()
+            .ok_or("reading the platform needs RPC access")?;
+        let data = rpc
+            .account_data(&self.platform)?
+            .ok_or_else(|| format!("platform {} does not exist", self.platform))?;
+        Platform::read(&data).map_err(|_| format!("{} is not a platform account", self.platform))
+    }
+}
+
+/// Build the instruction of subcommand `command`
+pub fn build(command: &str, args: &mut Args, ctx: &mut Context) -> Result<Instruction, String> {
+    let &(_, discriminator) = COMMANDS
+        .iter()
+        .find(|(name, _)| *name == command)
+        .ok_or_else(|| format!("unknown command `{}`", command))?;
+
+    let authority = AccountMeta::signer(ctx.authority, true);
+    let platform = ctx.platform;
+    let system_program = AccountMeta::readonly(Pubkey::default());
+
+    let (accounts, data) = match command {
+        "init-platform" => (
+            vec![authority, AccountMeta::writable(platform), system_program],
+            Vec::new(),
+        ),
+        "update-platform-config" => {
+            let update_args = platform_args(args)?;
+            let mut accounts = vec![authority, AccountMeta::writable(platform), system_program];
+            accounts.append(&mut ctx.remaining);
+            if update_args.is_fee_schedule_valid != 0 && update_args.fee_share_count > 0 {
+                accounts.push(AccountMeta::writable(pda::fee_schedule(&platform).0));
+            }
+            if update_args.is_treasury_valid != 0 && update_args.treasury_enabled != 0 {
+                accounts.push(AccountMeta::writable(pda::treasury(&platform).0));
+            }
+            (accounts, bytes_of(&update_args).to_vec())
+        }
+        "create-user" => {
+            let user_uuid: u128 = args.next_number("user uuid")?;
+            let mut accounts = vec![
+                authority,
+                AccountMeta::writable(platform),
+                AccountMeta::writable(ctx.user(user_uuid)),
+                system_program,
+            ];
+            accounts.append(&mut ctx.remaining);
+            (accounts, user_uuid.to_le_bytes().to_vec())
+        }
+        "mint-nft" => {
+            let user_uuid: u128 = args.next_number("user uuid")?;
+            let collection: [u8; 64] = fixed(args.option("collection").unwrap_or_default())?;
+            let user = ctx.user(user_uuid);
+            let (mint, _) = pda::mint(&user, user_uuid);
+            let fee_receiver = match args.pubkey_option("fee-receiver")? {
+                Some(fee_receiver) => fee_receiver,
+                None => {
+                    let state = ctx.platform_state()?;
+                    if state.has_treasury() {
+                        pda::treasury(&platform).0
+                    } else {
+                        Pubkey::new_from_array(state.fee_receiver)
+                    }
+                }
+            };
+
+            let mut data = user_uuid.to_le_bytes().to_vec();
+            data.extend_from_slice(&fixed::<256>(required(args, "name")?)?);
+            data.extend_from_slice(&fixed::<256>(
+                args.option("description").unwrap_or_default(),
+            )?);
+            data.extend_from_slice(&fixed::<256>(required(args, "uri")?)?);
+            data.extend_from_slice(&collection);
+            data.extend_from_slice(&fixed::<32>(args.option("attribute").unwrap_or_default())?);
+            let allowlist = args
+                .option("allowlist")
+                .map(|path| ctx.allowlist(path))
+                .transpose()?;
+            if let Some(allowlist) = &allowlist {
+                let proof = allowlist.proof(&user)?;
+                data.extend_from_slice(&(proof.len() as u32).to_le_bytes());
+                data.extend(proof.iter().flatten());
+            }
+
+            let mut accounts = vec![
+                authority,
+                AccountMeta::writable(platform),
+                AccountMeta::writable(user),
+                AccountMeta::writable(mint),
+                AccountMeta::writable(pda::metadata(&mint).0),
+                AccountMeta::writable(pda::token_account(&user, &mint)),
+                AccountMeta::writable(fee_receiver),
+                system_program,
+                AccountMeta::readonly(pda::token_2022()),
+                AccountMeta::readonly(pda::associated_token_program()),
+            ];
+            accounts.extend(ctx.role(ROLE_MINTER));
+            if collection != [0; 64] {
+                let (collection, _) = pda::collection(&platform, &collection);
+                if allowlist.is_some() {
+                    accounts.push(AccountMeta::writable(collection));
+                    accounts.push(AccountMeta::writable(pda::claim(&collection, &user).0));
+                } else {
+                    accounts.push(AccountMeta::readonly(collection));
+                }
+            } else if allowlist.is_some() {
+                return Err("--allowlist needs --collection".to_string());
+            }
+            accounts.append(&mut ctx.remaining);
+            (accounts, data)
+        }
+        "update-metadata" => {
+            let user_uuid: u128 = args.next_number("user uuid")?;
+            let user = ctx.user(user_uuid);
+            let mint = held_mint(args, &user, user_uuid)?;
+            let metadata = NftMetaArgs {
+                name: fixed(required(args, "name")?)?,
+                collection: fixed(args.option("collection").unwrap_or_default())?,
+                uri: fixed(required(args, "uri")?)?,
+                description: fixed(args.option("description").unwrap_or_default())?,
+                attribute: fixed(args.option("attribute").unwrap_or_default())?,
+            };
+
+            let mut accounts = vec![
+                authority,
+                AccountMeta::writable(platform),
+                AccountMeta::writable(user),
+                AccountMeta::writable(mint),
+                AccountMeta::writable(pda::metadata(&mint).0),
+                system_program,
+            ];
+            accounts.extend(ctx.role(ROLE_METADATA_EDITOR));
+            (accounts, bytes_of(&metadata).to_vec())
+        }
+        "burn-nft" => {
+            let user_uuid: u128 = args.next_number("user uuid")?;
+            let user = ctx.user(user_uuid);
+            let mint = held_mint(args, &user, user_uuid)?;
+
+            // the administrator receives the rent, a holder signs after the fixed accounts
+            let mut accounts = vec![
+                if ctx.is_administrator() {
+                    authority
+                } else {
+                    AccountMeta::writable(ctx.administrator)
+                },
+                AccountMeta::writable(platform),
+                AccountMeta::writable(user),
+                AccountMeta::writable(mint),
+                AccountMeta::writable(pda::metadata(&mint).0),
+                AccountMeta::writable(token_account(args, &user, &mint)?),
+                AccountMeta::readonly(pda::token_2022()),
+            ];
+            if ctx.is_administrator() {
+                accounts.append(&mut ctx.remaining);
+            } else {
+                accounts.push(AccountMeta::signer(ctx.authority, true));
+                if args.switch("session") {
+                    accounts.push(AccountMeta::readonly(pda::session(&user, &ctx.authority).0));
+                }
+            }
+            (accounts, Vec::new())
+        }
+        "withdraw-treasury" => {
+            let withdraw_args = WithdrawTreasuryArgs {
+                amount: args.next_number("amount")?,
+            };
+            let destination = args.next_pubkey("destination")?;
+            let mut accounts = vec![
+                authority,
+                AccountMeta::writable(platform),
+                AccountMeta::writable(pda::treasury(&platform).0),
+                AccountMeta::writable(destination),
+                system_program,
+            ];
+            accounts.append(&mut ctx.remaining);
+            (accounts, bytes_of(&withdraw_args).to_vec())
+        }
+        "set-collection-config" | "queue-collection-config" => {
+            let collection_args = collection_config_args(args)?;
+            let name = collection_args.name;
+            let collection = pda::collection(&platform, &name).0;
+            let mut accounts = vec![
+                authority,
+                AccountMeta::writable(platform),
+                AccountMeta::writable(collection),
+            ];
+            if command == "queue-collection-config" {
+                accounts[2] = AccountMeta::readonly(collection);
+                accounts.push(AccountMeta::writable(pda::collection_change(&collection).0));
+            }
+            accounts.push(system_program);
+            accounts.extend(ctx.role(ROLE_FEE_MANAGER));
+            (accounts, bytes_of(&collection_args).to_vec())
+        }
+        "set-collection-allowlist" | "queue-collection-allowlist" => {
+            let name: [u8; 64] = fixed(&args.next("name")?)?;
+            let allowlist_args = CollectionAllowlistArgs {
+                name,
+                allowlist_root: match args.option("allowlist") {
+                    Some(path) => ctx.allowlist(path)?.root(),
+                    None => [0; 32],
+                },
+                phase: parse_number(required(args, "phase")?)?,
+                phase_user_limit: args.number_option("user-limit")?.unwrap_or(1),
+                phase_supply: args.number_option("phase-supply")?.unwrap_or_default(),
+            };
+            let collection = pda::collection(&platform, &name).0;
+            let mut accounts = vec![authority];
+            if command == "queue-collection-allowlist" {
+                accounts.push(AccountMeta::writable(platform));
+                accounts.push(AccountMeta::readonly(collection));
+                accounts.push(AccountMeta::writable(pda::collection_change(&collection).0));
+                accounts.push(system_program);
+            } else {
+                accounts.push(AccountMeta::readonly(platform));
+                accounts.push(AccountMeta::writable(collection));
+            }
+            accounts.append(&mut ctx.remaining);
+            (accounts, bytes_of(&allowlist_args).to_vec())
+        }
+        "execute-collection-change" | "cancel-collection-change" => {
+            let name: [u8; 64] = fixed(&args.next("name")?)?;
+            let collection = pda::collection(&platform, &name).0;
+            let payer = args.pubkey_option("payer")?.unwrap_or(ctx.authority);
+            let mut accounts = vec![
+                authority,
+                AccountMeta::writable(platform),
+                AccountMeta::writable(collection),
+                AccountMeta::writable(pda::collection_change(&collection).0),
+                AccountMeta::writable(payer),
+            ];
+            if command == "execute-collection-change" {
+                accounts.push(system_program);
+                accounts.extend(ctx.role(ROLE_FEE_MANAGER));
+            } else {
+                accounts[2] = AccountMeta::readonly(collection);
+                accounts.append(&mut ctx.remaining);
+            }
+            (accounts, Vec::new())
+        }
+        "set-pause-flags" => {
+            let pause_args = SetPauseFlagsArgs {
+                paused: pause_flags(&args.next("flags")?)?,
+            };
+            let mut accounts = vec![
+                AccountMeta::signer(ctx.authority, false),
+                AccountMeta::writable(platform),
+            ];
+            accounts.append(&mut ctx.remaining);
+            (accounts, bytes_of(&pause_args).to_vec())
+        }
+        "set-user-tier" => {
+            let user = ctx.user(args.next_number("user uuid")?);
+            let tier_args = SetUserTierArgs {
+                tier_level: args.next_number("tier level")?,
+            };
+            let mut accounts = vec![
+                AccountMeta::signer(ctx.authority, false),
+                AccountMeta::readonly(platform),
+                AccountMeta::writable(user),
+            ];
+            accounts.extend(ctx.role(ROLE_FEE_MANAGER));
+            (accounts, bytes_of(&tier_args).to_vec())
+        }
+        "rotate-administrator" => {
+            let mut accounts = vec![
+                authority,
+                AccountMeta::writable(platform),
+                AccountMeta::signer(args.next_pubkey("new administrator")?, false),
+            ];
+            accounts.append(&mut ctx.remaining);
+            (accounts, Vec::new())
+        }
+        "grant-role" | "revoke-role" => {
+            let role_args = RoleArgs {
+                role_id: role_id(&args.next("role")?)?,
+                grantee: args.next_pubkey("grantee")?.to_bytes(),
+            };
+            let role = pda::role(&platform, role_args.role_id, &role_args.grantee.into()).0;
+            let mut accounts = vec![
+                authority,
+                AccountMeta::writable(platform),
+                AccountMeta::writable(role),
+            ];
+            if command == "revoke-role" {
+                accounts.append(&mut ctx.remaining);
+                (accounts, Vec::new())
+            } else {
+                accounts.push(system_program);
+                accounts.append(&mut ctx.remaining);
+                (accounts, bytes_of(&role_args).to_vec())
+            }
+        }
+        "set-admin-multisig" => {
+            let threshold = args.next_number("threshold")?;
+            let signers = args.rest();
+            if signers.len() > MAX_ADMIN_SIGNERS {
+                return Err(format!("at most {} signers", MAX_ADMIN_SIGNERS));
+            }
+            let mut multisig_args = UniPinoNft::instructions::AdminMultisigArgs {
+                threshold,
+                signer_count: signers.len() as u8,
+                signers: [[0; 32]; MAX_ADMIN_SIGNERS],
+            };
+            for (slot, signer) in multisig_args.signers.iter_mut().zip(&signers) {
+                *slot = parse_pubkey(signer)?.to_bytes();
+            }
+
+            let mut accounts = vec![
+                authority,
+                AccountMeta::writable(platform),
+                AccountMeta::writable(pda::admin_multisig(&platform).0),
+                system_program,
+            ];
+            accounts.append(&mut ctx.remaining);
+            (accounts, bytes_of(&multisig_args).to_vec())
+        }
+        "queue-config-change" => {
+            let update_args = platform_args(args)?;
+            let mut accounts = vec![
+                authority,
+                AccountMeta::readonly(platform),
+                AccountMeta::writable(pda::pending_config(&platform).0),
+                system_program,
+            ];
+            accounts.append(&mut ctx.remaining);
+            (accounts, bytes_of(&update_args).to_vec())
+        }
+        "execute-config-change" => {
+            let mut accounts = vec![
+                authority,
+                AccountMeta::writable(platform),
+                AccountMeta::writable(pda::pending_config(&platform).0),
+                system_program,
+            ];
+            accounts.append(&mut ctx.remaining);
+            (accounts, Vec::new())
+        }
+        "cancel-config-change" => {
+            let mut accounts = vec![
+                authority,
+                AccountMeta::readonly(platform),
+                AccountMeta::writable(pda::pending_config(&platform).0),
+            ];
+            accounts.append(&mut ctx.remaining);
+            (accounts, Vec::new())
+        }
+        "close-user" => {
+            let user_uuid: u128 = args.next_number("user uuid")?;
+            let mut accounts = vec![
+                authority,
+                AccountMeta::writable(platform),
+                AccountMeta::writable(ctx.user(user_uuid)),
+            ];
+            accounts.append(&mut ctx.remaining);
+            (accounts, Vec::new())
+        }
+        "close-platform" => {
+            let mut accounts = vec![
+                authority,
+                AccountMeta::writable(platform),
+                AccountMeta::writable(pda::admin_multisig(&platform).0),
+                AccountMeta::writable(pda::fee_schedule(&platform).0),
+                AccountMeta::writable(pda::pending_config(&platform).0),
+                AccountMeta::writable(pda::treasury(&platform).0),
+            ];
+            accounts.append(&mut ctx.remaining);
+            (accounts, Vec::new())
+        }
+        "migrate-account" => {
+            let account = args.next_pubkey("account")?;
+            let mut accounts = vec![
+                authority,
+                AccountMeta::readonly(platform),
+                AccountMeta::writable(account),
+                system_program,
+            ];
+            accounts.append(&mut ctx.remaining);
+            // NFT metadata names the user holding it and its mint
+            if let Some(user_uuid) = args.number_option::<u128>("user")? {
+                let user = ctx.user(user_uuid);
+                accounts.push(AccountMeta::readonly(user));
+                accounts.push(AccountMeta::readonly(held_mint(args, &user, user_uuid)?));
+            }
+            (accounts, Vec::new())
+        }
+        "link-user-wallet" | "unlink-user-wallet" => {
+            let user_uuid: u128 = args.next_number("user uuid")?;
+            // unlinking hands the user a new generation from the platform
+            let mut accounts = vec![
+                AccountMeta::signer(ctx.authority, false),
+                if command == "link-user-wallet" {
+                    AccountMeta::readonly(platform)
+                } else {
+                    AccountMeta::writable(platform)
+                },
+                AccountMeta::writable(ctx.user(user_uuid)),
+            ];
+            if command == "link-user-wallet" {
+                accounts.push(AccountMeta::signer(args.next_pubkey("wallet")?, false));
+            }
+            accounts.append(&mut ctx.remaining);
+            (accounts, Vec::new())
+        }
+        "create-session-key" | "revoke-session-key" => {
+            let user_uuid: u128 = args.next_number("user uuid")?;
+            let key = args.next_pubkey("key")?;
+            let user = ctx.user(user_uuid);
+            let mut accounts = vec![
+                authority,
+                AccountMeta::readonly(platform),
+                AccountMeta::writable(user),
+                AccountMeta::writable(pda::session(&user, &key).0),
+            ];
+            if command == "revoke-session-key" {
+                (accounts, Vec::new())
+            } else {
+                let session_args = SessionKeyArgs {
+                    key: key.to_bytes(),
+                    allowed_instructions: allowed_instructions(required(args, "allow")?)?,
+                    expiry_slot: parse_number(required(args, "expiry-slot")?)?,
+                };
+                accounts.push(system_program);
+                (accounts, bytes_of(&session_args).to_vec())
+            }
+        }
+        "approve-nft-delegate" | "revoke-nft-delegate" => {
+            let user_uuid: u128 = args.next_number("user uuid")?;
+            let user = ctx.user(user_uuid);
+            let mint = held_mint(args, &user, user_uuid)?;
+            let token_account = token_account(args, &user, &mint)?;
+
+            let mut accounts = vec![
+                AccountMeta::signer(ctx.authority, false),
+                AccountMeta::readonly(platform),
+                AccountMeta::readonly(user),
+                AccountMeta::readonly(mint),
+                AccountMeta::writable(pda::metadata(&mint).0),
+                AccountMeta::writable(token_account),
+            ];
+            if command == "approve-nft-delegate" {
+                accounts.push(AccountMeta::readonly(args.next_pubkey("delegate")?));
+            }
+            accounts.push(AccountMeta::readonly(pda::token_2022()));
+            if args.switch("session") {
+                accounts.push(AccountMeta::readonly(pda::session(&user, &ctx.authority).0));
+            }
+            (accounts, Vec::new())
+        }
+        "transfer-nft" => {
+            let from = ctx.user(args.next_number("sending user uuid")?);
+            let to = ctx.user(args.next_number("receiving user uuid")?);
+            let mint = args.next_pubkey("mint")?;
+
+            let mut accounts = vec![
+                AccountMeta::signer(ctx.authority, false),
+                AccountMeta::readonly(platform),
+                AccountMeta::writable(from),
+                AccountMeta::writable(to),
+                AccountMeta::readonly(mint),
+                AccountMeta::writable(pda::metadata(&mint).0),
+                AccountMeta::writable(token_account(args, &from, &mint)?),
+                AccountMeta::writable(
+                    args.pubkey_option("to-token-account")?
+                        .unwrap_or_else(|| pda::token_account(&to, &mint)),
+                ),
+                AccountMeta::readonly(pda::token_2022()),
+            ];
+            if args.switch("session") {
+                accounts.push(AccountMeta::readonly(pda::session(&from, &ctx.authority).0));
+            }
+            (accounts, Vec::new())
+        }
+        "verify-ownership" => {
+            let user_uuid: u128 = args.next_number("user uuid")?;
+            let user = ctx.user(user_uuid);
+            let mint = held_mint(args, &user, user_uuid)?;
+            let verify_args = VerifyOwnershipArgs {
+                holder: match args.option("holder") {
+                    Some(holder) => parse_pubkey(holder)?.to_bytes(),
+                    None => user.to_bytes(),
+                },
+                collection: fixed(args.option("collection").unwrap_or_default())?,
+                attribute: fixed(args.option("attribute").unwrap_or_default())?,
+            };
+            (
+                vec![
+                    AccountMeta::readonly(platform),
+                    AccountMeta::readonly(user),
+                    AccountMeta::readonly(mint),
+                    AccountMeta::readonly(pda::metadata(&mint).0),
+                    AccountMeta::readonly(token_account(
+                        args,
+                        &Pubkey::from(verify_args.holder),
+                        &mint,
+                    )?),
+                ],
+                bytes_of(&verify_args).to_vec(),
+            )
+        }
+        _ => unreachable!("every command has a builder"),
+    };
+
+    let mut instruction_data = vec![discriminator];
+    instruction_data.extend_from_slice(&data);
+    Ok(Instruction {
+        program_id: pda::program_id(),
+        accounts,
+        data: instruction_data,
+    })
+}
+
+fn required<'a>(args: &'a Args, name: &str) -> Result<&'a str, String> {
+    args.option(name)
+        .ok_or_else(|| format!("missing --{}", name))
+}
+
+/// NFT held by `user`: `--mint` for one it received, otherwise the one minted for it
+fn held_mint(args: &Args, user: &Pubkey, user_uuid: u128) -> Result<Pubkey, String> {
+    Ok(args
+        .pubkey_option("mint")?
+        .unwrap_or_else(|| pda::mint(user, user_uuid).0))
+}
+
+/// `--token-account`, otherwise the associated token account of `owner` for `mint`
+fn token_account(args: &Args, owner: &Pubkey, mint: &Pubkey) -> Result<Pubkey, String> {
+    Ok(args
+        .pubkey_option("token-account")?
+        .unwrap_or_else(|| pda::token_account(owner, mint)))
+}
+
+/// `minter`, `metadata-editor`, `fee-manager`, `program-minter` or a numeric role id
+pub fn role_id(text: &str) -> Result<u8, String> {
+    match text {
+        "minter" => Ok(ROLE_MINTER),
+        "metadata-editor" => Ok(ROLE_METADATA_EDITOR),
+        "fee-manager" => Ok(ROLE_FEE_MANAGER),
+        "program-minter" => Ok(ROLE_PROGRAM_MINTER),
+        _ => parse_number(text),
+    }
+}
+
+/// Comma separated `mint`, `metadata`, `burn`, `transfer`, `sale`, `all` or `none`
+fn pause_flags(text: &str) -> Result<u8, String> {
+    text.split(',').try_fold(0, |flags, name| {
+        Ok(flags
+            | match name {
+                "mint" => platform_state::PAUSE_MINT,
+                "metadata" => platform_state::PAUSE_METADATA_UPDATE,
+                "burn" => platform_state::PAUSE_BURN,
+                "transfer" => platform_state::PAUSE_TRANSFER,
+                "sale" => platform_state::PAUSE_SALE,
+                "all" => platform_state::PAUSE_ALL,
+                "none" => 0,
+                _ => return Err(format!("unknown pause flag `{}`", name)),
+            })
+    })
+}
+
+/// Comma separated subcommand names a session key may sign
+fn allowed_instructions(text: &str) -> Result<u64, String> {
+    text.split(',').try_fold(0, |mask, name| {
+        let &(_, discriminator) = COMMANDS
+            .iter()
+            .find(|(command, _)| *command == name)
+            .ok_or_else(|| format!("unknown instruction `{}`", name))?;
+        Ok(mask | 1 << discriminator)
+    })
+}
+
+/// `UpdatePlatformArgs` from the config options shared by update and queue
+fn collection_config_args(args: &mut Args) -> Result<CollectionConfigArgs, String> {
+    let name: [u8; 64] = fixed(&args.next("name")?)?;
+    let mint_fee: Option<u64> = args.number_option("mint-fee")?;
+    Ok(CollectionConfigArgs {
+        name,
+        mint_fee: mint_fee.unwrap_or_default(),
+        has_mint_fee: mint_fee.is_some() as u8,
+    })
+}
+
+fn platform_args(args: &Args) -> Result<UpdatePlatformArgs, String> {
+    let mut update_args = UpdatePlatformArgs::zeroed();
+    update_args.mint_fee = parse_number(required(args, "mint-fee")?)?;
+
+    if let Some(fee_receiver) = args.pubkey_option("fee-receiver")? {
+        update_args.is_receiver_valid = 1;
+        update_args.fee_receiver = fee_receiver.to_bytes();
+    }
+    if let Some(fee_mint) = args.option("fee-mint") {
+        update_args.is_fee_mint_valid = 1;
+        if fee_mint != "lamports" {
+            update_args.fee_mint = parse_pubkey(fee_mint)?.to_bytes();
+        }
+    }
+
+    let shares = args.options("fee-share");
+    if !shares.is_empty() || args.switch("clear-fee-shares") {
+        update_args.is_fee_schedule_valid = 1;
+        update_args.fee_share_count = fill(
+            &mut update_args.fee_shares,
+            MAX_FEE_SHARES,
+            &shares,
+            |receiver, bps| {
+                Ok(FeeShare {
+                    receiver: parse_pubkey(receiver)?.to_bytes(),
+                    bps,
+                })
+            },
+        )?;
+    }
+
+    if let Some(treasury) = args.option("treasury") {
+        update_args.is_treasury_valid = 1;
+        update_args.treasury_enabled = match treasury {
+            "on" => 1,
+            "off" => 0,
+            _ => return Err("--treasury takes `on` or `off`".to_string()),
+        };
+        update_args.treasury_max_withdrawal =
+            args.number_option("max-withdrawal")?.unwrap_or_default();
+    }
+
+    let tiers = args.options("fee-tier");
+    if !tiers.is_empty() || args.switch("clear-fee-tiers") {
+        update_args.is_fee_tiers_valid = 1;
+        update_args.fee_tier_count = fill(
+            &mut update_args.fee_tiers,
+            MAX_FEE_TIERS,
+            &tiers,
+            |level, bps| {
+                Ok(FeeTier {
+                    min_level: parse_number(level)?,
+                    discount_bps: bps,
+                })
+            },
+        )?;
+    }
+
+    if let Some(config_delay) = args.number_option("config-delay")? {
+        update_args.is_config_delay_valid = 1;
+        update_args.config_delay = config_delay;
+    }
+
+    Ok(update_args)
+}
+
+/// Fill `slots` from `<key>:<bps>` values, returning how many were set
+fn fill<T: Pod>(
+    slots: &mut [T],
+    max: usize,
+    values: &[&str],
+    parse: impl Fn(&str, u16) -> Result<T, String>,
+) -> Result<u8, String> {
+    if values.len() > max {
+        return Err(format!("at most {} entries", max));
+    }
+    for (slot, value) in slots.iter_mut().zip(values) {
+        let (key, bps) = value
+            .split_once(':')
+            .ok_or_else(|| format!("expected `<value>:<bps>`, got `{}`", value))?;
+        *slot = parse(key, parse_number(bps)?)?;
+    }
+    Ok(values.len() as u8)
+}
@@ -0,0 +1,202 @@
+use UniPinoNft::state::admin_multisig::AdminMultisig;
+use UniPinoNft::state::claim::Claim;
+use UniPinoNft::state::collection::Collection;
+use UniPinoNft::state::fee_schedule::FeeSchedule;
+use UniPinoNft::state::nft_meta::NftMeta;
+use UniPinoNft::state::pending_collection::PendingCollectionChange;
+use UniPinoNft::state::pending_config::PendingConfig;
+use UniPinoNft::state::platform::Platform;
+use UniPinoNft::state::program_state::ProgramState;
+use UniPinoNft::state::role::Role;
+use UniPinoNft::state::session_key::SessionKey;
+use UniPinoNft::state::user::User;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use bytemuck::pod_read_unaligned;
+use solana_pubkey::Pubkey;
+
+use crate::args::{Args, parse_pubkey};
+use crate::rpc::Rpc;
+
+/// `unipino inspect <address>` or `unipino inspect --data <base64>`, decodes a program account
+pub fn run(args: &mut Args, rpc: Option<&Rpc>) -> Result<(), String> {
+    let data = match args.option("data") {
+        Some(data) => BASE64
+            .decode(data)
+            .map_err(|err| format!("--data: {}", err))?,
+        None => {
+            let address = parse_pubkey(&args.next("address")?)?;
+            let rpc = rpc.ok_or("inspecting an address needs RPC access, pass --data offline")?;
+            rpc.account_data(&address)?
+                .ok_or_else(|| format!("account {} does not exist", address))?
+        }
+    };
+    args.finish()?;
+
+    print_account(&data)
+}
+
+fn print_account(data: &[u8]) -> Result<(), String> {
+    if let Ok(platform) = Platform::read(data) {
+        println!("Platform (version {})", platform.version);
+        field("administrator", key(&platform.administrator));
+        field("seed_key", key(&platform.seed_key));
+        field("fee_receiver", key(&platform.fee_receiver));
+        field("fee_mint", key(&platform.fee_mint));
+        field("mint_fee", platform.mint_fee);
+        field("total_users", platform.total_users);
+        field("total_mints", platform.total_mints);
+        field("bump", platform.bump);
+        field("fee_schedule_enabled", platform.fee_schedule_enabled);
+        field("treasury_enabled", platform.treasury_enabled);
+        field(
+            "treasury_lamports_collected",
+            platform.treasury_lamports_collected,
+        );
+        field(
+            "treasury_lamports_withdrawn",
+            platform.treasury_lamports_withdrawn,
+        );
+        field(
+            "treasury_tokens_collected",
+            platform.treasury_tokens_collected,
+        );
+        field(
+            "treasury_tokens_withdrawn",
+            platform.treasury_tokens_withdrawn,
+        );
+        field("treasury_max_withdrawal", platform.treasury_max_withdrawal);
+        field("role_count", platform.role_count);
+        field("collection_count", platform.collection_count);
+        field("collection_change_count", platform.collection_change_count);
+        field("last_generation", platform.last_generation);
+        for tier in platform.fee_tiers() {
+            field(
+                "fee_tier",
+                format!("level {}: {} bps", { tier.min_level }, {
+                    tier.discount_bps
+                }),
+            );
+        }
+        field("paused", format!("{:#07b}", platform.paused));
+        field("multisig_enabled", platform.multisig_enabled);
+        field("config_delay", platform.config_delay);
+    } else if let Ok(user) = User::try_from_bytes(data) {
+        println!("User (version {})", user.version);
+        field("user_uuid", user.user_uuid);
+        field("platform", key(&user.owner));
+        field("nft_count", user.nft_count);
+        field("collection_count", user.collection_count);
+        field("bump", user.bump);
+        field("authority", key(&user.authority));
+        field("nonce", user.nonce);
+        field("session_count", user.session_count);
+        field("claim_count", user.claim_count);
+        field("generation", user.generation);
+        field("tier_level", user.tier_level);
+    } else if let Ok(version) = NftMeta::version_of(data) {
+        // legacy accounts are read with a zeroed tail
+        let mut bytes = [0; NftMeta::INIT_SPACE];
+        bytes[..data.len()].copy_from_slice(data);
+        let metadata: NftMeta = pod_read_unaligned(&bytes);
+
+        println!("NftMeta (version {})", version);
+        field("name", text(&metadata.name));
+        field("collection", text(&metadata.collection));
+        field("uri", text(&metadata.uri));
+        field("description", text(&metadata.description));
+        field("delegate", key(&metadata.delegate));
+        field("holder", key(&metadata.holder));
+        field("attribute", text(&metadata.attribute));
+    } else if let Ok(collection) = Collection::try_from_bytes(data) {
+        println!("Collection");
+        field("platform", key(&collection.platform));
+        field("name", text(&collection.name));
+        field("mint_fee", collection.mint_fee);
+        field("has_mint_fee", collection.has_mint_fee);
+        field("bump", collection.bump);
+        field("allowlist_root", key(&collection.allowlist_root));
+        field("phase", collection.phase);
+        field("phase_user_limit", collection.phase_user_limit);
+        field("phase_supply", collection.phase_supply);
+        field("phase_minted", collection.phase_minted);
+    } else if let Ok(role) = Role::try_from_bytes(data) {
+        println!("Role");
+        field("platform", key(&role.platform));
+        field("grantee", key(&role.grantee));
+        field("role_id", role.role_id);
+        field("bump", role.bump);
+        field("authority_bump", role.authority_bump);
+    } else if let Ok(fee_schedule) = FeeSchedule::try_from_bytes(data) {
+        println!("FeeSchedule");
+        field("platform", key(&fee_schedule.platform));
+        for share in fee_schedule.shares() {
+            field(
+                "share",
+                format!("{}: {} bps", key(&share.receiver), { share.bps }),
+            );
+        }
+        field("bump", fee_schedule.bump);
+    } else if let Ok(multisig) = AdminMultisig::try_from_bytes(data) {
+        println!("AdminMultisig");
+        field("platform", key(&multisig.platform));
+        field("threshold", multisig.threshold);
+        for signer in multisig.signers() {
+            field("signer", key(signer));
+        }
+        field("bump", multisig.bump);
+    } else if let Ok(pending) = PendingConfig::try_from_bytes(data) {
+        println!("PendingConfig");
+        field("platform", key(&pending.platform));
+        field("eta", pending.eta);
+        field("mint_fee", pending.args.mint_fee);
+        field("bump", pending.bump);
+    } else if let Ok(pending) = PendingCollectionChange::try_from_bytes(data) {
+        println!("PendingCollectionChange");
+        field("platform", key(&pending.platform));
+        field("collection", key(&pending.collection));
+        field("payer", key(&pending.payer));
+        field("eta", pending.eta);
+        field("kind", pending.kind);
+        field("bump", pending.bump);
+    } else if let Ok(session) = SessionKey::try_from_bytes(data) {
+        println!("SessionKey");
+        field("user", key(&session.user));
+        field("key", key(&session.key));
+        field(
+            "allowed_instructions",
+            format!("{:#x}", { session.allowed_instructions }),
+        );
+        field("expiry_slot", session.expiry_slot);
+        field("generation", session.generation);
+        field("bump", session.bump);
+    } else if let Ok(claim) = Claim::try_from_bytes(data) {
+        println!("Claim");
+        field("collection", key(&claim.collection));
+        field("user", key(&claim.user));
+        field("phase", claim.phase);
+        field("count", claim.count);
+        field("bump", claim.bump);
+    } else {
+        return Err("not an account of this program".to_string());
+    }
+
+    Ok(())
+}
+
+fn field(name: &str, value: impl std::fmt::Display) {
+    println!("  {}: {}", name, value);
+}
+
+fn key(bytes: &[u8; 32]) -> Pubkey {
+    Pubkey::new_from_array(*bytes)
+}
+
+/// Fixed size text field up to its first NUL byte
+fn text(field: &[u8]) -> String {
+    let end = field
+        .iter()
+        .position(|&byte| byte == 0)
+        .unwrap_or(field.len());
+    String::from_utf8_lossy(&field[..end]).into_owned()
+}
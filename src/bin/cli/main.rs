@@ -0,0 +1,265 @@
+//! Administrator CLI: builds and sends the program instructions, prints PDA addresses
+//! and decodes program accounts. With `--offline` nothing is signed or sent, the
+//! serialized unsigned transaction is printed for signing on an air-gapped machine.
+//!
+//! Usage: `unipino <command> [arguments] [options]`, see `USAGE`.
+
+mod allowlist;
+mod args;
+mod commands;
+mod inspect;
+mod pda;
+mod rpc;
+mod transaction;
+
+use std::process::ExitCode;
+use std::{env, fs};
+
+use UniPinoNft::error::ERROR_TABLE;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use ed25519_dalek::{Signer, SigningKey};
+use solana_pubkey::Pubkey;
+
+use args::{Args, parse_pubkey};
+use commands::Context;
+use rpc::Rpc;
+use transaction::{AccountMeta, Message, serialize_transaction};
+
+const USAGE: &str = "usage: unipino <command> [arguments] [options]
+
+commands:
+  pda <kind> <arguments>                  print a PDA address and bump, kinds:
+                                          platform <admin>, user|mint|metadata <admin> <uuid>,
+                                          fee-schedule|treasury|admin-multisig|pending-config <platform>,
+                                          collection <platform> <name>, role <platform> <role> <grantee>,
+                                          session <user> <key>, minter-authority <platform> <program>,
+                                          claim <collection> <user>, collection-change <collection>
+  inspect <address> | --data <base64>     decode a program account
+  error <code>                            explain a custom error code, decimal or 0x hex
+  init-platform
+  update-platform-config                  --mint-fee <n> [config options]
+  queue-config-change                     --mint-fee <n> [config options]
+  execute-config-change
+  cancel-config-change
+  create-user <uuid>
+  close-user <uuid>                       --remaining <address>:w per allowlist claim PDA, after approvals
+  mint-nft <uuid>                         --name <s> --uri <s> [--description <s>] [--collection <s>]
+                                          [--attribute <s>] [--fee-receiver <address>] [--allowlist <file>]
+  update-metadata <uuid>                  --name <s> --uri <s> [--description <s>] [--collection <s>]
+                                          [--attribute <s>] [--mint <address>]
+  burn-nft <uuid>                         [--mint <address>] [--session]
+  withdraw-treasury <amount> <destination>
+  set-collection-config <name>            [--mint-fee <n>]
+  set-collection-allowlist <name>         --phase <n> [--allowlist <file>] [--user-limit <n>]
+                                          [--phase-supply <n>]
+  queue-collection-config <name>          [--mint-fee <n>]
+  queue-collection-allowlist <name>       --phase <n> [--allowlist <file>] [--user-limit <n>]
+                                          [--phase-supply <n>]
+  execute-collection-change <name>        [--payer <address>]
+  cancel-collection-change <name>         [--payer <address>]
+  set-pause-flags <flag,...>              mint, metadata, burn, transfer, sale, all or none
+  grant-role <role> <grantee>             minter, metadata-editor, fee-manager,
+                                          program-minter (grantee is a program id) or a role id
+  revoke-role <role> <grantee>
+  set-admin-multisig <threshold> [<signer>...]
+  close-platform                          --remaining <address>:w per role, collection and queued
+                                          collection change PDA
+  migrate-account <address>               [--user <uuid> [--mint <address>]] for NFT metadata
+  link-user-wallet <uuid> <wallet>
+  unlink-user-wallet <uuid>
+  create-session-key <uuid> <key>         --allow <command,...> --expiry-slot <slot>
+  revoke-session-key <uuid> <key>
+  set-user-tier <uuid> <level>            fee tier level, 0 for none
+  rotate-administrator <new admin>        the new administrator signs with --signer
+  approve-nft-delegate <uuid> <delegate>  [--mint <address>] [--token-account <address>] [--session]
+  revoke-nft-delegate <uuid>              [--mint <address>] [--token-account <address>] [--session]
+  transfer-nft <uuid> <to uuid> <mint>    [--token-account <address>] [--to-token-account <address>]
+                                          [--session]
+  verify-ownership <uuid>                 [--mint <address>] [--token-account <address>]
+                                          [--holder <address>] [--collection <s>] [--attribute <s>]
+
+config options:
+  --fee-receiver <address>  --fee-mint <address|lamports>  --fee-share <address>:<bps> (repeatable)
+  --clear-fee-shares  --treasury on|off  --max-withdrawal <n>  --fee-tier <level>:<bps> (repeatable)
+  --clear-fee-tiers  --config-delay <seconds>
+
+--mint defaults to the NFT minted for the user and token accounts to the associated token
+account of their owner, which has to exist before an NFT is transferred to it
+
+an allowlist file lists user uuids separated by whitespace, minting takes the same file
+to prove the user is on it
+
+options:
+  --url <url>               JSON-RPC endpoint, defaults to http://127.0.0.1:8899
+  --keypair <path>          signing keypair, defaults to ~/.config/solana/id.json
+  --authority <address>     signer address when offline, defaults to the keypair address
+  --admin <address>         platform administrator when the signer is a role or wallet holder
+  --platform-seed <address> administrator that created the platform, once it was rotated
+  --signer <path>           additional keypair signing the transaction (repeatable)
+  --remaining <address>[:s][:w]  extra signer/writable account, such as multisig approvers (repeatable)
+  --offline                 print the unsigned transaction instead of sending it
+  --blockhash <hash>        recent blockhash, required with --offline";
+
+fn main() -> ExitCode {
+    let mut args = match Args::parse(env::args().skip(1)) {
+        Ok(args) => args,
+        Err(err) => return fail(&err),
+    };
+
+    match run(&mut args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => fail(&err),
+    }
+}
+
+fn fail(err: &str) -> ExitCode {
+    eprintln!("{}\n\n{}", err, USAGE);
+    ExitCode::FAILURE
+}
+
+fn run(args: &mut Args) -> Result<(), String> {
+    let offline = args.switch("offline");
+    let rpc = (!offline).then(|| Rpc::new(args.option("url").unwrap_or("http://127.0.0.1:8899")));
+
+    let command = args.next("command")?;
+    match command.as_str() {
+        "pda" => return pda::run(args),
+        "inspect" => return inspect::run(args, rpc.as_ref()),
+        "error" => return explain_error(args),
+        _ if !commands::COMMANDS.iter().any(|(name, _)| *name == command) => {
+            return Err(format!("unknown command `{}`", command));
+        }
+        _ => {}
+    }
+
+    let keypair = match (offline, args.pubkey_option("authority")?) {
+        (true, Some(_)) => None,
+        _ => Some(read_keypair(&keypair_path(args))?),
+    };
+    let authority = match &keypair {
+        Some(keypair) => Pubkey::new_from_array(keypair.verifying_key().to_bytes()),
+        None => args.pubkey_option("authority")?.unwrap_or_default(),
+    };
+    let administrator = args.pubkey_option("admin")?.unwrap_or(authority);
+    let seed_key = args
+        .pubkey_option("platform-seed")?
+        .unwrap_or(administrator);
+    let (platform, platform_bump) = pda::platform(&seed_key);
+
+    let mut ctx = Context {
+        authority,
+        administrator,
+        platform,
+        platform_bump,
+        remaining: args
+            .options("remaining")
+            .into_iter()
+            .map(remaining_account)
+            .collect::<Result<_, _>>()?,
+        rpc,
+    };
+    let instruction = commands::build(&command, args, &mut ctx)?;
+    args.finish()?;
+
+    if offline {
+        let blockhash = args
+            .option("blockhash")
+            .ok_or("--offline needs --blockhash")?;
+        let message = Message::new(
+            &authority,
+            &[instruction],
+            parse_pubkey(blockhash)?.to_bytes(),
+        );
+        let message_bytes = message.serialize();
+        let unsigned = vec![[0; 64]; message.signers().len()];
+
+        for signer in message.signers() {
+            println!("signer: {}", signer);
+        }
+        println!("message: {}", BASE64.encode(&message_bytes));
+        println!(
+            "transaction: {}",
+            BASE64.encode(serialize_transaction(&unsigned, &message_bytes))
+        );
+        return Ok(());
+    }
+
+    let mut keypairs: Vec<SigningKey> = keypair.into_iter().collect();
+    for path in args.options("signer") {
+        keypairs.push(read_keypair(path)?);
+    }
+
+    let rpc = ctx.rpc.as_ref().expect("online mode has an RPC client");
+    let message = Message::new(&authority, &[instruction], rpc.latest_blockhash()?);
+    let message_bytes = message.serialize();
+    let signatures = message
+        .signers()
+        .iter()
+        .map(|signer| {
+            keypairs
+                .iter()
+                .find(|keypair| keypair.verifying_key().as_bytes() == signer.as_ref())
+                .map(|keypair| keypair.sign(&message_bytes).to_bytes())
+                .ok_or_else(|| format!("no keypair for signer {}, pass it with --signer", signer))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let signature = rpc.send_transaction(&serialize_transaction(&signatures, &message_bytes))?;
+    println!("{}", signature);
+    Ok(())
+}
+
+/// `unipino error <code>`, the name and message of a `ProgramError::Custom` code
+fn explain_error(args: &mut Args) -> Result<(), String> {
+    let text = args.next("code")?;
+    args.finish()?;
+
+    let code = match text.strip_prefix("0x") {
+        Some(hex) => u32::from_str_radix(hex, 16),
+        None => text.parse(),
+    }
+    .map_err(|err| format!("invalid error code `{}`: {}", text, err))?;
+    let (_, name, message) = ERROR_TABLE
+        .iter()
+        .find(|(error_code, _, _)| *error_code == code)
+        .ok_or_else(|| format!("{} is not an error code of this program", code))?;
+
+    println!("{} {}: {}", code, name, message);
+    Ok(())
+}
+
+fn keypair_path(args: &Args) -> String {
+    match args.option("keypair") {
+        Some(path) => path.to_string(),
+        None => format!(
+            "{}/.config/solana/id.json",
+            env::var("HOME").unwrap_or_default()
+        ),
+    }
+}
+
+/// Keypair file in the Solana CLI format, a JSON array of the 64 keypair bytes
+fn read_keypair(path: &str) -> Result<SigningKey, String> {
+    let text = fs::read_to_string(path).map_err(|err| format!("{}: {}", path, err))?;
+    let bytes: Vec<u8> = serde_json::from_str(&text).map_err(|err| format!("{}: {}", path, err))?;
+    let bytes: [u8; 64] = bytes
+        .try_into()
+        .map_err(|_| format!("{}: expected 64 keypair bytes", path))?;
+    SigningKey::from_keypair_bytes(&bytes).map_err(|err| format!("{}: {}", path, err))
+}
+
+/// `<address>[:s][:w]`, an extra account with the signer and writable flags it needs
+fn remaining_account(text: &str) -> Result<AccountMeta, String> {
+    let mut parts = text.split(':');
+    let pubkey = parse_pubkey(parts.next().unwrap_or_default())?;
+    let mut meta = AccountMeta::readonly(pubkey);
+    for flag in parts {
+        match flag {
+            "s" => meta.is_signer = true,
+            "w" => meta.is_writable = true,
+            _ => return Err(format!("unknown account flag `{}` in `{}`", flag, text)),
+        }
+    }
+    Ok(meta)
+}
@@ -0,0 +1,185 @@
+use UniPinoNft::cpi::MINTER_AUTHORITY_TOKEN;
+use UniPinoNft::instructions::{
+    ID,
+    allowlist::CLAIM_TOKEN,
+    collection::COLLECTION_TOKEN,
+    fee::FEE_SCHEDULE_TOKEN,
+    multisig::ADMIN_MULTISIG_TOKEN,
+    nft::ASSOCIATED_TOKEN_PROGRAM_ID,
+    platform::PLATFORM_TOKEN,
+    role::ROLE_TOKEN,
+    session::SESSION_TOKEN,
+    timelock::{COLLECTION_CHANGE_TOKEN, CONFIG_CHANGE_TOKEN},
+    treasury::TREASURY_TOKEN,
+    user::USER_TOKEN,
+};
+use solana_pubkey::Pubkey;
+
+use crate::args::{Args, fixed};
+
+pub fn program_id() -> Pubkey {
+    Pubkey::new_from_array(ID)
+}
+
+pub fn token_2022() -> Pubkey {
+    Pubkey::new_from_array(pinocchio_token_2022::ID)
+}
+
+pub fn associated_token_program() -> Pubkey {
+    Pubkey::new_from_array(ASSOCIATED_TOKEN_PROGRAM_ID)
+}
+
+pub fn platform(administrator: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PLATFORM_TOKEN, administrator.as_ref()], &program_id())
+}
+
+pub fn user(platform: &Pubkey, platform_bump: u8, user_uuid: u128) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            USER_TOKEN,
+            user_uuid.to_string().as_bytes(),
+            platform.as_ref(),
+            &[platform_bump],
+        ],
+        &program_id(),
+    )
+}
+
+pub fn mint(user: &Pubkey, user_uuid: u128) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            &user_uuid.to_le_bytes(),
+            user.as_ref(),
+            token_2022().as_ref(),
+        ],
+        &program_id(),
+    )
+}
+
+pub fn metadata(mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"metadata", mint.as_ref(), token_2022().as_ref()],
+        &program_id(),
+    )
+}
+
+/// Token-2022 associated token account of `owner` for `mint`
+pub fn token_account(owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[owner.as_ref(), token_2022().as_ref(), mint.as_ref()],
+        &associated_token_program(),
+    )
+    .0
+}
+
+pub fn fee_schedule(platform: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[FEE_SCHEDULE_TOKEN, platform.as_ref()], &program_id())
+}
+
+pub fn treasury(platform: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[TREASURY_TOKEN, platform.as_ref()], &program_id())
+}
+
+pub fn collection(platform: &Pubkey, name: &[u8; 64]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            COLLECTION_TOKEN,
+            platform.as_ref(),
+            &name[..32],
+            &name[32..],
+        ],
+        &program_id(),
+    )
+}
+
+pub fn role(platform: &Pubkey, role_id: u8, grantee: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[ROLE_TOKEN, platform.as_ref(), &[role_id], grantee.as_ref()],
+        &program_id(),
+    )
+}
+
+pub fn admin_multisig(platform: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ADMIN_MULTISIG_TOKEN, platform.as_ref()], &program_id())
+}
+
+pub fn pending_config(platform: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CONFIG_CHANGE_TOKEN, platform.as_ref()], &program_id())
+}
+
+pub fn collection_change(collection: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[COLLECTION_CHANGE_TOKEN, collection.as_ref()],
+        &program_id(),
+    )
+}
+
+pub fn session(user: &Pubkey, key: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[SESSION_TOKEN, user.as_ref(), key.as_ref()], &program_id())
+}
+
+pub fn claim(collection: &Pubkey, user: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[CLAIM_TOKEN, collection.as_ref(), user.as_ref()],
+        &program_id(),
+    )
+}
+
+/// PDA a partner `program` signs `MintNft` with under a `program-minter` role
+pub fn minter_authority(platform: &Pubkey, program: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[MINTER_AUTHORITY_TOKEN, platform.as_ref()], program)
+}
+
+/// `unipino pda <kind> ...`, prints the address and bump of one PDA
+pub fn run(args: &mut Args) -> Result<(), String> {
+    let kind = args.next("kind")?;
+    let (address, bump) = match kind.as_str() {
+        "platform" => platform(&args.next_pubkey("administrator")?),
+        "user" => {
+            let (platform, platform_bump) = platform(&args.next_pubkey("administrator")?);
+            user(&platform, platform_bump, args.next_number("user uuid")?)
+        }
+        "mint" | "metadata" => {
+            let (platform, platform_bump) = platform(&args.next_pubkey("administrator")?);
+            let user_uuid = args.next_number("user uuid")?;
+            let (user, _) = user(&platform, platform_bump, user_uuid);
+            let (mint, mint_bump) = mint(&user, user_uuid);
+            if kind == "mint" {
+                (mint, mint_bump)
+            } else {
+                metadata(&mint)
+            }
+        }
+        "fee-schedule" => fee_schedule(&args.next_pubkey("platform")?),
+        "treasury" => treasury(&args.next_pubkey("platform")?),
+        "collection" => {
+            let platform = args.next_pubkey("platform")?;
+            collection(&platform, &fixed(&args.next("name")?)?)
+        }
+        "role" => {
+            let platform = args.next_pubkey("platform")?;
+            let role_id = crate::commands::role_id(&args.next("role")?)?;
+            role(&platform, role_id, &args.next_pubkey("grantee")?)
+        }
+        "minter-authority" => {
+            let platform = args.next_pubkey("platform")?;
+            minter_authority(&platform, &args.next_pubkey("program")?)
+        }
+        "admin-multisig" => admin_multisig(&args.next_pubkey("platform")?),
+        "pending-config" => pending_config(&args.next_pubkey("platform")?),
+        "collection-change" => collection_change(&args.next_pubkey("collection")?),
+        "session" => {
+            let user = args.next_pubkey("user")?;
+            session(&user, &args.next_pubkey("key")?)
+        }
+        "claim" => {
+            let collection = args.next_pubkey("collection")?;
+            claim(&collection, &args.next_pubkey("user")?)
+        }
+        _ => return Err(format!("unknown PDA kind `{}`", kind)),
+    };
+    args.finish()?;
+
+    println!("{} {}", address, bump);
+    Ok(())
+}
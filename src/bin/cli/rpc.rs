@@ -0,0 +1,80 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use serde_json::{Value, json};
+use solana_pubkey::Pubkey;
+
+use crate::args::parse_pubkey;
+
+/// Minimal JSON-RPC client for the few methods the CLI needs
+pub struct Rpc {
+    url: String,
+}
+
+impl Rpc {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+        }
+    }
+
+    fn call(&self, method: &str, params: Value) -> Result<Value, String> {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        });
+        let body = ureq::post(&self.url)
+            .set("Content-Type", "application/json")
+            .send_string(&request.to_string())
+            .map_err(|err| format!("{}: {}", self.url, err))?
+            .into_string()
+            .map_err(|err| format!("{}: {}", self.url, err))?;
+
+        let mut response: Value =
+            serde_json::from_str(&body).map_err(|err| format!("{}: {}", method, err))?;
+        if let Some(error) = response.get("error") {
+            return Err(format!("{}: {}", method, error));
+        }
+        Ok(response["result"].take())
+    }
+
+    pub fn latest_blockhash(&self) -> Result<[u8; 32], String> {
+        let result = self.call("getLatestBlockhash", json!([]))?;
+        let blockhash = result["value"]["blockhash"]
+            .as_str()
+            .ok_or("getLatestBlockhash: missing blockhash")?;
+        Ok(parse_pubkey(blockhash)?.to_bytes())
+    }
+
+    /// Data of an account, `None` when it does not exist
+    pub fn account_data(&self, address: &Pubkey) -> Result<Option<Vec<u8>>, String> {
+        let result = self.call(
+            "getAccountInfo",
+            json!([address.to_string(), { "encoding": "base64" }]),
+        )?;
+        if result["value"].is_null() {
+            return Ok(None);
+        }
+
+        let data = result["value"]["data"][0]
+            .as_str()
+            .ok_or("getAccountInfo: missing data")?;
+        BASE64
+            .decode(data)
+            .map(Some)
+            .map_err(|err| format!("getAccountInfo: {}", err))
+    }
+
+    /// Submit a signed transaction, returning its signature
+    pub fn send_transaction(&self, transaction: &[u8]) -> Result<String, String> {
+        let result = self.call(
+            "sendTransaction",
+            json!([BASE64.encode(transaction), { "encoding": "base64" }]),
+        )?;
+        result
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| "sendTransaction: missing signature".to_string())
+    }
+}
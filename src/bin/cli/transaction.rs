@@ -0,0 +1,176 @@
+//! Legacy Solana transaction encoding, enough to build, sign and serialize the
+//! program instructions without the full SDK.
+
+use solana_pubkey::Pubkey;
+
+pub struct AccountMeta {
+    pub pubkey: Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+impl AccountMeta {
+    pub fn signer(pubkey: Pubkey, is_writable: bool) -> Self {
+        Self {
+            pubkey,
+            is_signer: true,
+            is_writable,
+        }
+    }
+
+    pub fn writable(pubkey: Pubkey) -> Self {
+        Self {
+            pubkey,
+            is_signer: false,
+            is_writable: true,
+        }
+    }
+
+    pub fn readonly(pubkey: Pubkey) -> Self {
+        Self {
+            pubkey,
+            is_signer: false,
+            is_writable: false,
+        }
+    }
+}
+
+pub struct Instruction {
+    pub program_id: Pubkey,
+    pub accounts: Vec<AccountMeta>,
+    pub data: Vec<u8>,
+}
+
+struct CompiledInstruction {
+    program_id_index: u8,
+    accounts: Vec<u8>,
+    data: Vec<u8>,
+}
+
+pub struct Message {
+    num_required_signatures: u8,
+    num_readonly_signed: u8,
+    num_readonly_unsigned: u8,
+    account_keys: Vec<Pubkey>,
+    recent_blockhash: [u8; 32],
+    instructions: Vec<CompiledInstruction>,
+}
+
+impl Message {
+    /// Order the accounts the way the runtime expects: the fee payer first, then writable
+    /// signers, readonly signers, writable and readonly non-signers
+    pub fn new(payer: &Pubkey, instructions: &[Instruction], recent_blockhash: [u8; 32]) -> Self {
+        let mut metas = vec![AccountMeta::signer(*payer, true)];
+        for instruction in instructions {
+            for meta in instruction
+                .accounts
+                .iter()
+                .chain([&AccountMeta::readonly(instruction.program_id)])
+            {
+                match metas.iter_mut().find(|known| known.pubkey == meta.pubkey) {
+                    Some(known) => {
+                        known.is_signer |= meta.is_signer;
+                        known.is_writable |= meta.is_writable;
+                    }
+                    None => metas.push(AccountMeta {
+                        pubkey: meta.pubkey,
+                        is_signer: meta.is_signer,
+                        is_writable: meta.is_writable,
+                    }),
+                }
+            }
+        }
+        // stable, so the fee payer stays first among the writable signers
+        metas.sort_by_key(|meta| (!meta.is_signer, !meta.is_writable));
+
+        let count = |signer: bool, writable: bool| {
+            metas
+                .iter()
+                .filter(|meta| meta.is_signer == signer && meta.is_writable == writable)
+                .count() as u8
+        };
+        let account_keys: Vec<Pubkey> = metas.iter().map(|meta| meta.pubkey).collect();
+        let index_of = |pubkey: &Pubkey| {
+            account_keys
+                .iter()
+                .position(|key| key == pubkey)
+                .expect("every instruction account is in the key list") as u8
+        };
+
+        let instructions = instructions
+            .iter()
+            .map(|instruction| CompiledInstruction {
+                program_id_index: index_of(&instruction.program_id),
+                accounts: instruction
+                    .accounts
+                    .iter()
+                    .map(|meta| index_of(&meta.pubkey))
+                    .collect(),
+                data: instruction.data.clone(),
+            })
+            .collect();
+
+        Self {
+            num_required_signatures: count(true, true) + count(true, false),
+            num_readonly_signed: count(true, false),
+            num_readonly_unsigned: count(false, false),
+            account_keys,
+            recent_blockhash,
+            instructions,
+        }
+    }
+
+    /// Keys whose signatures the transaction needs, in signature order
+    pub fn signers(&self) -> &[Pubkey] {
+        &self.account_keys[..self.num_required_signatures as usize]
+    }
+
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut out = vec![
+            self.num_required_signatures,
+            self.num_readonly_signed,
+            self.num_readonly_unsigned,
+        ];
+
+        write_length(&mut out, self.account_keys.len());
+        for key in &self.account_keys {
+            out.extend_from_slice(key.as_ref());
+        }
+        out.extend_from_slice(&self.recent_blockhash);
+
+        write_length(&mut out, self.instructions.len());
+        for instruction in &self.instructions {
+            out.push(instruction.program_id_index);
+            write_length(&mut out, instruction.accounts.len());
+            out.extend_from_slice(&instruction.accounts);
+            write_length(&mut out, instruction.data.len());
+            out.extend_from_slice(&instruction.data);
+        }
+
+        out
+    }
+}
+
+/// Wire format of a transaction, unsigned slots are left zeroed
+pub fn serialize_transaction(signatures: &[[u8; 64]], message: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_length(&mut out, signatures.len());
+    for signature in signatures {
+        out.extend_from_slice(signature);
+    }
+    out.extend_from_slice(message);
+    out
+}
+
+/// Compact-u16 length prefix
+fn write_length(out: &mut Vec<u8>, mut length: usize) {
+    loop {
+        let byte = (length & 0x7f) as u8;
+        length >>= 7;
+        if length == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
@@ -0,0 +1,55 @@
+const ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Base58 encoding used for Solana addresses
+pub fn encode(bytes: &[u8]) -> String {
+    // base58 digits of the big-endian number, least significant first
+    let mut digits: Vec<u8> = Vec::new();
+    for &byte in bytes {
+        let mut carry = byte as u32;
+        for digit in digits.iter_mut() {
+            carry += (*digit as u32) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+
+    let leading_zeros = bytes.iter().take_while(|&&byte| byte == 0).count();
+    let mut encoded = "1".repeat(leading_zeros);
+    encoded.extend(
+        digits
+            .iter()
+            .rev()
+            .map(|&digit| ALPHABET[digit as usize] as char),
+    );
+    encoded
+}
+
+/// Decode base58 text, `None` on characters outside the alphabet
+pub fn decode(text: &str) -> Option<Vec<u8>> {
+    // base256 digits of the number, least significant first
+    let mut bytes: Vec<u8> = Vec::new();
+    for character in text.bytes() {
+        let mut carry = ALPHABET.iter().position(|&digit| digit == character)? as u32;
+        for byte in bytes.iter_mut() {
+            carry += (*byte as u32) * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+
+    let leading_ones = text
+        .bytes()
+        .take_while(|&character| character == b'1')
+        .count();
+    let mut decoded = vec![0; leading_ones];
+    decoded.extend(bytes.iter().rev());
+    Some(decoded)
+}
@@ -0,0 +1,297 @@
+//! Line based ledger dump read by the indexer. Blank lines and `#` comments are ignored,
+//! every other line is one record:
+//!
+//! ```text
+//! account <address> <hex data>                     final state of a program account
+//! tx <signature> <slot>                            starts a transaction
+//! ix <program id> <hex data> [<account address>]*  top-level instruction of the transaction
+//! data <hex field> [<hex field>]*                  `Program data:` entry logged by the program
+//! ```
+//!
+//! `ix` and `data` lines belong to the last `tx` line before them.
+//!
+//! A dump starting with `{` is JSON instead, in the shape of RPC results so it can be
+//! saved straight from a node or written by a LiteSVM test from its accounts and
+//! transaction logs:
+//!
+//! ```text
+//! {
+//!   "accounts": [{ "pubkey": <address>, "account": { "data": [<base64>, "base64"] } }],
+//!   "transactions": [<getTransaction result with "json" encoding>]
+//! }
+//! ```
+//!
+//! `accounts` is a `getProgramAccounts` result. Each transaction needs `slot`, the first of
+//! `transaction.signatures`, `transaction.message.accountKeys` and `instructions`, and
+//! `meta.logMessages`, plus `meta.loadedAddresses` for versioned transactions. Only
+//! `Program data:` entries logged while the program itself runs are kept.
+
+use std::fmt;
+
+use UniPinoNft::instructions::ID;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use serde_json::Value;
+
+use crate::base58;
+
+pub struct Account {
+    pub address: String,
+    pub data: Vec<u8>,
+}
+
+pub struct Instruction {
+    pub program_id: String,
+    pub data: Vec<u8>,
+    pub accounts: Vec<String>,
+}
+
+pub struct Transaction {
+    pub signature: String,
+    pub slot: u64,
+    pub instructions: Vec<Instruction>,
+    pub program_data: Vec<Vec<Vec<u8>>>,
+}
+
+#[derive(Default)]
+pub struct Dump {
+    pub accounts: Vec<Account>,
+    pub transactions: Vec<Transaction>,
+}
+
+#[derive(Debug)]
+pub struct ParseError {
+    /// Line of a text record, or of a JSON syntax error; `None` for invalid JSON content
+    pub line: Option<usize>,
+    pub reason: &'static str,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {}: {}", line, self.reason),
+            None => write!(f, "{}", self.reason),
+        }
+    }
+}
+
+impl Dump {
+    /// Append the records of `text` to the dump, in the text or the JSON format
+    pub fn parse(&mut self, text: &str) -> Result<(), ParseError> {
+        if text.trim_start().starts_with('{') {
+            self.parse_json(text)
+        } else {
+            self.parse_text(text)
+        }
+    }
+
+    fn parse_text(&mut self, text: &str) -> Result<(), ParseError> {
+        for (i, line) in text.lines().enumerate() {
+            let error = |reason| ParseError {
+                line: Some(i + 1),
+                reason,
+            };
+
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut fields = line.split_whitespace();
+            match fields.next() {
+                Some("account") => {
+                    let (Some(address), Some(data), None) =
+                        (fields.next(), fields.next(), fields.next())
+                    else {
+                        return Err(error("expected `account <address> <hex data>`"));
+                    };
+                    self.accounts.push(Account {
+                        address: address.to_string(),
+                        data: decode_hex(data).ok_or(error("invalid hex data"))?,
+                    });
+                }
+                Some("tx") => {
+                    let (Some(signature), Some(slot), None) =
+                        (fields.next(), fields.next(), fields.next())
+                    else {
+                        return Err(error("expected `tx <signature> <slot>`"));
+                    };
+                    self.transactions.push(Transaction {
+                        signature: signature.to_string(),
+                        slot: slot.parse().map_err(|_| error("invalid slot"))?,
+                        instructions: Vec::new(),
+                        program_data: Vec::new(),
+                    });
+                }
+                Some("ix") => {
+                    let (Some(program_id), Some(data)) = (fields.next(), fields.next()) else {
+                        return Err(error("expected `ix <program id> <hex data> [accounts]`"));
+                    };
+                    let instruction = Instruction {
+                        program_id: program_id.to_string(),
+                        data: decode_hex(data).ok_or(error("invalid hex data"))?,
+                        accounts: fields.map(str::to_string).collect(),
+                    };
+                    self.transactions
+                        .last_mut()
+                        .ok_or(error("`ix` before any `tx`"))?
+                        .instructions
+                        .push(instruction);
+                }
+                Some("data") => {
+                    let entry = fields
+                        .map(decode_hex)
+                        .collect::<Option<Vec<_>>>()
+                        .ok_or(error("invalid hex field"))?;
+                    self.transactions
+                        .last_mut()
+                        .ok_or(error("`data` before any `tx`"))?
+                        .program_data
+                        .push(entry);
+                }
+                _ => return Err(error("unknown record")),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn parse_json(&mut self, text: &str) -> Result<(), ParseError> {
+        let json: Value = serde_json::from_str(text).map_err(|err| ParseError {
+            line: Some(err.line()),
+            reason: "invalid JSON",
+        })?;
+        let error = |reason| ParseError { line: None, reason };
+
+        for account in array(&json["accounts"]).ok_or(error("`accounts` is not an array"))? {
+            let address = account["pubkey"]
+                .as_str()
+                .ok_or(error("account without `pubkey`"))?;
+            let data = match &account["account"]["data"] {
+                Value::Array(data) if data.get(1) == Some(&Value::from("base64")) => {
+                    data[0].as_str().and_then(|data| BASE64.decode(data).ok())
+                }
+                _ => None,
+            };
+            self.accounts.push(Account {
+                address: address.to_string(),
+                data: data.ok_or(error("account data is not `[<base64>, \"base64\"]`"))?,
+            });
+        }
+
+        let program_id = base58::encode(&ID);
+        for transaction in
+            array(&json["transactions"]).ok_or(error("`transactions` is not an array"))?
+        {
+            let message = &transaction["transaction"]["message"];
+            let meta = &transaction["meta"];
+
+            // versioned transactions append the addresses loaded from lookup tables
+            let keys: Vec<&str> = [
+                &message["accountKeys"],
+                &meta["loadedAddresses"]["writable"],
+                &meta["loadedAddresses"]["readonly"],
+            ]
+            .into_iter()
+            .flat_map(|keys| array(keys).unwrap_or_default())
+            .map(Value::as_str)
+            .collect::<Option<_>>()
+            .ok_or(error("account key is not a string"))?;
+            let key = |index: &Value| {
+                index
+                    .as_u64()
+                    .and_then(|index| keys.get(index as usize))
+                    .map(|key| key.to_string())
+                    .ok_or(error("account index out of range"))
+            };
+
+            let mut instructions = Vec::new();
+            for instruction in array(&message["instructions"])
+                .ok_or(error("transaction without `message.instructions`"))?
+            {
+                instructions.push(Instruction {
+                    program_id: key(&instruction["programIdIndex"])?,
+                    data: instruction["data"]
+                        .as_str()
+                        .and_then(base58::decode)
+                        .ok_or(error("instruction data is not base58"))?,
+                    accounts: array(&instruction["accounts"])
+                        .unwrap_or_default()
+                        .iter()
+                        .map(key)
+                        .collect::<Result<_, _>>()?,
+                });
+            }
+
+            let logs = array(&meta["logMessages"])
+                .unwrap_or_default()
+                .iter()
+                .map(Value::as_str)
+                .collect::<Option<Vec<_>>>()
+                .ok_or(error("log message is not a string"))?;
+
+            self.transactions.push(Transaction {
+                signature: transaction["transaction"]["signatures"][0]
+                    .as_str()
+                    .ok_or(error("transaction without signature"))?
+                    .to_string(),
+                slot: transaction["slot"]
+                    .as_u64()
+                    .ok_or(error("transaction without slot"))?,
+                instructions,
+                program_data: program_data(&logs, &program_id)
+                    .ok_or(error("invalid base64 in `Program data:`"))?,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Items of a JSON array, empty when the field is missing
+fn array(value: &Value) -> Option<&[Value]> {
+    match value {
+        Value::Null => Some(&[]),
+        Value::Array(items) => Some(items),
+        _ => None,
+    }
+}
+
+/// `Program data:` entries logged while `program_id` is the innermost running program,
+/// tracked through the `invoke` and `success`/`failed` lines around each invocation
+fn program_data(logs: &[&str], program_id: &str) -> Option<Vec<Vec<Vec<u8>>>> {
+    let mut running = Vec::new();
+    let mut entries = Vec::new();
+    for line in logs {
+        if let Some(fields) = line.strip_prefix("Program data: ") {
+            if running.last() == Some(&program_id) {
+                let entry = fields
+                    .split(' ')
+                    .map(|field| BASE64.decode(field).ok())
+                    .collect::<Option<_>>()?;
+                entries.push(entry);
+            }
+        } else if let Some(line) = line.strip_prefix("Program ") {
+            let mut words = line.split(' ');
+            match (words.next(), words.next()) {
+                (Some(program), Some("invoke")) => running.push(program),
+                (Some(_), Some("success" | "failed:")) => {
+                    running.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+    Some(entries)
+}
+
+fn decode_hex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
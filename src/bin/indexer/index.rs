@@ -0,0 +1,277 @@
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+use UniPinoNft::events::ProgramEvent;
+use UniPinoNft::instructions::ID;
+use UniPinoNft::instructions::{
+    collection::*, delegate::*, migrate::*, multisig::*, nft::*, ownership::*, platform::*,
+    role::*, session::*, timelock::*, transfer::*, treasury::*, user::*,
+};
+use UniPinoNft::state::nft_meta::NftMeta;
+use UniPinoNft::state::platform::Platform;
+use UniPinoNft::state::program_state::ProgramState;
+use UniPinoNft::state::user::User;
+use bytemuck::pod_read_unaligned;
+
+use crate::base58;
+use crate::dump::Dump;
+
+pub struct InstructionRow {
+    pub signature: String,
+    pub slot: u64,
+    pub name: &'static str,
+    pub accounts: Vec<String>,
+}
+
+pub struct OwnershipRow {
+    pub signature: String,
+    pub slot: u64,
+    pub change: &'static str,
+    pub mint: String,
+    pub user: String,
+}
+
+pub struct FeeRow {
+    pub signature: String,
+    pub slot: u64,
+    pub platform: String,
+    pub mint: String,
+    pub fee: u64,
+}
+
+/// Relational view of the platforms, users and NFTs found in a dump
+#[derive(Default)]
+pub struct Index {
+    pub platforms: Vec<(String, Platform)>,
+    pub users: Vec<(String, User)>,
+    pub nfts: Vec<(String, NftMeta)>,
+    pub instructions: Vec<InstructionRow>,
+    pub ownership: Vec<OwnershipRow>,
+    pub fees: Vec<FeeRow>,
+}
+
+impl Index {
+    pub fn build(dump: &Dump) -> Self {
+        let mut index = Self::default();
+
+        for account in &dump.accounts {
+            let address = account.address.clone();
+            if let Ok(platform) = Platform::read(&account.data) {
+                index.platforms.push((address, platform));
+            } else if let Ok(user) = User::try_from_bytes(&account.data) {
+                index.users.push((address, *user));
+            } else if let Some(metadata) = read_metadata(&account.data) {
+                index.nfts.push((address, metadata));
+            }
+        }
+
+        let program_id = base58::encode(&ID);
+        for transaction in &dump.transactions {
+            for instruction in &transaction.instructions {
+                if instruction.program_id != program_id {
+                    continue;
+                }
+                index.instructions.push(InstructionRow {
+                    signature: transaction.signature.clone(),
+                    slot: transaction.slot,
+                    name: instruction_name(instruction.data.first()),
+                    accounts: instruction.accounts.clone(),
+                });
+            }
+
+            for entry in &transaction.program_data {
+                let fields: Vec<&[u8]> = entry.iter().map(Vec::as_slice).collect();
+                let row = |change, mint: &[u8; 32], user: &[u8; 32]| OwnershipRow {
+                    signature: transaction.signature.clone(),
+                    slot: transaction.slot,
+                    change,
+                    mint: base58::encode(mint),
+                    user: base58::encode(user),
+                };
+
+                match ProgramEvent::decode(&fields) {
+                    Some(ProgramEvent::NftMinted(event)) => {
+                        index
+                            .ownership
+                            .push(row("minted", &event.mint, &event.user));
+                        index.fees.push(FeeRow {
+                            signature: transaction.signature.clone(),
+                            slot: transaction.slot,
+                            platform: base58::encode(&event.platform),
+                            mint: base58::encode(&event.mint),
+                            fee: event.fee,
+                        });
+                    }
+                    Some(ProgramEvent::NftBurned(event)) => {
+                        index
+                            .ownership
+                            .push(row("burned", &event.mint, &event.user));
+                    }
+                    Some(ProgramEvent::NftTransferred(event)) => {
+                        index.ownership.push(row("sent", &event.mint, &event.from));
+                        index
+                            .ownership
+                            .push(row("received", &event.mint, &event.to));
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        index
+    }
+
+    /// Write every table as tab separated rows under a `[table]` header
+    pub fn write(&self, out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, "[platforms]")?;
+        writeln!(
+            out,
+            "address\tadministrator\tfee_receiver\tmint_fee\ttotal_users\ttotal_mints\tpaused"
+        )?;
+        for (address, platform) in &self.platforms {
+            writeln!(
+                out,
+                "{}\t{}\t{}\t{}\t{}\t{}\t{}",
+                address,
+                base58::encode(&platform.administrator),
+                base58::encode(&platform.fee_receiver),
+                { platform.mint_fee },
+                { platform.total_users },
+                { platform.total_mints },
+                platform.paused,
+            )?;
+        }
+
+        writeln!(out, "\n[users]")?;
+        writeln!(out, "address\tplatform\tuser_uuid\tnft_count\tauthority")?;
+        for (address, user) in &self.users {
+            writeln!(
+                out,
+                "{}\t{}\t{}\t{}\t{}",
+                address,
+                base58::encode(&user.owner),
+                { user.user_uuid },
+                { user.nft_count },
+                base58::encode(&user.authority),
+            )?;
+        }
+
+        writeln!(out, "\n[nfts]")?;
+        writeln!(out, "metadata\tname\tcollection\turi\tversion\tdelegate")?;
+        for (address, metadata) in &self.nfts {
+            writeln!(
+                out,
+                "{}\t{}\t{}\t{}\t{}\t{}",
+                address,
+                text(&metadata.name),
+                text(&metadata.collection),
+                text(&metadata.uri),
+                metadata.version,
+                base58::encode(&metadata.delegate),
+            )?;
+        }
+
+        writeln!(out, "\n[instructions]")?;
+        writeln!(out, "slot\tsignature\tinstruction\taccounts")?;
+        for row in &self.instructions {
+            writeln!(
+                out,
+                "{}\t{}\t{}\t{}",
+                row.slot,
+                row.signature,
+                row.name,
+                row.accounts.join(",")
+            )?;
+        }
+
+        writeln!(out, "\n[ownership]")?;
+        writeln!(out, "slot\tsignature\tchange\tmint\tuser")?;
+        for row in &self.ownership {
+            writeln!(
+                out,
+                "{}\t{}\t{}\t{}\t{}",
+                row.slot, row.signature, row.change, row.mint, row.user
+            )?;
+        }
+
+        writeln!(out, "\n[fees]")?;
+        writeln!(out, "slot\tsignature\tplatform\tmint\tfee")?;
+        for row in &self.fees {
+            writeln!(
+                out,
+                "{}\t{}\t{}\t{}\t{}",
+                row.slot, row.signature, row.platform, row.mint, row.fee
+            )?;
+        }
+
+        let mut fee_totals = BTreeMap::<&str, u64>::new();
+        for row in &self.fees {
+            *fee_totals.entry(&row.platform).or_default() += row.fee;
+        }
+        writeln!(out, "\n[fee_totals]")?;
+        writeln!(out, "platform\tfees")?;
+        for (platform, total) in fee_totals {
+            writeln!(out, "{}\t{}", platform, total)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Decode metadata of any supported version, legacy accounts read with a zeroed tail
+fn read_metadata(data: &[u8]) -> Option<NftMeta> {
+    NftMeta::version_of(data).ok()?;
+
+    let mut bytes = [0; NftMeta::INIT_SPACE];
+    bytes[..data.len()].copy_from_slice(data);
+    Some(pod_read_unaligned(&bytes))
+}
+
+/// Fixed size text field up to its first NUL byte
+fn text(field: &[u8]) -> String {
+    let end = field
+        .iter()
+        .position(|&byte| byte == 0)
+        .unwrap_or(field.len());
+    String::from_utf8_lossy(&field[..end]).into_owned()
+}
+
+fn instruction_name(discriminator: Option<&u8>) -> &'static str {
+    match discriminator {
+        Some(InitPlatform::DISCRIMINATOR) => "InitPlatform",
+        Some(UpdatePlatformConfig::DISCRIMINATOR) => "UpdatePlatformConfig",
+        Some(CreateUser::DISCRIMINATOR) => "CreateUser",
+        Some(MintNft::DISCRIMINATOR) => "MintNft",
+        Some(UpdateNFTMetadata::DISCRIMINATOR) => "UpdateNFTMetadata",
+        Some(BurnNft::DISCRIMINATOR) => "BurnNft",
+        Some(WithdrawTreasury::DISCRIMINATOR) => "WithdrawTreasury",
+        Some(SetCollectionConfig::DISCRIMINATOR) => "SetCollectionConfig",
+        Some(SetPauseFlags::DISCRIMINATOR) => "SetPauseFlags",
+        Some(GrantRole::DISCRIMINATOR) => "GrantRole",
+        Some(RevokeRole::DISCRIMINATOR) => "RevokeRole",
+        Some(SetAdminMultisig::DISCRIMINATOR) => "SetAdminMultisig",
+        Some(QueueConfigChange::DISCRIMINATOR) => "QueueConfigChange",
+        Some(ExecuteConfigChange::DISCRIMINATOR) => "ExecuteConfigChange",
+        Some(CancelConfigChange::DISCRIMINATOR) => "CancelConfigChange",
+        Some(CloseUser::DISCRIMINATOR) => "CloseUser",
+        Some(ClosePlatform::DISCRIMINATOR) => "ClosePlatform",
+        Some(MigrateAccount::DISCRIMINATOR) => "MigrateAccount",
+        Some(LinkUserWallet::DISCRIMINATOR) => "LinkUserWallet",
+        Some(UnlinkUserWallet::DISCRIMINATOR) => "UnlinkUserWallet",
+        Some(CreateSessionKey::DISCRIMINATOR) => "CreateSessionKey",
+        Some(RevokeSessionKey::DISCRIMINATOR) => "RevokeSessionKey",
+        Some(ApproveNftDelegate::DISCRIMINATOR) => "ApproveNftDelegate",
+        Some(RevokeNftDelegate::DISCRIMINATOR) => "RevokeNftDelegate",
+        Some(VerifyOwnership::DISCRIMINATOR) => "VerifyOwnership",
+        Some(SetCollectionAllowlist::DISCRIMINATOR) => "SetCollectionAllowlist",
+        Some(QueueCollectionConfig::DISCRIMINATOR) => "QueueCollectionConfig",
+        Some(QueueCollectionAllowlist::DISCRIMINATOR) => "QueueCollectionAllowlist",
+        Some(ExecuteCollectionChange::DISCRIMINATOR) => "ExecuteCollectionChange",
+        Some(CancelCollectionChange::DISCRIMINATOR) => "CancelCollectionChange",
+        Some(TransferNftInternal::DISCRIMINATOR) => "TransferNftInternal",
+        Some(BurnNftWithIntent::DISCRIMINATOR) => "BurnNftWithIntent",
+        Some(SetUserTier::DISCRIMINATOR) => "SetUserTier",
+        Some(RotateAdministrator::DISCRIMINATOR) => "RotateAdministrator",
+        _ => "Unknown",
+    }
+}
@@ -0,0 +1,49 @@
+//! Rebuilds a relational view of platforms, users, NFTs, ownership history and fees from
+//! ledger dumps, without any RPC access. See `dump.rs` for the text and JSON formats.
+//!
+//! Usage: `unipino-indexer <dump file>...`
+
+mod base58;
+mod dump;
+mod index;
+
+use std::io::{self, Write};
+use std::process::ExitCode;
+use std::{env, fs};
+
+use dump::Dump;
+use index::Index;
+
+fn main() -> ExitCode {
+    let paths: Vec<String> = env::args().skip(1).collect();
+    if paths.is_empty() {
+        eprintln!("usage: unipino-indexer <dump file>...");
+        return ExitCode::FAILURE;
+    }
+
+    let mut dump = Dump::default();
+    for path in &paths {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) => {
+                eprintln!("{}: {}", path, err);
+                return ExitCode::FAILURE;
+            }
+        };
+        if let Err(err) = dump.parse(&text) {
+            eprintln!("{}: {}", path, err);
+            return ExitCode::FAILURE;
+        }
+    }
+
+    let mut out = io::stdout().lock();
+    if let Err(err) = Index::build(&dump)
+        .write(&mut out)
+        .and_then(|_| out.flush())
+    {
+        eprintln!("{}", err);
+        return ExitCode::FAILURE;
+    }
+
+    ExitCode::SUCCESS
+}
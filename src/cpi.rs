@@ -0,0 +1,225 @@
+//! Cross-program invocation interface for partner programs. A partner depends on this
+//! crate without the `bpf-entrypoint` feature and calls these helpers from its own
+//! instruction handlers.
+//!
+//! To mint, the administrator grants `ROLE_PROGRAM_MINTER` to the partner program id;
+//! the partner then signs `MintNft` with its minter authority PDA, seeds
+//! `["minter_authority", platform_pda]` under the partner program. `transfer_internal`
+//! moves an NFT between two users of a platform and `verify_ownership` reads back the
+//! `OwnershipProof` the program returns.
+
+use alloc::vec::Vec;
+use bytemuck::{bytes_of, try_pod_read_unaligned};
+use pinocchio::ProgramResult;
+use pinocchio::account_info::AccountInfo;
+use pinocchio::cpi::{get_return_data, invoke, slice_invoke_signed};
+use pinocchio::instruction::{AccountMeta, Instruction, Signer};
+use pinocchio::program_error::ProgramError;
+use pinocchio::pubkey::{Pubkey, create_program_address, try_find_program_address};
+
+use crate::instructions::nft::MintNft;
+use crate::instructions::ownership::VerifyOwnership;
+use crate::instructions::transfer::TransferNftInternal;
+use crate::instructions::{ID, MintNftArgs, OwnershipProof, VerifyOwnershipArgs};
+
+pub const MINTER_AUTHORITY_TOKEN: &[u8] = b"minter_authority";
+
+/// Minter authority PDA and bump `program_id` signs `MintNft` with on `platform_pda`
+pub fn find_minter_authority(platform_pda: &Pubkey, program_id: &Pubkey) -> Option<(Pubkey, u8)> {
+    try_find_program_address(&[MINTER_AUTHORITY_TOKEN, platform_pda.as_ref()], program_id)
+}
+
+/// Minter authority PDA of `program_id` on `platform_pda` for a known bump
+pub fn minter_authority(
+    platform_pda: &Pubkey,
+    program_id: &Pubkey,
+    bump: u8,
+) -> Result<Pubkey, ProgramError> {
+    create_program_address(
+        &[
+            MINTER_AUTHORITY_TOKEN,
+            platform_pda.as_ref(),
+            core::slice::from_ref(&bump),
+        ],
+        program_id,
+    )
+}
+
+/// Accounts of `MintNft`
+pub struct MintNftAccounts<'a> {
+    /// Minter authority PDA of the calling program, a system account paying rent and fees
+    pub authority: &'a AccountInfo,
+    pub platform_pda: &'a AccountInfo,
+    pub user_pda: &'a AccountInfo,
+    pub mint_pda: &'a AccountInfo,
+    pub metadata_pda: &'a AccountInfo,
+    /// Token-2022 associated token account of the user PDA for the mint, created by `MintNft`
+    pub token_account: &'a AccountInfo,
+    pub fee_receiver: &'a AccountInfo,
+    pub system_program: &'a AccountInfo,
+    /// Token-2022
+    pub token_program: &'a AccountInfo,
+    pub associated_token_program: &'a AccountInfo,
+    /// Role PDA, collection and claim PDAs and fee accounts, in `MintNft` order
+    pub remaining: &'a [AccountInfo],
+}
+
+/// Mint an NFT for a user, `signers` holding the seeds of the minter authority PDA.
+/// `proof` is the allowlist merkle proof of the user, empty for open collections.
+pub fn mint_nft(
+    accounts: &MintNftAccounts,
+    args: &MintNftArgs,
+    proof: &[[u8; 32]],
+    signers: &[Signer],
+) -> ProgramResult {
+    let mut account_metas = Vec::with_capacity(10 + accounts.remaining.len());
+    account_metas.extend([
+        AccountMeta::writable_signer(accounts.authority.key()),
+        AccountMeta::writable(accounts.platform_pda.key()),
+        AccountMeta::writable(accounts.user_pda.key()),
+        AccountMeta::writable(accounts.mint_pda.key()),
+        AccountMeta::writable(accounts.metadata_pda.key()),
+        AccountMeta::writable(accounts.token_account.key()),
+        AccountMeta::writable(accounts.fee_receiver.key()),
+        AccountMeta::readonly(accounts.system_program.key()),
+        AccountMeta::readonly(accounts.token_program.key()),
+        AccountMeta::readonly(accounts.associated_token_program.key()),
+    ]);
+    account_metas.extend(accounts.remaining.iter().map(AccountMeta::from));
+
+    let mut account_infos = Vec::with_capacity(account_metas.len());
+    account_infos.extend([
+        accounts.authority,
+        accounts.platform_pda,
+        accounts.user_pda,
+        accounts.mint_pda,
+        accounts.metadata_pda,
+        accounts.token_account,
+        accounts.fee_receiver,
+        accounts.system_program,
+        accounts.token_program,
+        accounts.associated_token_program,
+    ]);
+    account_infos.extend(accounts.remaining);
+
+    let mut data = Vec::with_capacity(1 + size_of::<MintNftArgs>() + 4 + proof.len() * 32);
+    data.push(*MintNft::DISCRIMINATOR);
+    data.extend_from_slice(bytes_of(args));
+    data.extend_from_slice(&(proof.len() as u32).to_le_bytes());
+    data.extend(proof.iter().flatten());
+
+    let instruction = Instruction {
+        program_id: &ID,
+        accounts: &account_metas,
+        data: &data,
+    };
+    slice_invoke_signed(&instruction, &account_infos, signers)
+}
+
+/// Accounts of `TransferNFTInternal`
+pub struct TransferInternalAccounts<'a> {
+    /// Administrator, linked wallet or session key of the sending user
+    pub authority: &'a AccountInfo,
+    pub platform_pda: &'a AccountInfo,
+    pub from_user_pda: &'a AccountInfo,
+    pub to_user_pda: &'a AccountInfo,
+    pub mint_pda: &'a AccountInfo,
+    pub metadata_pda: &'a AccountInfo,
+    /// Token-2022 account of the sending user PDA
+    pub from_token_account: &'a AccountInfo,
+    /// Token-2022 account of the receiving user PDA
+    pub to_token_account: &'a AccountInfo,
+    pub token_program: &'a AccountInfo,
+    /// Session key PDA when a session key signs
+    pub remaining: &'a [AccountInfo],
+}
+
+/// Move an NFT from one user of a platform to another, `signers` holding the seeds of
+/// `authority` when it is a PDA of the calling program
+pub fn transfer_internal(accounts: &TransferInternalAccounts, signers: &[Signer]) -> ProgramResult {
+    let mut account_metas = Vec::with_capacity(9 + accounts.remaining.len());
+    account_metas.extend([
+        AccountMeta::readonly_signer(accounts.authority.key()),
+        AccountMeta::readonly(accounts.platform_pda.key()),
+        AccountMeta::writable(accounts.from_user_pda.key()),
+        AccountMeta::writable(accounts.to_user_pda.key()),
+        AccountMeta::readonly(accounts.mint_pda.key()),
+        AccountMeta::writable(accounts.metadata_pda.key()),
+        AccountMeta::writable(accounts.from_token_account.key()),
+        AccountMeta::writable(accounts.to_token_account.key()),
+        AccountMeta::readonly(accounts.token_program.key()),
+    ]);
+    account_metas.extend(accounts.remaining.iter().map(AccountMeta::from));
+
+    let mut account_infos = Vec::with_capacity(account_metas.len());
+    account_infos.extend([
+        accounts.authority,
+        accounts.platform_pda,
+        accounts.from_user_pda,
+        accounts.to_user_pda,
+        accounts.mint_pda,
+        accounts.metadata_pda,
+        accounts.from_token_account,
+        accounts.to_token_account,
+        accounts.token_program,
+    ]);
+    account_infos.extend(accounts.remaining);
+
+    let instruction = Instruction {
+        program_id: &ID,
+        accounts: &account_metas,
+        data: core::slice::from_ref(TransferNftInternal::DISCRIMINATOR),
+    };
+    slice_invoke_signed(&instruction, &account_infos, signers)
+}
+
+/// Accounts of `VerifyOwnership`
+pub struct VerifyOwnershipAccounts<'a> {
+    pub platform_pda: &'a AccountInfo,
+    pub user_pda: &'a AccountInfo,
+    pub mint_pda: &'a AccountInfo,
+    pub metadata_pda: &'a AccountInfo,
+    /// Token-2022 account of the holder
+    pub token_account: &'a AccountInfo,
+}
+
+/// Invoke `VerifyOwnership` and return the `OwnershipProof` it sets as return data
+pub fn verify_ownership(
+    accounts: &VerifyOwnershipAccounts,
+    args: &VerifyOwnershipArgs,
+) -> Result<OwnershipProof, ProgramError> {
+    let account_metas = [
+        AccountMeta::readonly(accounts.platform_pda.key()),
+        AccountMeta::readonly(accounts.user_pda.key()),
+        AccountMeta::readonly(accounts.mint_pda.key()),
+        AccountMeta::readonly(accounts.metadata_pda.key()),
+        AccountMeta::readonly(accounts.token_account.key()),
+    ];
+
+    let mut data = Vec::with_capacity(1 + size_of::<VerifyOwnershipArgs>());
+    data.push(*VerifyOwnership::DISCRIMINATOR);
+    data.extend_from_slice(bytes_of(args));
+
+    let instruction = Instruction {
+        program_id: &ID,
+        accounts: &account_metas,
+        data: &data,
+    };
+    invoke(
+        &instruction,
+        &[
+            accounts.platform_pda,
+            accounts.user_pda,
+            accounts.mint_pda,
+            accounts.metadata_pda,
+            accounts.token_account,
+        ],
+    )?;
+
+    // a failed check aborts the whole transaction, so the return data is ours once here
+    let return_data = get_return_data().ok_or(ProgramError::InvalidAccountData)?;
+    if return_data.program_id() != &ID {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    try_pod_read_unaligned(return_data.as_slice()).map_err(|_| ProgramError::InvalidAccountData)
+}
@@ -2,6 +2,8 @@ use num_derive::FromPrimitive;
 use pinocchio::program_error::{ProgramError, ToStr};
 use thiserror::Error;
 
+// Codes are the variant indexes, so new variants are only ever appended. Each variant
+// sits on the line after its `#[error]` message, build.rs relies on that layout
 #[derive(Clone, Debug, PartialEq, Eq, Error, FromPrimitive)]
 pub enum UniPinoNftErr {
     #[error("Fail to find a valid PDA")]
@@ -10,18 +12,83 @@ pub enum UniPinoNftErr {
     UninitPda,
     #[error("Instruction try to re-init exist PDA")]
     ReInitPda,
+    #[error("Operation is paused on this platform")]
+    PlatformPaused,
+    #[error("Config changes must be queued while a timelock is set")]
+    ConfigTimelocked,
+    #[error("Queued config change is not executable yet")]
+    TimelockNotElapsed,
+    #[error("Account still holds users or NFTs")]
+    AccountNotEmpty,
+    #[error("Account layout version is not supported")]
+    UnsupportedVersion,
+    #[error("User intent does not match a verified signature")]
+    InvalidIntent,
+    #[error("User intent has expired")]
+    IntentExpired,
+    #[error("Session key is expired or not allowed to sign this instruction")]
+    SessionNotAllowed,
+    #[error("Signer is not the platform administrator")]
+    NotAdministrator,
+    #[error("Signer does not hold the required role")]
+    MissingRole,
+    #[error("Signer is neither the administrator nor the linked wallet of the user")]
+    NotUserHolder,
+    #[error("User account does not belong to this platform")]
+    UserMismatch,
+    #[error("User wallet is not initialized or has been closed")]
+    UserInactive,
+    #[error("Mint does not match the NFT mint PDA of the user")]
+    MintMismatch,
+    #[error("Metadata account does not match the mint")]
+    MetadataMismatch,
+    #[error("Account belongs to another platform")]
+    PlatformMismatch,
+    #[error("Account address does not match its PDA seeds")]
+    PdaMismatch,
+    #[error("Fee receiver does not match the platform configuration")]
+    WrongFeeReceiver,
+    #[error("Fee mint does not match the platform configuration")]
+    WrongFeeMint,
+    #[error("Token account has the wrong mint or owner")]
+    WrongTokenAccount,
+    #[error("Fee basis points exceed 100%")]
+    FeeTooHigh,
+    #[error("Fee shares or tiers are malformed")]
+    InvalidFeeConfig,
+    #[error("Withdrawal exceeds the treasury max per withdrawal")]
+    MaxWithdrawalExceeded,
+    #[error("Admin multisig approvals are missing or invalid")]
+    NotEnoughApprovals,
+    #[error("Session key belongs to another user or key")]
+    SessionMismatch,
+    #[error("Collection account does not match the requested collection")]
+    CollectionMismatch,
+    #[error("User is not on the collection allowlist")]
+    NotAllowlisted,
+    #[error("Allowlist mint limit of the current phase is reached")]
+    AllowlistLimitReached,
+    #[error("Allowlist phase can only move forward")]
+    PhaseNotIncreasing,
+    #[error("Treasury still holds fees that were not withdrawn")]
+    TreasuryNotDrained,
+    #[error("User does not hold the NFT")]
+    NftNotHeld,
+    #[error("NFT does not have the requested attribute")]
+    AttributeMismatch,
+    #[error("Payer does not match the account that queued the change")]
+    PayerMismatch,
 }
 
-impl ToStr for UniPinoNftErr {
-    fn to_str<E>(&self) -> &'static str
-    where
-        E: 'static + ToStr + TryFrom<u32>,
-    {
-        match self {
-            Self::PdaErr => "ERROR: Fail to find a valid PDA",
-            Self::UninitPda => "ERROR: Instruction try to access uninit PDA",
-            Self::ReInitPda => "ERROR: Instruction try to re-init exist PDA",
-        }
+// `ERROR_TABLE` and the `ToStr` messages, generated by build.rs from the `#[error]`
+// attributes above
+include!(concat!(env!("OUT_DIR"), "/error_table.rs"));
+
+impl TryFrom<u32> for UniPinoNftErr {
+    type Error = ProgramError;
+
+    fn try_from(code: u32) -> Result<Self, Self::Error> {
+        num_traits::FromPrimitive::from_u32(code).ok_or(ProgramError::InvalidArgument)
     }
 }
 
@@ -30,3 +97,52 @@ impl From<UniPinoNftErr> for ProgramError {
         ProgramError::Custom(e as u32)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::vec::Vec;
+
+    use num_traits::FromPrimitive;
+
+    use super::*;
+
+    #[test]
+    fn error_codes_are_table_indexes() {
+        for (index, &(code, name, _)) in ERROR_TABLE.iter().enumerate() {
+            assert_eq!(code, index as u32, "{}", name);
+            assert!(UniPinoNftErr::from_u32(code).is_some(), "{}", name);
+        }
+        assert!(UniPinoNftErr::from_u32(ERROR_TABLE.len() as u32).is_none());
+    }
+
+    #[test]
+    fn messages_match_display_and_logs() {
+        for &(code, _, message) in ERROR_TABLE {
+            let error = UniPinoNftErr::from_u32(code).unwrap();
+            assert_eq!(std::format!("{}", error), message);
+            assert_eq!(
+                error.to_str::<UniPinoNftErr>(),
+                std::format!("ERROR: {}", message)
+            );
+        }
+    }
+
+    #[test]
+    fn readme_lists_every_error() {
+        let rows: Vec<(u32, &str, &str)> = include_str!("../README.md")
+            .lines()
+            .skip_while(|line| *line != "## Errors")
+            .filter_map(|line| {
+                let cells: Vec<&str> = line.split('|').map(str::trim).collect();
+                let [_, code, name, message, _] = cells[..] else {
+                    return None;
+                };
+                Some((code.parse().ok()?, name.trim_matches('`'), message))
+            })
+            .collect();
+
+        assert_eq!(rows, ERROR_TABLE);
+    }
+}
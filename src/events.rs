@@ -0,0 +1,140 @@
+use bytemuck::{Pod, Zeroable, bytes_of, try_pod_read_unaligned};
+use pinocchio::log::sol_log_data;
+use pinocchio::pubkey::Pubkey;
+
+/// Binary event logged through `sol_log_data`. Each event is one `Program data:` entry
+/// with two fields: the one-byte `TAG`, then the packed event struct.
+pub trait Event: Pod {
+    const TAG: u8;
+
+    fn emit(&self) {
+        sol_log_data(&[&[Self::TAG], bytes_of(self)]);
+    }
+}
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct PlatformInitialized {
+    pub platform: Pubkey,
+    pub administrator: Pubkey,
+}
+
+impl Event for PlatformInitialized {
+    const TAG: u8 = 0;
+}
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct UserCreated {
+    pub platform: Pubkey,
+    pub user: Pubkey,
+    pub user_uuid: u128,
+}
+
+impl Event for UserCreated {
+    const TAG: u8 = 1;
+}
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct NftMinted {
+    pub platform: Pubkey,
+    pub user: Pubkey,
+    pub mint: Pubkey,
+    pub collection: [u8; 64],
+    pub fee: u64, // in lamports or `fee_mint` units
+}
+
+impl Event for NftMinted {
+    const TAG: u8 = 2;
+}
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct MetadataUpdated {
+    pub platform: Pubkey,
+    pub user: Pubkey,
+    pub mint: Pubkey,
+}
+
+impl Event for MetadataUpdated {
+    const TAG: u8 = 3;
+}
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct NftBurned {
+    pub platform: Pubkey,
+    pub user: Pubkey,
+    pub mint: Pubkey,
+}
+
+impl Event for NftBurned {
+    const TAG: u8 = 4;
+}
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct ConfigUpdated {
+    pub platform: Pubkey,
+    pub mint_fee: u64,
+    pub fee_receiver: Pubkey,
+    pub fee_mint: Pubkey,
+    pub fee_schedule_enabled: u8,
+    pub treasury_enabled: u8,
+    pub fee_tier_count: u8,
+    pub config_delay: i64,
+}
+
+impl Event for ConfigUpdated {
+    const TAG: u8 = 5;
+}
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct NftTransferred {
+    pub platform: Pubkey,
+    pub from: Pubkey, // sending user PDA
+    pub to: Pubkey,   // receiving user PDA
+    pub mint: Pubkey,
+}
+
+impl Event for NftTransferred {
+    const TAG: u8 = 6;
+}
+
+/// Any event emitted by the program
+#[derive(Clone, Copy)]
+pub enum ProgramEvent {
+    PlatformInitialized(PlatformInitialized),
+    UserCreated(UserCreated),
+    NftMinted(NftMinted),
+    MetadataUpdated(MetadataUpdated),
+    NftBurned(NftBurned),
+    ConfigUpdated(ConfigUpdated),
+    NftTransferred(NftTransferred),
+}
+
+impl ProgramEvent {
+    /// Decode the base64-decoded fields of one `Program data:` log entry
+    pub fn decode(fields: &[&[u8]]) -> Option<Self> {
+        let [[tag], body] = fields else {
+            return None;
+        };
+
+        match *tag {
+            PlatformInitialized::TAG => read(body).map(Self::PlatformInitialized),
+            UserCreated::TAG => read(body).map(Self::UserCreated),
+            NftMinted::TAG => read(body).map(Self::NftMinted),
+            MetadataUpdated::TAG => read(body).map(Self::MetadataUpdated),
+            NftBurned::TAG => read(body).map(Self::NftBurned),
+            ConfigUpdated::TAG => read(body).map(Self::ConfigUpdated),
+            NftTransferred::TAG => read(body).map(Self::NftTransferred),
+            _ => None,
+        }
+    }
+}
+
+fn read<T: Event>(body: &[u8]) -> Option<T> {
+    try_pod_read_unaligned(body).ok()
+}
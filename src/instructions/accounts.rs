@@ -0,0 +1,446 @@
+//! Typed account wrappers, validated when an instruction's accounts are parsed so every
+//! handler runs the same signer, owner, initialization and address checks
+
+use alloc::string::{String, ToString};
+use core::marker::PhantomData;
+use core::ops::Deref;
+use pinocchio::ProgramResult;
+use pinocchio::account_info::{AccountInfo, Ref, RefMut};
+use pinocchio::instruction::Seed;
+use pinocchio::program_error::ProgramError;
+use pinocchio::pubkey::{Pubkey, create_program_address, try_find_program_address};
+use pinocchio_token_2022::ID as TOKEN_2022_ID;
+
+use super::{ID, user};
+use crate::error::UniPinoNftErr;
+use crate::state::nft_meta::NftMeta;
+use crate::state::platform::Platform;
+use crate::state::program_state::ProgramState;
+use crate::state::user::User;
+
+/// An account that signed the transaction
+#[derive(Clone, Copy)]
+pub struct SignerAccount<'a>(&'a AccountInfo);
+
+impl<'a> SignerAccount<'a> {
+    pub fn info(&self) -> &'a AccountInfo {
+        self.0
+    }
+}
+
+impl<'a> TryFrom<&'a AccountInfo> for SignerAccount<'a> {
+    type Error = ProgramError;
+
+    fn try_from(info: &'a AccountInfo) -> Result<Self, Self::Error> {
+        if !info.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        Ok(Self(info))
+    }
+}
+
+impl Deref for SignerAccount<'_> {
+    type Target = AccountInfo;
+
+    fn deref(&self) -> &AccountInfo {
+        self.0
+    }
+}
+
+/// An initialized account of this program holding a `T`
+pub struct ProgramAccount<'a, T> {
+    info: &'a AccountInfo,
+    state: PhantomData<T>,
+}
+
+impl<'a, T: ProgramState> ProgramAccount<'a, T> {
+    pub fn info(&self) -> &'a AccountInfo {
+        self.info
+    }
+
+    /// Borrow the account state
+    pub fn load(&self) -> Result<Ref<'a, T>, ProgramError> {
+        Ref::try_map(self.info.try_borrow_data()?, T::try_from_bytes).map_err(|(_, err)| err)
+    }
+
+    /// Mutably borrow the account state
+    pub fn load_mut(&self) -> Result<RefMut<'a, T>, ProgramError> {
+        RefMut::try_map(self.info.try_borrow_mut_data()?, T::try_from_bytes_mut)
+            .map_err(|(_, err)| err)
+    }
+}
+
+impl<'a, T: ProgramState> TryFrom<&'a AccountInfo> for ProgramAccount<'a, T> {
+    type Error = ProgramError;
+
+    fn try_from(info: &'a AccountInfo) -> Result<Self, Self::Error> {
+        T::check_account(info)?;
+
+        Ok(Self {
+            info,
+            state: PhantomData,
+        })
+    }
+}
+
+impl<T> Clone for ProgramAccount<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for ProgramAccount<'_, T> {}
+
+impl<T> Deref for ProgramAccount<'_, T> {
+    type Target = AccountInfo;
+
+    fn deref(&self) -> &AccountInfo {
+        self.info
+    }
+}
+
+impl<'a> ProgramAccount<'a, User> {
+    /// Check the user belongs to `platform_pda` and sits at the address derived from its
+    /// uuid and bumps
+    pub fn check_platform(
+        &self,
+        user: &User,
+        platform_pda: &AccountInfo,
+        platform: &Platform,
+    ) -> ProgramResult {
+        if user.owner != platform_pda.key().as_ref() {
+            return Err(UniPinoNftErr::UserMismatch.into());
+        }
+
+        if &UserSeeds::new(user, platform_pda, platform).address()? != self.key() {
+            return Err(UniPinoNftErr::UserMismatch.into());
+        }
+        Ok(())
+    }
+
+    /// Check the user holds the NFT of `mint`, whose metadata was checked against it: the
+    /// holder its metadata records, or the user it was minted for on metadata from before
+    /// holders were recorded
+    pub fn check_holder(
+        &self,
+        user: &User,
+        mint: &AccountInfo,
+        metadata: &AccountInfo,
+    ) -> ProgramResult {
+        match NftMeta::holder_of(metadata.try_borrow_data()?.as_ref())? {
+            Some(holder) if &holder == self.key() => Ok(()),
+            Some(_) => Err(UniPinoNftErr::NftNotHeld.into()),
+            None => Pda::nft_mint(mint, user.user_uuid, self).map(|_| ()),
+        }
+    }
+}
+
+/// Seeds of a user PDA, `["user_wallet", user_uuid as decimal, platform, platform bump,
+/// user bump]`, shared by the address checks and every CPI the user PDA signs
+pub struct UserSeeds {
+    user_uuid: String,
+    platform: Pubkey,
+    platform_bump: u8,
+    bump: u8,
+}
+
+impl UserSeeds {
+    /// Seeds of an existing user
+    pub fn new(user: &User, platform_pda: &AccountInfo, platform: &Platform) -> Self {
+        Self {
+            user_uuid: { user.user_uuid }.to_string(),
+            platform: *platform_pda.key(),
+            platform_bump: platform.bump,
+            bump: user.bump,
+        }
+    }
+
+    /// Seeds of the canonical user PDA of `user_uuid` on the platform, checking it is `info`
+    pub fn find(
+        info: &AccountInfo,
+        user_uuid: u128,
+        platform_pda: &AccountInfo,
+        platform: &Platform,
+    ) -> Result<Self, ProgramError> {
+        let mut seeds = Self {
+            user_uuid: user_uuid.to_string(),
+            platform: *platform_pda.key(),
+            platform_bump: platform.bump,
+            bump: 0,
+        };
+        seeds.bump = Pda::find(
+            info,
+            &[
+                user::USER_TOKEN,
+                seeds.user_uuid.as_bytes(),
+                seeds.platform.as_ref(),
+                core::slice::from_ref(&seeds.platform_bump),
+            ],
+            &ID,
+            UniPinoNftErr::UserMismatch,
+        )?
+        .bump;
+        Ok(seeds)
+    }
+
+    pub fn bump(&self) -> u8 {
+        self.bump
+    }
+
+    /// Seeds to sign a CPI as the user PDA
+    pub fn signer_seeds(&self) -> [Seed<'_>; 5] {
+        [
+            Seed::from(user::USER_TOKEN),
+            Seed::from(self.user_uuid.as_bytes()),
+            Seed::from(self.platform.as_ref()),
+            Seed::from(core::slice::from_ref(&self.platform_bump)),
+            Seed::from(core::slice::from_ref(&self.bump)),
+        ]
+    }
+
+    fn address(&self) -> Result<Pubkey, ProgramError> {
+        create_program_address(
+            &[
+                user::USER_TOKEN,
+                self.user_uuid.as_bytes(),
+                self.platform.as_ref(),
+                core::slice::from_ref(&self.platform_bump),
+                core::slice::from_ref(&self.bump),
+            ],
+            &ID,
+        )
+        .map_err(|_| UniPinoNftErr::UserMismatch.into())
+    }
+}
+
+/// An account at the program derived address of some seeds, initialized or not
+#[derive(Clone, Copy)]
+pub struct Pda<'a> {
+    info: &'a AccountInfo,
+    pub bump: u8,
+}
+
+impl<'a> Pda<'a> {
+    /// Check `info` is the canonical PDA of `seeds` under `program_id`, failing with
+    /// `mismatch` otherwise
+    pub fn find(
+        info: &'a AccountInfo,
+        seeds: &[&[u8]],
+        program_id: &Pubkey,
+        mismatch: UniPinoNftErr,
+    ) -> Result<Self, ProgramError> {
+        let (address, bump) =
+            try_find_program_address(seeds, program_id).ok_or(UniPinoNftErr::PdaErr)?;
+
+        if &address != info.key() {
+            return Err(mismatch.into());
+        }
+        Ok(Self { info, bump })
+    }
+
+    /// NFT mint of a user, PDA seeds `[user_uuid as le bytes, user, token program]`
+    /// under this program, which signs its creation before handing it to Token-2022
+    pub fn nft_mint(
+        info: &'a AccountInfo,
+        user_uuid: u128,
+        user_pda: &AccountInfo,
+    ) -> Result<Self, ProgramError> {
+        Self::find(
+            info,
+            &[
+                &user_uuid.to_le_bytes(),
+                user_pda.key().as_ref(),
+                &TOKEN_2022_ID,
+            ],
+            &ID,
+            UniPinoNftErr::MintMismatch,
+        )
+    }
+
+    /// Metadata of an NFT mint, PDA seeds `["metadata", mint, token program]` under this
+    /// program
+    pub fn nft_metadata(info: &'a AccountInfo, mint: &AccountInfo) -> Result<Self, ProgramError> {
+        Self::find(
+            info,
+            &[b"metadata", mint.key().as_ref(), &TOKEN_2022_ID],
+            &ID,
+            UniPinoNftErr::MetadataMismatch,
+        )
+    }
+
+    pub fn info(&self) -> &'a AccountInfo {
+        self.info
+    }
+
+    /// Whether the account has been created
+    pub fn is_initialized(&self) -> bool {
+        self.info.lamports() > 0
+    }
+}
+
+impl Deref for Pda<'_> {
+    type Target = AccountInfo;
+
+    fn deref(&self) -> &AccountInfo {
+        self.info
+    }
+}
+
+/// Close a program owned account, moving its lamports to `receiver` and zeroing its data
+pub fn close(info: &AccountInfo, receiver: &AccountInfo) -> ProgramResult {
+    let lamports = info.lamports();
+    *info.try_borrow_mut_lamports()? = 0;
+    let mut receiver_lamports = receiver.try_borrow_mut_lamports()?;
+    *receiver_lamports = receiver_lamports
+        .checked_add(lamports)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    info.try_borrow_mut_data()?.fill(0);
+    Ok(())
+}
+
+/// Accounts built from a loader input buffer, so handler checks run on the host
+#[cfg(test)]
+pub(crate) mod testing {
+    extern crate std;
+
+    use core::mem::MaybeUninit;
+    use std::vec::Vec;
+
+    use bytemuck::{Pod, bytes_of};
+    use pinocchio::account_info::{AccountInfo, MAX_PERMITTED_DATA_INCREASE};
+    use pinocchio::entrypoint::{NON_DUP_MARKER, deserialize};
+    use pinocchio::pubkey::Pubkey;
+
+    use super::ID;
+
+    pub struct TestAccount {
+        pub key: Pubkey,
+        pub owner: Pubkey,
+        pub lamports: u64,
+        pub data: Vec<u8>,
+        pub is_signer: bool,
+        pub is_writable: bool,
+    }
+
+    impl TestAccount {
+        pub fn new(key: Pubkey) -> Self {
+            Self {
+                key,
+                owner: Pubkey::default(),
+                lamports: 1,
+                data: Vec::new(),
+                is_signer: false,
+                is_writable: false,
+            }
+        }
+
+        /// Transaction signer without data
+        pub fn signer(key: Pubkey) -> Self {
+            Self {
+                is_signer: true,
+                ..Self::new(key)
+            }
+        }
+
+        /// Account of this program holding `state`
+        pub fn state<T: Pod>(key: Pubkey, state: &T) -> Self {
+            Self {
+                owner: ID,
+                data: bytes_of(state).to_vec(),
+                is_writable: true,
+                ..Self::new(key)
+            }
+        }
+    }
+
+    /// Key made of `byte` repeated, distinct per byte
+    pub fn key(byte: u8) -> Pubkey {
+        [byte; 32]
+    }
+
+    /// Serialize `accounts` the way the loader does and run `f` on the `AccountInfo`s
+    /// parsed back by the entrypoint deserializer
+    pub fn with_accounts<R>(accounts: &[TestAccount], f: impl FnOnce(&[AccountInfo]) -> R) -> R {
+        let mut input = Vec::new();
+        input.extend_from_slice(&(accounts.len() as u64).to_le_bytes());
+        for account in accounts {
+            input.extend_from_slice(&[
+                NON_DUP_MARKER,
+                account.is_signer as u8,
+                account.is_writable as u8,
+                0,
+            ]);
+            input.extend_from_slice(&0u32.to_le_bytes());
+            input.extend_from_slice(&account.key);
+            input.extend_from_slice(&account.owner);
+            input.extend_from_slice(&account.lamports.to_le_bytes());
+            input.extend_from_slice(&(account.data.len() as u64).to_le_bytes());
+            input.extend_from_slice(&account.data);
+            input.resize(input.len() + MAX_PERMITTED_DATA_INCREASE, 0);
+            input.resize(input.len().next_multiple_of(8), 0);
+            input.extend_from_slice(&0u64.to_le_bytes());
+        }
+        input.extend_from_slice(&0u64.to_le_bytes());
+        input.extend_from_slice(&ID);
+
+        // `u64` words keep the buffer 8 byte aligned like the loader input
+        let mut words = std::vec![0u64; input.len().div_ceil(8)];
+        bytemuck::cast_slice_mut::<u64, u8>(&mut words)[..input.len()].copy_from_slice(&input);
+
+        let mut infos = [const { MaybeUninit::<AccountInfo>::uninit() }; 16];
+        // SAFETY: `words` holds a loader input buffer and outlives every use of `infos`
+        let (_, count, _) = unsafe { deserialize(words.as_mut_ptr() as *mut u8, &mut infos) };
+        // SAFETY: the deserializer initialized the first `count` entries
+        let infos =
+            unsafe { core::slice::from_raw_parts(infos.as_ptr() as *const AccountInfo, count) };
+        f(infos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::testing::{TestAccount, key, with_accounts};
+    use super::*;
+
+    #[test]
+    fn signer_accounts_have_to_sign() {
+        let accounts = [TestAccount::signer(key(1)), TestAccount::new(key(2))];
+        with_accounts(&accounts, |infos| {
+            assert!(SignerAccount::try_from(&infos[0]).is_ok());
+            assert_eq!(
+                SignerAccount::try_from(&infos[1]).err(),
+                Some(ProgramError::MissingRequiredSignature)
+            );
+        });
+    }
+
+    #[test]
+    fn program_accounts_need_this_program_lamports_and_their_state() {
+        let user = User::new(key(9), 1, 255, 1);
+        let foreign = TestAccount {
+            owner: key(3),
+            ..TestAccount::state(key(2), &user)
+        };
+        let closed = TestAccount {
+            lamports: 0,
+            ..TestAccount::state(key(3), &user)
+        };
+        let platform = TestAccount::state(key(4), &Platform::new(key(5), 255));
+        let accounts = [TestAccount::state(key(1), &user), foreign, closed, platform];
+
+        with_accounts(&accounts, |infos| {
+            assert!(ProgramAccount::<User>::try_from(&infos[0]).is_ok());
+            for info in &infos[1..3] {
+                assert_eq!(
+                    ProgramAccount::<User>::try_from(info).err(),
+                    Some(UniPinoNftErr::UserInactive.into())
+                );
+            }
+            assert_eq!(
+                ProgramAccount::<User>::try_from(&infos[3]).err(),
+                Some(ProgramError::InvalidAccountData)
+            );
+        });
+    }
+}
@@ -0,0 +1,221 @@
+use pinocchio::ProgramResult;
+use pinocchio::account_info::AccountInfo;
+use pinocchio::instruction::{Seed, Signer};
+use pinocchio::program_error::ProgramError;
+use pinocchio::pubkey::Pubkey;
+use pinocchio::sysvars::Sysvar;
+use pinocchio::sysvars::rent::Rent;
+use pinocchio_system::instructions::CreateAccount;
+
+use crate::error::UniPinoNftErr;
+use crate::state::claim::Claim;
+use crate::state::collection::Collection;
+use crate::state::program_state::ProgramState;
+use crate::state::user::User;
+
+use super::ID;
+use super::accounts::{Pda, ProgramAccount};
+
+pub const CLAIM_TOKEN: &[u8] = b"claim";
+
+/// Hash prefixes keeping leaves and inner nodes apart, so a node cannot pass as a leaf
+pub const LEAF_PREFIX: &[u8] = &[0];
+pub const NODE_PREFIX: &[u8] = &[1];
+
+/// Longest proof accepted, enough for allowlists of 2^32 users
+pub const MAX_PROOF_NODES: usize = 32;
+
+fn hashv(vals: &[&[u8]]) -> [u8; 32] {
+    #[cfg(target_os = "solana")]
+    {
+        let mut hash = [0; 32];
+        // SAFETY: `vals` is a slice of byte slices and `hash` has room for the 32 byte digest
+        unsafe {
+            pinocchio::syscalls::sol_sha256(
+                vals as *const _ as *const u8,
+                vals.len() as u64,
+                hash.as_mut_ptr(),
+            );
+        }
+        hash
+    }
+
+    #[cfg(not(target_os = "solana"))]
+    {
+        use sha2::{Digest, Sha256};
+
+        vals.iter()
+            .fold(Sha256::new(), |hasher, val| hasher.chain_update(val))
+            .finalize()
+            .into()
+    }
+}
+
+/// Leaf of a user PDA, `sha256(0x00 || user_pda)`
+pub fn leaf(user_pda: &Pubkey) -> [u8; 32] {
+    hashv(&[LEAF_PREFIX, user_pda])
+}
+
+/// Parent of two nodes, `sha256(0x01 || min || max)`, so proofs need no left/right flags
+pub fn node(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (min, max) = if a <= b { (a, b) } else { (b, a) };
+    hashv(&[NODE_PREFIX, min, max])
+}
+
+/// Whether `proof` leads from `leaf` to `root`
+pub fn verify(root: &[u8; 32], leaf: [u8; 32], proof: &[[u8; 32]]) -> bool {
+    proof
+        .iter()
+        .fold(leaf, |hash, sibling| node(&hash, sibling))
+        == *root
+}
+
+/// Merkle proof appended to instruction data as a Borsh `Vec<[u8; 32]>`, a `u32` node
+/// count followed by the nodes; no data at all is an empty proof
+pub fn proof_nodes(data: &[u8]) -> Result<&[[u8; 32]], ProgramError> {
+    if data.is_empty() {
+        return Ok(&[]);
+    }
+
+    let (count, nodes) = data
+        .split_first_chunk::<4>()
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    let proof: &[[u8; 32]] =
+        bytemuck::try_cast_slice(nodes).map_err(|_| ProgramError::InvalidInstructionData)?;
+    if proof.len() != u32::from_le_bytes(*count) as usize || proof.len() > MAX_PROOF_NODES {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    Ok(proof)
+}
+
+/// Check `user_pda` is on the allowlist of `collection_pda` and count one mint against
+/// the phase limits, creating the claim PDA of the user on its first allowlist mint
+pub fn claim(
+    payer: &AccountInfo,
+    collection_pda: &AccountInfo,
+    user_pda: &AccountInfo,
+    user: &mut User,
+    claim_pda: &AccountInfo,
+    proof: &[[u8; 32]],
+) -> ProgramResult {
+    let collection_pda = ProgramAccount::<Collection>::try_from(collection_pda)?;
+    let mut collection = collection_pda.load_mut()?;
+
+    if !verify(&collection.allowlist_root, leaf(user_pda.key()), proof) {
+        return Err(UniPinoNftErr::NotAllowlisted.into());
+    }
+
+    if collection.phase_supply != 0 && collection.phase_minted >= collection.phase_supply {
+        return Err(UniPinoNftErr::AllowlistLimitReached.into());
+    }
+
+    let claim_pda = Pda::find(
+        claim_pda,
+        &[CLAIM_TOKEN, collection_pda.key(), user_pda.key()],
+        &ID,
+        UniPinoNftErr::PdaMismatch,
+    )?;
+    if !claim_pda.is_initialized() {
+        let bump = claim_pda.bump;
+        let claim_seeds = [
+            Seed::from(CLAIM_TOKEN),
+            Seed::from(collection_pda.key().as_ref()),
+            Seed::from(user_pda.key().as_ref()),
+            Seed::from(core::slice::from_ref(&bump)),
+        ];
+        let claim_signer = Signer::from(&claim_seeds);
+
+        CreateAccount {
+            from: payer,
+            to: &claim_pda,
+            lamports: Rent::get()?.minimum_balance(Claim::INIT_SPACE),
+            space: Claim::INIT_SPACE as u64,
+            owner: &ID,
+        }
+        .invoke_signed(&[claim_signer])?;
+
+        Claim::new(*collection_pda.key(), *user_pda.key(), bump).init(&claim_pda)?;
+        user.claim_count = user
+            .claim_count
+            .checked_add(1)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+    }
+
+    let claim_pda = ProgramAccount::<Claim>::try_from(claim_pda.info())?;
+    let mut claim = claim_pda.load_mut()?;
+    let phase = collection.phase;
+    let count = claim.count_in(phase);
+    if count >= collection.phase_user_limit {
+        return Err(UniPinoNftErr::AllowlistLimitReached.into());
+    }
+
+    claim.phase = phase;
+    claim.count = count + 1;
+    collection.phase_minted = collection
+        .phase_minted
+        .checked_add(1)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::vec::Vec;
+
+    use super::*;
+
+    /// Borsh `Vec` of `count` nodes filled with `byte`
+    fn encoded(count: usize, byte: u8) -> Vec<u8> {
+        let mut data = (count as u32).to_le_bytes().to_vec();
+        data.resize(4 + 32 * count, byte);
+        data
+    }
+
+    #[test]
+    fn proof_is_read_as_a_counted_list_of_nodes() {
+        assert_eq!(proof_nodes(&encoded(2, 7)), Ok(&[[7; 32], [7; 32]][..]));
+        assert_eq!(proof_nodes(&encoded(0, 7)), Ok(&[][..]));
+        assert_eq!(proof_nodes(&[]), Ok(&[][..]));
+
+        let data = encoded(2, 7);
+        for invalid in [&data[..3], &data[..data.len() - 1], &data[..36]] {
+            assert_eq!(
+                proof_nodes(invalid),
+                Err(ProgramError::InvalidInstructionData)
+            );
+        }
+        // raw nodes without their count
+        assert_eq!(
+            proof_nodes(&data[4..]),
+            Err(ProgramError::InvalidInstructionData)
+        );
+    }
+
+    #[test]
+    fn proofs_longer_than_the_maximum_are_rejected() {
+        assert_eq!(
+            proof_nodes(&encoded(MAX_PROOF_NODES, 0)).map(<[_]>::len),
+            Ok(MAX_PROOF_NODES)
+        );
+        assert_eq!(
+            proof_nodes(&encoded(MAX_PROOF_NODES + 1, 0)),
+            Err(ProgramError::InvalidInstructionData)
+        );
+    }
+
+    #[test]
+    fn nodes_are_ordered_and_domain_separated() {
+        let (a, b) = ([1; 32], [2; 32]);
+        assert_eq!(node(&a, &b), node(&b, &a));
+        assert_ne!(node(&a, &b), hashv(&[&a, &b]));
+        assert_ne!(leaf(&a), hashv(&[&a]));
+
+        // a single user allowlist has its leaf as root and an empty proof
+        assert!(verify(&leaf(&a), leaf(&a), &[]));
+        assert!(verify(&node(&leaf(&a), &leaf(&b)), leaf(&b), &[leaf(&a)]));
+        assert!(!verify(&node(&leaf(&a), &leaf(&b)), leaf(&b), &[leaf(&b)]));
+    }
+}
@@ -0,0 +1,277 @@
+use bytemuck::try_from_bytes;
+use pinocchio::ProgramResult;
+use pinocchio::account_info::AccountInfo;
+use pinocchio::instruction::{Seed, Signer};
+use pinocchio::program_error::ProgramError;
+use pinocchio::sysvars::Sysvar;
+use pinocchio::sysvars::rent::Rent;
+use pinocchio_log::log;
+use pinocchio_system::instructions::CreateAccount;
+
+use crate::error::UniPinoNftErr;
+use crate::state::collection::Collection;
+use crate::state::platform::Platform;
+use crate::state::program_state::ProgramState;
+use crate::state::role::ROLE_FEE_MANAGER;
+
+use super::accounts::{Pda, ProgramAccount, SignerAccount};
+use super::*;
+
+pub const COLLECTION_TOKEN: &[u8] = b"collection";
+
+/// Load the collection PDA named `name` for `platform_pda`
+pub fn load_collection(
+    collection_pda: &AccountInfo,
+    platform_pda: &AccountInfo,
+    name: &[u8; 64],
+) -> Result<Collection, ProgramError> {
+    let collection = *ProgramAccount::<Collection>::try_from(collection_pda)?.load()?;
+    if collection.platform != platform_pda.key().as_ref() {
+        return Err(UniPinoNftErr::PlatformMismatch.into());
+    }
+
+    if collection.name != *name {
+        return Err(UniPinoNftErr::CollectionMismatch.into());
+    }
+
+    Ok(collection)
+}
+
+/// Check `collection_pda` is the collection PDA named `name` of `platform_pda`, the
+/// 64 byte name split in two since seeds are limited to 32 bytes each
+pub fn find_collection<'a>(
+    collection_pda: &'a AccountInfo,
+    platform_pda: &AccountInfo,
+    name: &[u8; 64],
+) -> Result<Pda<'a>, ProgramError> {
+    Pda::find(
+        collection_pda,
+        &[
+            COLLECTION_TOKEN,
+            platform_pda.key().as_ref(),
+            &name[..32],
+            &name[32..],
+        ],
+        &ID,
+        UniPinoNftErr::PdaMismatch,
+    )
+}
+
+/// Reject a config naming no collection
+pub fn validate_config_args(config_args: &CollectionConfigArgs) -> ProgramResult {
+    if config_args.name == [0; 64] {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    Ok(())
+}
+
+/// Reject an allowlist nobody could mint from
+pub fn validate_allowlist_args(allowlist_args: &CollectionAllowlistArgs) -> ProgramResult {
+    if allowlist_args.allowlist_root != [0; 32] && allowlist_args.phase_user_limit == 0 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    Ok(())
+}
+
+/// Create the collection PDA if it does not exist yet, paid by `payer` and counted on
+/// the platform, and set its mint fee override
+pub fn apply_config(
+    payer: &AccountInfo,
+    platform_pda: &AccountInfo,
+    platform: &mut Platform,
+    collection_pda: &Pda,
+    config_args: &CollectionConfigArgs,
+) -> ProgramResult {
+    let name = &config_args.name;
+    let bump = collection_pda.bump;
+    if !collection_pda.is_initialized() {
+        let collection_seeds = [
+            Seed::from(COLLECTION_TOKEN),
+            Seed::from(platform_pda.key().as_ref()),
+            Seed::from(&name[..32]),
+            Seed::from(&name[32..]),
+            Seed::from(core::slice::from_ref(&bump)),
+        ];
+        let collection_signer = Signer::from(&collection_seeds);
+
+        let min_lamports = Rent::get()?.minimum_balance(Collection::INIT_SPACE);
+        CreateAccount {
+            from: payer,
+            to: collection_pda,
+            lamports: min_lamports,
+            space: Collection::INIT_SPACE as u64,
+            owner: &ID,
+        }
+        .invoke_signed(&[collection_signer])?;
+
+        let collection = Collection::new(*platform_pda.key(), *name, bump);
+        collection.init(collection_pda)?;
+        platform.collection_count = platform
+            .collection_count
+            .checked_add(1)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        log!("collection pda created");
+    }
+
+    let collection_pda = ProgramAccount::<Collection>::try_from(collection_pda.info())?;
+    let mut collection = collection_pda.load_mut()?;
+    collection.mint_fee = config_args.mint_fee;
+    collection.has_mint_fee = config_args.has_mint_fee;
+
+    log!("collection config updated");
+    Ok(())
+}
+
+/// Set the allowlist root and phase limits of an existing collection
+pub fn apply_allowlist(
+    collection: &mut Collection,
+    allowlist_args: &CollectionAllowlistArgs,
+) -> ProgramResult {
+    // a new phase starts counting allowlist mints from zero again; phases only move
+    // forward, so claims of an earlier phase never count again
+    if allowlist_args.phase < collection.phase {
+        return Err(UniPinoNftErr::PhaseNotIncreasing.into());
+    }
+    if collection.phase != allowlist_args.phase {
+        collection.phase_minted = 0;
+    }
+    collection.allowlist_root = allowlist_args.allowlist_root;
+    collection.phase = allowlist_args.phase;
+    collection.phase_user_limit = allowlist_args.phase_user_limit;
+    collection.phase_supply = allowlist_args.phase_supply;
+
+    log!("collection allowlist updated");
+    Ok(())
+}
+
+pub struct SetCollectionConfig<'a> {
+    pub authority: SignerAccount<'a>,
+    pub platform_pda: ProgramAccount<'a, Platform>,
+    pub collection_pda: Pda<'a>,
+    pub config_args: &'a CollectionConfigArgs,
+    pub remaining: &'a [AccountInfo],
+}
+
+impl<'a> SetCollectionConfig<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &7;
+
+    pub fn process(self) -> ProgramResult {
+        let mut platform = self.platform_pda.load_mut()?;
+
+        role::authorize(
+            &self.authority,
+            &self.platform_pda,
+            &platform,
+            ROLE_FEE_MANAGER,
+            self.remaining,
+        )?;
+
+        // collection prices are timelocked like the platform fee, see QueueCollectionConfig
+        if platform.config_delay > 0 {
+            return Err(UniPinoNftErr::ConfigTimelocked.into());
+        }
+
+        apply_config(
+            &self.authority,
+            &self.platform_pda,
+            &mut platform,
+            &self.collection_pda,
+            self.config_args,
+        )
+    }
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &'a [u8])> for SetCollectionConfig<'a> {
+    type Error = ProgramError;
+
+    fn try_from(value: (&'a [AccountInfo], &'a [u8])) -> Result<Self, Self::Error> {
+        let (accounts, instruction_data) = value;
+
+        let [authority, platform_pda, collection_pda, _, remaining @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if instruction_data.len() != size_of::<CollectionConfigArgs>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let config_args = try_from_bytes::<CollectionConfigArgs>(instruction_data)
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+        validate_config_args(config_args)?;
+
+        let platform_pda = ProgramAccount::try_from(platform_pda)?;
+        let collection_pda = find_collection(collection_pda, &platform_pda, &config_args.name)?;
+
+        Ok(Self {
+            authority: SignerAccount::try_from(authority)?,
+            platform_pda,
+            collection_pda,
+            config_args,
+            remaining,
+        })
+    }
+}
+
+pub struct SetCollectionAllowlist<'a> {
+    pub administrator: &'a AccountInfo,
+    pub platform_pda: ProgramAccount<'a, Platform>,
+    pub collection_pda: ProgramAccount<'a, Collection>,
+    pub allowlist_args: &'a CollectionAllowlistArgs,
+    pub remaining: &'a [AccountInfo],
+}
+
+impl<'a> SetCollectionAllowlist<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &25;
+
+    pub fn process(self) -> ProgramResult {
+        let platform = self.platform_pda.load()?;
+
+        multisig::authorize_admin(
+            self.administrator,
+            &self.platform_pda,
+            &platform,
+            self.remaining,
+        )?;
+
+        // allowlists are timelocked like the platform fee, see QueueCollectionAllowlist
+        if platform.config_delay > 0 {
+            return Err(UniPinoNftErr::ConfigTimelocked.into());
+        }
+
+        load_collection(
+            &self.collection_pda,
+            &self.platform_pda,
+            &self.allowlist_args.name,
+        )?;
+        let mut collection = self.collection_pda.load_mut()?;
+        apply_allowlist(&mut collection, self.allowlist_args)
+    }
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &'a [u8])> for SetCollectionAllowlist<'a> {
+    type Error = ProgramError;
+
+    fn try_from(value: (&'a [AccountInfo], &'a [u8])) -> Result<Self, Self::Error> {
+        let (accounts, instruction_data) = value;
+
+        let [administrator, platform_pda, collection_pda, remaining @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if instruction_data.len() != size_of::<CollectionAllowlistArgs>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let allowlist_args = try_from_bytes::<CollectionAllowlistArgs>(instruction_data)
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+        validate_allowlist_args(allowlist_args)?;
+
+        Ok(Self {
+            administrator,
+            platform_pda: ProgramAccount::try_from(platform_pda)?,
+            collection_pda: ProgramAccount::try_from(collection_pda)?,
+            allowlist_args,
+            remaining,
+        })
+    }
+}
@@ -0,0 +1,198 @@
+use pinocchio::ProgramResult;
+use pinocchio::account_info::AccountInfo;
+use pinocchio::instruction::Signer;
+use pinocchio::program_error::ProgramError;
+use pinocchio::pubkey::Pubkey;
+use pinocchio_log::log;
+use pinocchio_token_2022::ID as TOKEN_2022_ID;
+use pinocchio_token_2022::instructions::{Approve, Revoke};
+
+use crate::state::nft_meta::NftMeta;
+use crate::state::platform::{PAUSE_TRANSFER, Platform};
+use crate::state::user::User;
+
+use super::accounts::{Pda, ProgramAccount, SignerAccount, UserSeeds};
+use super::*;
+
+/// Accounts shared by the delegate handlers
+struct NftHolding<'a> {
+    authority: SignerAccount<'a>,
+    platform_pda: ProgramAccount<'a, Platform>,
+    user_pda: ProgramAccount<'a, User>,
+    mint_pda: &'a AccountInfo,
+    metadata_pda: ProgramAccount<'a, NftMeta>,
+    token_account: &'a AccountInfo,
+    token_program: &'a AccountInfo,
+    remaining: &'a [AccountInfo],
+}
+
+impl NftHolding<'_> {
+    /// Authorize `authority` for the instruction with `discriminator`, check the token
+    /// account holds the mint for the user PDA, then run `cpi` signed by the user PDA
+    /// and store the resulting delegate in the metadata
+    fn update_delegate(
+        &self,
+        discriminator: u8,
+        delegate: Pubkey,
+        cpi: impl FnOnce(&[Signer]) -> ProgramResult,
+    ) -> ProgramResult {
+        let platform = self.platform_pda.load()?;
+        // a delegate exists to move the NFT, so delegation pauses with transfers
+        platform.ensure_not_paused(PAUSE_TRANSFER)?;
+
+        let user = self.user_pda.load()?;
+        self.user_pda
+            .check_platform(&user, &self.platform_pda, &platform)?;
+        self.user_pda
+            .check_holder(&user, self.mint_pda, &self.metadata_pda)?;
+
+        session::authorize(
+            &self.authority,
+            &platform,
+            &self.user_pda,
+            &user,
+            discriminator,
+            self.remaining,
+        )?;
+
+        if self.token_program.key() != &TOKEN_2022_ID {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        fee::validate_token_account(
+            self.token_account,
+            self.mint_pda,
+            self.token_program,
+            self.user_pda.key(),
+        )?;
+
+        // the user PDA owns the holder token account
+        let user_seeds = UserSeeds::new(&user, &self.platform_pda, &platform);
+        cpi(&[Signer::from(&user_seeds.signer_seeds())])?;
+
+        // legacy metadata has no delegate slot and has to be migrated first
+        self.metadata_pda.load_mut()?.delegate = delegate;
+
+        Ok(())
+    }
+}
+
+pub struct ApproveNftDelegate<'a> {
+    holding: NftHolding<'a>,
+    delegate: &'a AccountInfo,
+}
+
+impl<'a> ApproveNftDelegate<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &22;
+
+    pub fn process(self) -> ProgramResult {
+        let holding = &self.holding;
+        holding.update_delegate(*Self::DISCRIMINATOR, *self.delegate.key(), |signers| {
+            Approve {
+                source: holding.token_account,
+                delegate: self.delegate,
+                authority: &holding.user_pda,
+                amount: 1,
+                token_program: &TOKEN_2022_ID,
+            }
+            .invoke_signed(signers)
+        })?;
+
+        log!("nft delegate approved: {}", self.delegate.key());
+        Ok(())
+    }
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for ApproveNftDelegate<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [
+            authority,
+            platform_pda,
+            user_pda,
+            mint_pda,
+            metadata_pda,
+            token_account,
+            delegate,
+            token_program,
+            remaining @ ..,
+        ] = accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        Ok(Self {
+            holding: NftHolding {
+                authority: SignerAccount::try_from(authority)?,
+                platform_pda: ProgramAccount::try_from(platform_pda)?,
+                user_pda: ProgramAccount::try_from(user_pda)?,
+                mint_pda,
+                metadata_pda: ProgramAccount::try_from(
+                    Pda::nft_metadata(metadata_pda, mint_pda)?.info(),
+                )?,
+                token_account,
+                token_program,
+                remaining,
+            },
+            delegate,
+        })
+    }
+}
+
+pub struct RevokeNftDelegate<'a> {
+    holding: NftHolding<'a>,
+}
+
+impl<'a> RevokeNftDelegate<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &23;
+
+    pub fn process(self) -> ProgramResult {
+        let holding = &self.holding;
+        holding.update_delegate(*Self::DISCRIMINATOR, Pubkey::default(), |signers| {
+            Revoke {
+                source: holding.token_account,
+                authority: &holding.user_pda,
+                token_program: &TOKEN_2022_ID,
+            }
+            .invoke_signed(signers)
+        })?;
+
+        log!("nft delegate revoked");
+        Ok(())
+    }
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for RevokeNftDelegate<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [
+            authority,
+            platform_pda,
+            user_pda,
+            mint_pda,
+            metadata_pda,
+            token_account,
+            token_program,
+            remaining @ ..,
+        ] = accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        Ok(Self {
+            holding: NftHolding {
+                authority: SignerAccount::try_from(authority)?,
+                platform_pda: ProgramAccount::try_from(platform_pda)?,
+                user_pda: ProgramAccount::try_from(user_pda)?,
+                mint_pda,
+                metadata_pda: ProgramAccount::try_from(
+                    Pda::nft_metadata(metadata_pda, mint_pda)?.info(),
+                )?,
+                token_account,
+                token_program,
+                remaining,
+            },
+        })
+    }
+}
@@ -0,0 +1,450 @@
+use pinocchio::ProgramResult;
+use pinocchio::account_info::AccountInfo;
+use pinocchio::program_error::ProgramError;
+use pinocchio::pubkey::Pubkey;
+use pinocchio_log::log;
+use pinocchio_pubkey::pubkey;
+use pinocchio_system::instructions::Transfer;
+use pinocchio_token_2022::ID as TOKEN_2022_ID;
+use pinocchio_token_2022::instructions::TransferChecked;
+use pinocchio_token_2022::state::{Mint, TokenAccount};
+
+use super::accounts::ProgramAccount;
+use super::treasury;
+use crate::error::UniPinoNftErr;
+use crate::state::collection::Collection;
+use crate::state::fee_schedule::{FeeSchedule, MAX_BPS};
+use crate::state::platform::Platform;
+
+/// Legacy SPL Token program, accepted next to Token-2022 for fee mints
+pub const TOKEN_PROGRAM_ID: Pubkey = pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+
+pub const FEE_SCHEDULE_TOKEN: &[u8] = b"fee_schedule";
+
+/// Extra accounts required when the platform charges fees in an SPL token.
+/// They are passed as remaining accounts, in this exact order.
+pub struct TokenFeeAccounts<'a> {
+    pub fee_mint: &'a AccountInfo,
+    pub payer_token_account: &'a AccountInfo,
+    pub token_program: &'a AccountInfo,
+    pub decimals: u8,
+}
+
+impl<'a> TokenFeeAccounts<'a> {
+    /// Split the token fee accounts off the front of `remaining` and validate
+    /// them against the platform fee mint
+    pub fn split(
+        platform: &Platform,
+        remaining: &'a [AccountInfo],
+    ) -> Result<(Self, &'a [AccountInfo]), ProgramError> {
+        let [fee_mint, payer_token_account, token_program, rest @ ..] = remaining else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if platform.fee_mint != fee_mint.key().as_ref() {
+            return Err(UniPinoNftErr::WrongFeeMint.into());
+        }
+
+        let decimals = mint_decimals(fee_mint, token_program)?;
+
+        Ok((
+            Self {
+                fee_mint,
+                payer_token_account,
+                token_program,
+                decimals,
+            },
+            rest,
+        ))
+    }
+}
+
+fn is_token_program(key: &Pubkey) -> bool {
+    *key == TOKEN_PROGRAM_ID || *key == TOKEN_2022_ID
+}
+
+/// Read the decimals of `mint`, checking it belongs to a supported token program
+pub fn mint_decimals(mint: &AccountInfo, token_program: &AccountInfo) -> Result<u8, ProgramError> {
+    if !is_token_program(token_program.key()) || !mint.is_owned_by(token_program.key()) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    if mint.data_len() < Mint::BASE_LEN {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let mint_data = mint.try_borrow_data()?;
+    // SAFETY: owner and length are checked above, and `Mint` has an alignment of 1
+    let mint = unsafe { Mint::from_bytes_unchecked(&mint_data) };
+    Ok(mint.decimals())
+}
+
+/// Check `token_account` is a token account of `mint` held by `holder`
+pub fn validate_token_account(
+    token_account: &AccountInfo,
+    mint: &AccountInfo,
+    token_program: &AccountInfo,
+    holder: &Pubkey,
+) -> ProgramResult {
+    if !token_account.is_owned_by(token_program.key()) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    if token_account.data_len() < TokenAccount::BASE_LEN {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let data = token_account.try_borrow_data()?;
+    // SAFETY: owner and length are checked above, and `TokenAccount` has an alignment of 1
+    let token_account = unsafe { TokenAccount::from_bytes_unchecked(&data) };
+    if token_account.mint() != mint.key() || token_account.owner() != holder {
+        return Err(UniPinoNftErr::WrongTokenAccount.into());
+    }
+
+    Ok(())
+}
+
+/// Mint price for a user of `tier_level`: the collection override if any, otherwise
+/// `Platform.mint_fee`, minus the discount of the user's fee tier
+pub fn mint_price(
+    platform: &Platform,
+    collection: Option<&Collection>,
+    tier_level: u8,
+) -> Result<u64, ProgramError> {
+    let base = collection
+        .and_then(Collection::mint_fee_override)
+        .unwrap_or(platform.mint_fee);
+    let discount_bps = platform.fee_discount_bps(tier_level).min(MAX_BPS);
+
+    let price = (base as u128)
+        .checked_mul((MAX_BPS - discount_bps) as u128)
+        .ok_or(ProgramError::ArithmeticOverflow)?
+        / MAX_BPS as u128;
+    u64::try_from(price).map_err(|_| ProgramError::ArithmeticOverflow)
+}
+
+/// Move `amount` from `payer` to `destination`, which must belong to `receiver`
+fn pay(
+    token: Option<&TokenFeeAccounts>,
+    payer: &AccountInfo,
+    destination: &AccountInfo,
+    receiver: &Pubkey,
+    amount: u64,
+) -> ProgramResult {
+    // the receiver is checked even for a zero share, so a wrong account order or a
+    // swapped receiver fails instead of passing silently while shares round to zero
+    let Some(token) = token else {
+        if destination.key() != receiver {
+            return Err(UniPinoNftErr::WrongFeeReceiver.into());
+        }
+        if amount == 0 {
+            return Ok(());
+        }
+
+        Transfer {
+            from: payer,
+            to: destination,
+            lamports: amount,
+        }
+        .invoke()?;
+
+        log!("collected fee: {} lamports", amount);
+        return Ok(());
+    };
+
+    validate_token_account(destination, token.fee_mint, token.token_program, receiver)?;
+    if amount == 0 {
+        return Ok(());
+    }
+
+    TransferChecked {
+        from: token.payer_token_account,
+        mint: token.fee_mint,
+        to: destination,
+        authority: payer,
+        amount,
+        decimals: token.decimals,
+        token_program: token.token_program.key(),
+    }
+    .invoke()?;
+
+    log!("collected fee: {} tokens", amount);
+    Ok(())
+}
+
+/// Collect `amount` from `payer` for the platform.
+///
+/// Remaining accounts are consumed in this order:
+/// - `[fee_mint, payer_token_account, token_program]` when a fee mint is set
+/// - `[fee_schedule, receiver_0, .., receiver_n]` when a fee schedule is enabled
+///
+/// Each schedule receiver gets its basis-point share, and whatever is left goes
+/// to `fee_receiver`, which must be the treasury PDA while the treasury is enabled.
+/// With a fee mint, every destination is a token account of that mint owned by
+/// the configured receiver.
+pub fn collect_fee(
+    platform_pda: &AccountInfo,
+    platform: &mut Platform,
+    payer: &AccountInfo,
+    fee_receiver: &AccountInfo,
+    amount: u64,
+    remaining: &[AccountInfo],
+) -> ProgramResult {
+    if amount == 0 {
+        return Ok(());
+    }
+
+    let (token, remaining) = if platform.has_fee_mint() {
+        let (token, rest) = TokenFeeAccounts::split(platform, remaining)?;
+        (Some(token), rest)
+    } else {
+        (None, remaining)
+    };
+
+    let mut remainder = amount;
+
+    if platform.has_fee_schedule() {
+        let [fee_schedule_pda, receivers @ ..] = remaining else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        let schedule = *ProgramAccount::<FeeSchedule>::try_from(fee_schedule_pda)?.load()?;
+        if schedule.platform != platform_pda.key().as_ref() {
+            return Err(UniPinoNftErr::PlatformMismatch.into());
+        }
+
+        let shares = schedule.shares();
+        if receivers.len() < shares.len() {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+
+        for (share, destination) in shares.iter().zip(receivers) {
+            let portion = share.portion(amount)?;
+            pay(token.as_ref(), payer, destination, &share.receiver, portion)?;
+            remainder = remainder
+                .checked_sub(portion)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+        }
+    }
+
+    if !platform.has_treasury() {
+        return pay(
+            token.as_ref(),
+            payer,
+            fee_receiver,
+            &platform.fee_receiver,
+            remainder,
+        );
+    }
+
+    let treasury = treasury::treasury_address(platform_pda.key(), platform.treasury_bump)?;
+    pay(token.as_ref(), payer, fee_receiver, &treasury, remainder)?;
+
+    if token.is_some() {
+        platform.treasury_tokens_collected = platform
+            .treasury_tokens_collected
+            .checked_add(remainder)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+    } else {
+        platform.treasury_lamports_collected = platform
+            .treasury_lamports_collected
+            .checked_add(remainder)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+    use bytemuck::Zeroable;
+
+    use super::super::accounts::testing::{TestAccount, key, with_accounts};
+    use super::*;
+    use crate::state::fee_schedule::FeeShare;
+    use crate::state::platform::FeeTier;
+
+    const PLATFORM: Pubkey = [1; 32];
+
+    fn platform_with_tiers(mint_fee: u64, tiers: &[(u32, u16)]) -> Platform {
+        let mut platform = Platform::new(key(2), 255);
+        platform.mint_fee = mint_fee;
+        platform.fee_tier_count = tiers.len() as u8;
+        for (tier, &(min_level, discount_bps)) in platform.fee_tiers.iter_mut().zip(tiers) {
+            *tier = FeeTier {
+                min_level,
+                discount_bps,
+            };
+        }
+        platform
+    }
+
+    #[test]
+    fn mint_price_applies_the_highest_reached_tier() {
+        let platform = platform_with_tiers(1_000, &[(1, 1_000), (5, 2_500), (10, MAX_BPS)]);
+
+        assert_eq!(mint_price(&platform, None, 0), Ok(1_000));
+        assert_eq!(mint_price(&platform, None, 4), Ok(900));
+        assert_eq!(mint_price(&platform, None, 5), Ok(750));
+        assert_eq!(mint_price(&platform, None, u8::MAX), Ok(0));
+    }
+
+    #[test]
+    fn mint_price_discounts_the_collection_override() {
+        let platform = platform_with_tiers(1_000, &[(1, 5_000)]);
+        let mut collection = Collection::new(PLATFORM, [0; 64], 255);
+        assert_eq!(mint_price(&platform, Some(&collection), 1), Ok(500));
+
+        collection.has_mint_fee = 1;
+        collection.mint_fee = 333;
+        assert_eq!(mint_price(&platform, Some(&collection), 0), Ok(333));
+        // rounded down
+        assert_eq!(mint_price(&platform, Some(&collection), 1), Ok(166));
+
+        // a zero override is a free collection, not a missing one
+        collection.mint_fee = 0;
+        assert_eq!(mint_price(&platform, Some(&collection), 0), Ok(0));
+    }
+
+    #[test]
+    fn mint_price_caps_discounts_and_does_not_overflow() {
+        let platform = platform_with_tiers(u64::MAX, &[(0, u16::MAX)]);
+        assert_eq!(mint_price(&platform, None, 0), Ok(0));
+
+        let platform = platform_with_tiers(u64::MAX, &[(1, 1)]);
+        assert_eq!(mint_price(&platform, None, 0), Ok(u64::MAX));
+        assert_eq!(
+            mint_price(&platform, None, 1),
+            Ok((u64::MAX as u128 * 9_999 / 10_000) as u64)
+        );
+    }
+
+    #[test]
+    fn shares_round_down_and_never_exceed_the_amount() {
+        let share = |bps| FeeShare {
+            receiver: key(3),
+            bps,
+        };
+        assert_eq!(share(2_500).portion(999), Ok(249));
+        assert_eq!(share(MAX_BPS).portion(u64::MAX), Ok(u64::MAX));
+        assert_eq!(share(0).portion(u64::MAX), Ok(0));
+
+        let shares = [share(3_333), share(3_333), share(3_334)];
+        for amount in [0, 1, 2, 3, 10, 9_999, 1_000_001, u64::MAX] {
+            let total: u128 = shares
+                .iter()
+                .map(|share| share.portion(amount).unwrap() as u128)
+                .sum();
+            assert!(total <= amount as u128, "{}", amount);
+        }
+    }
+
+    /// Platform charging lamport fees, split 25% and 10% by its fee schedule
+    fn split_platform() -> (Platform, FeeSchedule) {
+        let mut platform = Platform::new(key(2), 255);
+        platform.fee_receiver = key(20);
+        platform.fee_schedule_enabled = 1;
+
+        let mut schedule = FeeSchedule::new(PLATFORM, 255);
+        schedule.share_count = 2;
+        schedule.shares[0] = FeeShare {
+            receiver: key(21),
+            bps: 2_500,
+        };
+        schedule.shares[1] = FeeShare {
+            receiver: key(22),
+            bps: 1_000,
+        };
+        (platform, schedule)
+    }
+
+    /// Accounts `[platform, payer, fee_receiver, fee_schedule, receivers..]`
+    fn fee_accounts(schedule: &FeeSchedule, receivers: &[Pubkey]) -> Vec<TestAccount> {
+        let mut accounts = alloc::vec![
+            TestAccount::new(PLATFORM),
+            TestAccount {
+                is_writable: true,
+                ..TestAccount::signer(key(9))
+            },
+            TestAccount::new(key(20)),
+            TestAccount::state(key(4), schedule),
+        ];
+        accounts.extend(receivers.iter().map(|&receiver| TestAccount {
+            is_writable: true,
+            ..TestAccount::new(receiver)
+        }));
+        accounts
+    }
+
+    fn collect(
+        platform: &mut Platform,
+        accounts: &[TestAccount],
+        amount: u64,
+    ) -> Result<(), ProgramError> {
+        with_accounts(accounts, |infos| {
+            collect_fee(
+                &infos[0],
+                platform,
+                &infos[1],
+                &infos[2],
+                amount,
+                &infos[3..],
+            )
+        })
+    }
+
+    #[test]
+    fn schedule_receivers_are_paid_in_order() {
+        let (mut platform, schedule) = split_platform();
+
+        let accounts = fee_accounts(&schedule, &[key(21), key(22)]);
+        assert_eq!(collect(&mut platform, &accounts, 1_000), Ok(()));
+
+        let accounts = fee_accounts(&schedule, &[key(22), key(21)]);
+        assert_eq!(
+            collect(&mut platform, &accounts, 1_000),
+            Err(UniPinoNftErr::WrongFeeReceiver.into())
+        );
+
+        // receivers are checked even when every share rounds down to zero
+        assert_eq!(
+            collect(&mut platform, &accounts, 3),
+            Err(UniPinoNftErr::WrongFeeReceiver.into())
+        );
+    }
+
+    #[test]
+    fn schedule_needs_every_receiver_and_its_own_platform() {
+        let (mut platform, mut schedule) = split_platform();
+
+        let accounts = fee_accounts(&schedule, &[key(21)]);
+        assert_eq!(
+            collect(&mut platform, &accounts, 1_000),
+            Err(ProgramError::NotEnoughAccountKeys)
+        );
+
+        schedule.platform = key(3);
+        let accounts = fee_accounts(&schedule, &[key(21), key(22)]);
+        assert_eq!(
+            collect(&mut platform, &accounts, 1_000),
+            Err(UniPinoNftErr::PlatformMismatch.into())
+        );
+    }
+
+    #[test]
+    fn remainder_goes_to_the_fee_receiver() {
+        let (mut platform, _) = split_platform();
+        platform.fee_schedule_enabled = 0;
+
+        let accounts = fee_accounts(&FeeSchedule::zeroed(), &[]);
+        assert_eq!(collect(&mut platform, &accounts, 1_000), Ok(()));
+
+        platform.fee_receiver = key(23);
+        assert_eq!(
+            collect(&mut platform, &accounts, 1_000),
+            Err(UniPinoNftErr::WrongFeeReceiver.into())
+        );
+        // nothing is checked for a free mint
+        assert_eq!(collect(&mut platform, &accounts, 0), Ok(()));
+    }
+}
@@ -0,0 +1,317 @@
+use bytemuck::bytes_of;
+use pinocchio::account_info::AccountInfo;
+use pinocchio::program_error::ProgramError;
+use pinocchio::pubkey::Pubkey;
+use pinocchio::sysvars::Sysvar;
+use pinocchio::sysvars::clock::Clock;
+use pinocchio::sysvars::instructions::Instructions;
+use pinocchio_pubkey::pubkey;
+
+use super::UserIntent;
+use crate::error::UniPinoNftErr;
+use crate::state::user::User;
+
+/// Native program verifying Ed25519 signatures listed in its instruction data
+pub const ED25519_PROGRAM_ID: Pubkey = pubkey!("Ed25519SigVerify111111111111111111111111111");
+
+// `UserIntent.action` values, one per handler accepting intents
+pub const INTENT_BURN_NFT: u8 = 1;
+
+/// Offsets of a single-signature Ed25519 program instruction
+const SIGNATURE_COUNT_OFFSET: usize = 0;
+const SIGNATURE_INSTRUCTION_INDEX_OFFSET: usize = 4;
+const PUBLIC_KEY_OFFSET_OFFSET: usize = 6;
+const PUBLIC_KEY_INSTRUCTION_INDEX_OFFSET: usize = 8;
+const MESSAGE_OFFSET_OFFSET: usize = 10;
+const MESSAGE_SIZE_OFFSET: usize = 12;
+const MESSAGE_INSTRUCTION_INDEX_OFFSET: usize = 14;
+const ED25519_HEADER_LEN: usize = 16;
+
+/// Index value telling the Ed25519 program the data lives in its own instruction
+const CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+/// Check `intent` was signed for `action` on `mint` by the wallet linked to `user`, and
+/// consume its nonce. The signature is checked by an Ed25519 program instruction placed
+/// right before the current one, found through the instructions sysvar.
+pub fn consume(
+    instructions_sysvar: &AccountInfo,
+    user_pda: &AccountInfo,
+    user: &mut User,
+    mint: &AccountInfo,
+    action: u8,
+    intent: &UserIntent,
+) -> Result<(), ProgramError> {
+    if !user.has_authority() {
+        return Err(UniPinoNftErr::InvalidIntent.into());
+    }
+
+    if intent.action != action
+        || intent.user != user_pda.key().as_ref()
+        || intent.generation != user.generation
+        || intent.mint != mint.key().as_ref()
+        || intent.nonce != user.nonce
+    {
+        return Err(UniPinoNftErr::InvalidIntent.into());
+    }
+
+    if Clock::get()?.unix_timestamp > intent.expiry {
+        return Err(UniPinoNftErr::IntentExpired.into());
+    }
+
+    verify_signature(instructions_sysvar, &user.authority, bytes_of(intent))?;
+
+    user.nonce = user
+        .nonce
+        .checked_add(1)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    Ok(())
+}
+
+/// Check the previous instruction verified `signer`'s signature over `message`
+fn verify_signature(
+    instructions_sysvar: &AccountInfo,
+    signer: &Pubkey,
+    message: &[u8],
+) -> Result<(), ProgramError> {
+    let instructions = Instructions::try_from(instructions_sysvar)?;
+    let ed25519_instruction = instructions.get_instruction_relative(-1)?;
+    if ed25519_instruction.get_program_id() != &ED25519_PROGRAM_ID {
+        return Err(UniPinoNftErr::InvalidIntent.into());
+    }
+
+    let data = ed25519_instruction.get_instruction_data();
+    if data.len() < ED25519_HEADER_LEN || data[SIGNATURE_COUNT_OFFSET] != 1 {
+        return Err(UniPinoNftErr::InvalidIntent.into());
+    }
+
+    let read_u16 = |offset: usize| u16::from_le_bytes([data[offset], data[offset + 1]]);
+
+    // signature, key and message all have to be embedded in the Ed25519 instruction,
+    // otherwise it could have verified data taken from another instruction
+    if read_u16(SIGNATURE_INSTRUCTION_INDEX_OFFSET) != CURRENT_INSTRUCTION
+        || read_u16(PUBLIC_KEY_INSTRUCTION_INDEX_OFFSET) != CURRENT_INSTRUCTION
+        || read_u16(MESSAGE_INSTRUCTION_INDEX_OFFSET) != CURRENT_INSTRUCTION
+    {
+        return Err(UniPinoNftErr::InvalidIntent.into());
+    }
+
+    let public_key_offset = read_u16(PUBLIC_KEY_OFFSET_OFFSET) as usize;
+    let message_offset = read_u16(MESSAGE_OFFSET_OFFSET) as usize;
+    let message_size = read_u16(MESSAGE_SIZE_OFFSET) as usize;
+
+    let signed_key = data.get(public_key_offset..public_key_offset + size_of::<Pubkey>());
+    let signed_message = data.get(message_offset..message_offset + message_size);
+    if signed_key != Some(signer.as_ref()) || signed_message != Some(message) {
+        return Err(UniPinoNftErr::InvalidIntent.into());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+    use pinocchio::sysvars::instructions::INSTRUCTIONS_ID;
+
+    use super::super::accounts::testing::{TestAccount, key, with_accounts};
+    use super::*;
+
+    const USER: Pubkey = [1; 32];
+    const MINT: Pubkey = [2; 32];
+    const WALLET: Pubkey = [3; 32];
+
+    fn intent() -> UserIntent {
+        UserIntent {
+            action: INTENT_BURN_NFT,
+            user: USER,
+            generation: 7,
+            mint: MINT,
+            nonce: 3,
+            expiry: i64::MAX,
+        }
+    }
+
+    /// Ed25519 program data verifying `signer` over `message`, all in the instruction
+    fn ed25519_data(signer: &Pubkey, message: &[u8]) -> Vec<u8> {
+        let public_key_offset = ED25519_HEADER_LEN as u16;
+        let signature_offset = public_key_offset + 32;
+        let message_offset = signature_offset + 64;
+
+        let mut data = alloc::vec![1, 0];
+        for value in [
+            signature_offset,
+            CURRENT_INSTRUCTION,
+            public_key_offset,
+            CURRENT_INSTRUCTION,
+            message_offset,
+            message.len() as u16,
+            CURRENT_INSTRUCTION,
+        ] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(signer);
+        data.extend_from_slice(&[0; 64]);
+        data.extend_from_slice(message);
+        data
+    }
+
+    /// Instructions sysvar data of a transaction running `instructions`, the last one
+    /// being the current instruction
+    fn sysvar_data(instructions: &[(Pubkey, Vec<u8>)]) -> Vec<u8> {
+        let mut serialized = Vec::new();
+        let mut offsets = Vec::new();
+        let header_len = 2 + 2 * instructions.len();
+        for (program_id, data) in instructions {
+            offsets.push((header_len + serialized.len()) as u16);
+            serialized.extend_from_slice(&0u16.to_le_bytes());
+            serialized.extend_from_slice(program_id);
+            serialized.extend_from_slice(&(data.len() as u16).to_le_bytes());
+            serialized.extend_from_slice(data);
+        }
+
+        let mut sysvar = (instructions.len() as u16).to_le_bytes().to_vec();
+        offsets
+            .iter()
+            .for_each(|offset| sysvar.extend_from_slice(&offset.to_le_bytes()));
+        sysvar.extend_from_slice(&serialized);
+        sysvar.extend_from_slice(&(instructions.len() as u16 - 1).to_le_bytes());
+        sysvar
+    }
+
+    fn verify(sysvar: Vec<u8>) -> Result<(), ProgramError> {
+        let accounts = [TestAccount {
+            data: sysvar,
+            ..TestAccount::new(INSTRUCTIONS_ID)
+        }];
+        with_accounts(&accounts, |infos| {
+            verify_signature(&infos[0], &WALLET, bytemuck::bytes_of(&intent()))
+        })
+    }
+
+    fn signed_by(signer: &Pubkey, intent: &UserIntent) -> Vec<u8> {
+        sysvar_data(&[
+            (
+                ED25519_PROGRAM_ID,
+                ed25519_data(signer, bytemuck::bytes_of(intent)),
+            ),
+            (super::super::ID, alloc::vec![5]),
+        ])
+    }
+
+    #[test]
+    fn previous_ed25519_instruction_verifies_the_intent() {
+        assert_eq!(verify(signed_by(&WALLET, &intent())), Ok(()));
+    }
+
+    #[test]
+    fn signatures_of_other_keys_or_messages_are_rejected() {
+        assert_eq!(
+            verify(signed_by(&key(4), &intent())),
+            Err(UniPinoNftErr::InvalidIntent.into())
+        );
+
+        let replayed = UserIntent {
+            nonce: 2,
+            ..intent()
+        };
+        assert_eq!(
+            verify(signed_by(&WALLET, &replayed)),
+            Err(UniPinoNftErr::InvalidIntent.into())
+        );
+    }
+
+    #[test]
+    fn signature_data_has_to_be_embedded_in_the_ed25519_instruction() {
+        let mut data = ed25519_data(&WALLET, bytemuck::bytes_of(&intent()));
+        // the verified key taken from the current instruction instead
+        data[PUBLIC_KEY_INSTRUCTION_INDEX_OFFSET..][..2].copy_from_slice(&1u16.to_le_bytes());
+        let sysvar = sysvar_data(&[
+            (ED25519_PROGRAM_ID, data),
+            (super::super::ID, alloc::vec![5]),
+        ]);
+        assert_eq!(verify(sysvar), Err(UniPinoNftErr::InvalidIntent.into()));
+    }
+
+    #[test]
+    fn intent_needs_a_previous_ed25519_instruction() {
+        let data = ed25519_data(&WALLET, bytemuck::bytes_of(&intent()));
+        let sysvar = sysvar_data(&[(key(6), data), (super::super::ID, alloc::vec![5])]);
+        assert_eq!(verify(sysvar), Err(UniPinoNftErr::InvalidIntent.into()));
+
+        let sysvar = sysvar_data(&[(super::super::ID, alloc::vec![5])]);
+        assert_eq!(verify(sysvar), Err(ProgramError::InvalidInstructionData));
+    }
+
+    #[test]
+    fn only_the_instructions_sysvar_is_read() {
+        let accounts = [TestAccount {
+            data: signed_by(&WALLET, &intent()),
+            ..TestAccount::new(key(7))
+        }];
+        let result = with_accounts(&accounts, |infos| {
+            verify_signature(&infos[0], &WALLET, bytemuck::bytes_of(&intent()))
+        });
+        assert_eq!(result, Err(ProgramError::UnsupportedSysvar));
+    }
+
+    #[test]
+    fn intent_has_to_match_the_user_and_its_nonce() {
+        let mut user = User::new(key(8), 42, 255, 7);
+        user.authority = WALLET;
+        user.nonce = 3;
+
+        let accounts = [
+            TestAccount::new(INSTRUCTIONS_ID),
+            TestAccount::new(USER),
+            TestAccount::new(MINT),
+        ];
+        let consume_intent = |user: &mut User, intent: UserIntent| {
+            with_accounts(&accounts, |infos| {
+                consume(
+                    &infos[0],
+                    &infos[1],
+                    user,
+                    &infos[2],
+                    INTENT_BURN_NFT,
+                    &intent,
+                )
+            })
+        };
+
+        for intent in [
+            UserIntent {
+                action: INTENT_BURN_NFT + 1,
+                ..intent()
+            },
+            UserIntent {
+                user: key(9),
+                ..intent()
+            },
+            UserIntent {
+                generation: 6,
+                ..intent()
+            },
+            UserIntent {
+                mint: key(9),
+                ..intent()
+            },
+            UserIntent {
+                nonce: 2,
+                ..intent()
+            },
+        ] {
+            assert_eq!(
+                consume_intent(&mut user, intent),
+                Err(UniPinoNftErr::InvalidIntent.into())
+            );
+        }
+
+        // users without a linked wallet cannot sign intents
+        user.authority = Pubkey::default();
+        assert_eq!(
+            consume_intent(&mut user, intent()),
+            Err(UniPinoNftErr::InvalidIntent.into())
+        );
+        assert_eq!({ user.nonce }, 3);
+    }
+}
@@ -0,0 +1,140 @@
+use pinocchio::ProgramResult;
+use pinocchio::account_info::AccountInfo;
+use pinocchio::program_error::ProgramError;
+use pinocchio::sysvars::Sysvar;
+use pinocchio::sysvars::rent::Rent;
+use pinocchio_log::log;
+use pinocchio_system::instructions::Transfer;
+
+use crate::error::UniPinoNftErr;
+use crate::state::nft_meta::NftMeta;
+use crate::state::platform::Platform;
+use crate::state::program_state::ProgramState;
+use crate::state::user::User;
+
+use super::accounts::{Pda, ProgramAccount};
+use super::*;
+
+pub struct MigrateAccount<'a> {
+    pub administrator: &'a AccountInfo,
+    pub platform_pda: ProgramAccount<'a, Platform>,
+    pub account: &'a AccountInfo,
+    pub remaining: &'a [AccountInfo],
+}
+
+impl<'a> MigrateAccount<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &17;
+
+    pub fn process(self) -> ProgramResult {
+        // the migrated account can have any layout, so only its owner is checked here
+        if !self.account.is_owned_by(&ID) || self.account.lamports() == 0 {
+            return Err(UniPinoNftErr::UninitPda.into());
+        }
+
+        // the platform itself may still be in a layout shorter than `Platform`
+        let platform = Platform::read(self.platform_pda.try_borrow_data()?.as_ref())?;
+        let remaining = multisig::authorize_admin(
+            self.administrator,
+            &self.platform_pda,
+            &platform,
+            self.remaining,
+        )?;
+
+        let discriminator: [u8; 8] = self
+            .account
+            .try_borrow_data()?
+            .get(..8)
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or(ProgramError::InvalidAccountData)?;
+
+        match discriminator {
+            Platform::DISCRIMINATOR => {
+                if self.account.key() != self.platform_pda.key() {
+                    return Err(UniPinoNftErr::PlatformMismatch.into());
+                }
+                self.grow(Platform::INIT_SPACE)?;
+
+                let mut data = self.account.try_borrow_mut_data()?;
+                let migrated = Platform::try_from_bytes_mut(data.as_mut())?;
+                migrated.seed_key = platform.seed_key;
+                migrated.version = Platform::CURRENT_VERSION;
+            }
+            User::DISCRIMINATOR => {
+                self.grow(User::INIT_SPACE)?;
+
+                let mut data = self.account.try_borrow_mut_data()?;
+                let user = User::try_from_bytes_mut(data.as_mut())?;
+                if user.owner != self.platform_pda.key().as_ref() {
+                    return Err(UniPinoNftErr::UserMismatch.into());
+                }
+                user.version = User::CURRENT_VERSION;
+            }
+            NftMeta::DISCRIMINATOR => {
+                // metadata PDAs are derived from their mint only, the user holding the NFT
+                // ties it to the platform
+                let [user_pda, mint, ..] = remaining else {
+                    return Err(ProgramError::NotEnoughAccountKeys);
+                };
+                NftMeta::version_of(self.account.try_borrow_data()?.as_ref())?;
+                Pda::nft_metadata(self.account, mint)?;
+
+                let user_pda = ProgramAccount::<User>::try_from(user_pda)?;
+                let user = user_pda.load()?;
+                user_pda.check_platform(&user, &self.platform_pda, &platform)?;
+                user_pda.check_holder(&user, mint, self.account)?;
+                drop(user);
+
+                self.grow(NftMeta::INIT_SPACE)?;
+
+                let mut data = self.account.try_borrow_mut_data()?;
+                let metadata = NftMeta::try_from_bytes_mut(data.as_mut())?;
+                metadata.version = NftMeta::CURRENT_VERSION;
+            }
+            _ => return Err(ProgramError::InvalidAccountData),
+        }
+
+        log!("account migrated to the current layout");
+        Ok(())
+    }
+
+    /// Realloc the account to `space`, topping up its rent from the administrator
+    fn grow(&self, space: usize) -> ProgramResult {
+        let data_len = self.account.data_len();
+        if data_len == space {
+            return Ok(());
+        }
+        if data_len > space {
+            return Err(UniPinoNftErr::UnsupportedVersion.into());
+        }
+
+        let min_lamports = Rent::get()?.minimum_balance(space);
+        let lamports = self.account.lamports();
+        if lamports < min_lamports {
+            Transfer {
+                from: self.administrator,
+                to: self.account,
+                lamports: min_lamports - lamports,
+            }
+            .invoke()?;
+        }
+
+        self.account.resize(space)
+    }
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for MigrateAccount<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [administrator, platform_pda, account, _, remaining @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        Ok(Self {
+            administrator,
+            platform_pda: ProgramAccount::try_from(platform_pda)?,
+            account,
+            remaining,
+        })
+    }
+}
@@ -1,11 +1,30 @@
+pub mod accounts;
+pub mod allowlist;
+pub mod collection;
+pub mod delegate;
+pub mod fee;
+pub mod intent;
+pub mod migrate;
+pub mod multisig;
 pub mod nft;
+pub mod ownership;
 pub mod platform;
+pub mod role;
+pub mod session;
+pub mod timelock;
+pub mod transfer;
+pub mod treasury;
 pub mod user;
 
+use alloc::vec::Vec;
 use bytemuck::{Pod, Zeroable};
+use pinocchio::program_error::ProgramError;
 use pinocchio::pubkey::Pubkey;
 use pinocchio_pubkey::declare_id;
-use shank::ShankInstruction;
+use shank::{ShankInstruction, ShankType};
+
+use crate::state::fee_schedule::FeeShare;
+use crate::state::platform::FeeTier;
 
 declare_id!("6jpuWYTM3ARc5CHrMBtR1c7gyjkMTsJoYT7PqqhMpRWh");
 
@@ -21,6 +40,7 @@ pub enum UniPinoNftInstruction {
     )]
     #[account(
         1,
+        writable,
         name = "platform PDA",
         desc = "account for on-chain platform management"
     )]
@@ -41,6 +61,26 @@ pub enum UniPinoNftInstruction {
         desc = "account for on-chain platform management"
     )]
     #[account(2, name = "system_program")]
+    #[account(
+        3,
+        optional,
+        name = "admin multisig PDA",
+        desc = "required while the multisig is enabled, followed by threshold member signers"
+    )]
+    #[account(
+        4,
+        optional,
+        writable,
+        name = "fee schedule PDA",
+        desc = "required when a non-empty fee schedule is set"
+    )]
+    #[account(
+        5,
+        optional,
+        writable,
+        name = "treasury PDA",
+        desc = "required when the treasury is enabled, after the fee schedule PDA if any"
+    )]
     UpdatePlatform { args: UpdatePlatformArgs },
 
     #[account(
@@ -58,6 +98,13 @@ pub enum UniPinoNftInstruction {
     )]
     #[account(2, writable, name = "user wallet PDA")]
     #[account(3, name = "system_program")]
+    #[account(
+        4,
+        optional,
+        signer,
+        name = "approvers",
+        desc = "admin multisig PDA followed by threshold member signers, while it is enabled"
+    )]
     CreateUser { user_uuid: u128 },
     /* TODO
     ActivateUserWallet,
@@ -68,7 +115,7 @@ pub enum UniPinoNftInstruction {
         signer,
         writable,
         name = "authority account",
-        desc = "init account, and it is responsible for paying gas and NFT rent"
+        desc = "administrator or minter, responsible for paying gas and NFT rent"
     )]
     #[account(
         1,
@@ -82,18 +129,75 @@ pub enum UniPinoNftInstruction {
     #[account(
         5,
         writable,
+        name = "user token account",
+        desc = "Token-2022 associated token account of the user PDA, created for the mint"
+    )]
+    #[account(
+        6,
+        writable,
         name = "fee_receiver",
-        desc = "account to receive mint fees"
+        desc = "fee receiver or treasury PDA, or its token account when a fee mint is set"
+    )]
+    #[account(7, name = "system_program")]
+    #[account(8, name = "token_program", desc = "Token-2022 program")]
+    #[account(9, name = "associated_token_program")]
+    #[account(
+        10,
+        optional,
+        name = "role PDA",
+        desc = "required when authority is a minter; optional accounts are left out, not replaced"
+    )]
+    #[account(
+        11,
+        optional,
+        writable,
+        name = "collection PDA",
+        desc = "required when minting into a collection, writable when it has an allowlist"
+    )]
+    #[account(
+        12,
+        optional,
+        writable,
+        name = "claim PDA",
+        desc = "allowlist claim of the user, required when the collection has an allowlist"
+    )]
+    #[account(
+        13,
+        optional,
+        name = "fee_mint",
+        desc = "required when a fee mint is set"
+    )]
+    #[account(
+        14,
+        optional,
+        writable,
+        name = "payer token account",
+        desc = "authority token account the fee is paid from"
+    )]
+    #[account(
+        15,
+        optional,
+        name = "fee token program",
+        desc = "Token or Token-2022 program of the fee mint"
+    )]
+    #[account(
+        16,
+        optional,
+        name = "fee schedule PDA",
+        desc = "required when fee splitting is enabled, followed by each share receiver in order"
     )]
-    #[account(6, name = "system_program")]
-    MintNft { mint_nft_args: MintNftArgs },
+    MintNft {
+        mint_nft_args: MintNftArgs,
+        // allowlist merkle proof, empty outside allowlisted collections
+        proof: Vec<[u8; 32]>,
+    },
 
     #[account(
         0,
         signer,
         writable,
         name = "authority account",
-        desc = "init account, and it is responsible for paying gas and NFT rent"
+        desc = "administrator or metadata editor"
     )]
     #[account(
         1,
@@ -105,14 +209,20 @@ pub enum UniPinoNftInstruction {
     #[account(3, writable, name = "mint PDA")]
     #[account(4, writable, name = "metadata PDA")]
     #[account(5, name = "system_program")]
-    UpdateNFTMetadata { nft_meta: NftMeta },
+    #[account(
+        6,
+        optional,
+        name = "role PDA",
+        desc = "required when authority is a metadata editor"
+    )]
+    UpdateNFTMetadata { nft_meta: NftMetaArgs },
 
     #[account(
         0,
-        signer,
+        optional_signer,
         writable,
-        name = "authority account",
-        desc = "init account, and it is responsible for paying gas and NFT rent"
+        name = "administrator",
+        desc = "platform administrator, receives the rent; signs unless a holder does"
     )]
     #[account(
         1,
@@ -123,40 +233,922 @@ pub enum UniPinoNftInstruction {
     #[account(2, writable, name = "user PDA")]
     #[account(3, writable, name = "mint PDA")]
     #[account(4, writable, name = "metadata PDA")]
-    #[account(5, name = "system_program")]
+    #[account(
+        5,
+        writable,
+        name = "holder token account",
+        desc = "token account of the user PDA holding the NFT"
+    )]
+    #[account(6, name = "token_program", desc = "Token-2022")]
+    #[account(
+        7,
+        optional,
+        signer,
+        name = "holder",
+        desc = "linked wallet or session key signing instead of the administrator"
+    )]
+    #[account(
+        8,
+        optional,
+        name = "session key PDA",
+        desc = "required when a session key signs"
+    )]
+    #[account(
+        9,
+        optional,
+        signer,
+        name = "approvers",
+        desc = "admin multisig PDA and member signers in place of the holder, while enabled"
+    )]
     BurnNFT,
-    /* TODO
-    TransferNFTInternal,
-    WithdrawNFT,
-    DepositNFT,
-    CreateAuction,
-    PlaceBid,
-    SettleAuction,
-    */
-}
 
-#[repr(C, packed)]
-#[derive(Clone, Copy, Pod, Zeroable)]
-pub struct UpdatePlatformArgs {
-    pub mint_fee: u64,
-    pub is_receiver_valid: u8,
-    pub fee_receiver: Pubkey,
-}
+    #[account(
+        0,
+        signer,
+        writable,
+        name = "authority account",
+        desc = "platform administrator"
+    )]
+    #[account(
+        1,
+        writable,
+        name = "platform PDA",
+        desc = "account for on-chain platform management"
+    )]
+    #[account(2, writable, name = "treasury PDA")]
+    #[account(
+        3,
+        writable,
+        name = "destination",
+        desc = "receives lamports, or a token account for token withdrawals"
+    )]
+    #[account(4, name = "system_program")]
+    #[account(
+        5,
+        optional,
+        name = "admin multisig PDA",
+        desc = "required while the multisig is enabled, followed by threshold member signers"
+    )]
+    #[account(6, optional, name = "mint", desc = "required for token withdrawals")]
+    #[account(
+        7,
+        optional,
+        writable,
+        name = "treasury token account",
+        desc = "token account owned by the treasury PDA"
+    )]
+    #[account(8, optional, name = "token_program")]
+    WithdrawTreasury { args: WithdrawTreasuryArgs },
 
-#[repr(C, packed)]
-#[derive(Clone, Copy, Pod, Zeroable)]
-pub struct MintNftArgs {
-    user_uuid: u128,
-    asset_name: [u8; 256],
-    desc: [u8; 256],
-    uri: [u8; 256],
-}
+    #[account(
+        0,
+        signer,
+        writable,
+        name = "authority account",
+        desc = "administrator or fee manager, pays the collection PDA rent"
+    )]
+    #[account(
+        1,
+        writable,
+        name = "platform PDA",
+        desc = "account for on-chain platform management"
+    )]
+    #[account(2, writable, name = "collection PDA")]
+    #[account(3, name = "system_program")]
+    #[account(
+        4,
+        optional,
+        name = "role PDA",
+        desc = "required when authority is a fee manager"
+    )]
+    SetCollectionConfig { args: CollectionConfigArgs },
 
-#[repr(C, packed)]
-#[derive(Clone, Copy, Pod, Zeroable)]
-pub struct NftMeta {
-    pub name: [u8; 256],
-    pub collection: [u8; 64],
-    pub uri: [u8; 256],
-    pub description: [u8; 256],
+    #[account(0, signer, name = "authority account", desc = "platform administrator")]
+    #[account(
+        1,
+        writable,
+        name = "platform PDA",
+        desc = "account for on-chain platform management"
+    )]
+    #[account(
+        2,
+        optional,
+        signer,
+        name = "approvers",
+        desc = "admin multisig PDA followed by threshold member signers, while it is enabled"
+    )]
+    SetPauseFlags { args: SetPauseFlagsArgs },
+
+    #[account(
+        0,
+        signer,
+        writable,
+        name = "authority account",
+        desc = "platform administrator, pays the role PDA rent"
+    )]
+    #[account(
+        1,
+        writable,
+        name = "platform PDA",
+        desc = "account for on-chain platform management"
+    )]
+    #[account(2, writable, name = "role PDA")]
+    #[account(3, name = "system_program")]
+    #[account(
+        4,
+        optional,
+        name = "admin multisig PDA",
+        desc = "required while the multisig is enabled, followed by threshold member signers"
+    )]
+    GrantRole { args: RoleArgs },
+
+    #[account(
+        0,
+        signer,
+        writable,
+        name = "authority account",
+        desc = "platform administrator, receives the role PDA rent"
+    )]
+    #[account(
+        1,
+        writable,
+        name = "platform PDA",
+        desc = "account for on-chain platform management"
+    )]
+    #[account(2, writable, name = "role PDA")]
+    #[account(
+        3,
+        optional,
+        signer,
+        name = "approvers",
+        desc = "admin multisig PDA followed by threshold member signers, while it is enabled"
+    )]
+    RevokeRole,
+
+    #[account(
+        0,
+        signer,
+        writable,
+        name = "authority account",
+        desc = "platform administrator, pays the multisig PDA rent"
+    )]
+    #[account(
+        1,
+        writable,
+        name = "platform PDA",
+        desc = "account for on-chain platform management"
+    )]
+    #[account(2, writable, name = "admin multisig PDA")]
+    #[account(3, name = "system_program")]
+    #[account(
+        4,
+        optional,
+        signer,
+        name = "approvers",
+        desc = "threshold members of the current signer set, while it is enabled"
+    )]
+    SetAdminMultisig { args: AdminMultisigArgs },
+
+    #[account(
+        0,
+        signer,
+        writable,
+        name = "authority account",
+        desc = "platform administrator, pays the pending change PDA rent"
+    )]
+    #[account(
+        1,
+        name = "platform PDA",
+        desc = "account for on-chain platform management"
+    )]
+    #[account(2, writable, name = "pending config PDA")]
+    #[account(3, name = "system_program")]
+    #[account(
+        4,
+        optional,
+        signer,
+        name = "approvers",
+        desc = "admin multisig PDA followed by threshold member signers, while it is enabled"
+    )]
+    QueueConfigChange { args: UpdatePlatformArgs },
+
+    #[account(
+        0,
+        signer,
+        writable,
+        name = "authority account",
+        desc = "platform administrator, receives the pending change PDA rent"
+    )]
+    #[account(
+        1,
+        writable,
+        name = "platform PDA",
+        desc = "account for on-chain platform management"
+    )]
+    #[account(2, writable, name = "pending config PDA")]
+    #[account(3, name = "system_program")]
+    #[account(
+        4,
+        optional,
+        name = "config accounts",
+        desc = "fee schedule, receivers and treasury accounts the queued change needs"
+    )]
+    ExecuteConfigChange,
+
+    #[account(
+        0,
+        signer,
+        writable,
+        name = "authority account",
+        desc = "platform administrator, receives the pending change PDA rent"
+    )]
+    #[account(
+        1,
+        name = "platform PDA",
+        desc = "account for on-chain platform management"
+    )]
+    #[account(2, writable, name = "pending config PDA")]
+    #[account(
+        3,
+        optional,
+        signer,
+        name = "approvers",
+        desc = "admin multisig PDA followed by threshold member signers, while it is enabled"
+    )]
+    CancelConfigChange,
+
+    #[account(
+        0,
+        signer,
+        writable,
+        name = "authority account",
+        desc = "platform administrator, receives the user wallet PDA rent"
+    )]
+    #[account(
+        1,
+        writable,
+        name = "platform PDA",
+        desc = "account for on-chain platform management"
+    )]
+    #[account(
+        2,
+        writable,
+        name = "user wallet PDA",
+        desc = "must not hold any NFT or session key"
+    )]
+    #[account(
+        3,
+        optional,
+        signer,
+        name = "approvers",
+        desc = "admin multisig PDA followed by threshold member signers, while it is enabled"
+    )]
+    #[account(
+        4,
+        optional,
+        writable,
+        name = "claim PDAs",
+        desc = "every allowlist claim PDA of the user, closed with it, after the approvers"
+    )]
+    CloseUser,
+
+    #[account(
+        0,
+        signer,
+        writable,
+        name = "authority account",
+        desc = "platform administrator, receives the platform PDA rent"
+    )]
+    #[account(
+        1,
+        writable,
+        name = "platform PDA",
+        desc = "must not have users, NFTs or undrained treasury fees left"
+    )]
+    #[account(2, writable, name = "admin multisig PDA", desc = "closed if it exists")]
+    #[account(3, writable, name = "fee schedule PDA", desc = "closed if it exists")]
+    #[account(4, writable, name = "pending config PDA", desc = "closed if it exists")]
+    #[account(5, writable, name = "treasury PDA", desc = "closed if it exists")]
+    #[account(
+        6,
+        optional,
+        signer,
+        name = "approvers",
+        desc = "admin multisig PDA followed by threshold member signers while it is enabled, then every role, collection and pending collection change PDA of the platform, writable"
+    )]
+    ClosePlatform,
+
+    #[account(
+        0,
+        signer,
+        writable,
+        name = "authority account",
+        desc = "platform administrator, pays the rent of the grown account"
+    )]
+    #[account(
+        1,
+        name = "platform PDA",
+        desc = "account for on-chain platform management"
+    )]
+    #[account(
+        2,
+        writable,
+        name = "migrated account",
+        desc = "platform, user wallet or NFT metadata PDA to upgrade"
+    )]
+    #[account(3, name = "system_program")]
+    #[account(
+        4,
+        optional,
+        signer,
+        name = "approvers",
+        desc = "admin multisig PDA followed by threshold member signers, while it is enabled"
+    )]
+    #[account(
+        5,
+        optional,
+        name = "user PDA",
+        desc = "user holding the NFT, required to migrate NFT metadata"
+    )]
+    #[account(
+        6,
+        optional,
+        name = "mint PDA",
+        desc = "mint of the NFT, required to migrate NFT metadata"
+    )]
+    MigrateAccount,
+
+    #[account(0, signer, name = "authority account", desc = "platform administrator")]
+    #[account(
+        1,
+        name = "platform PDA",
+        desc = "account for on-chain platform management"
+    )]
+    #[account(2, writable, name = "user wallet PDA")]
+    #[account(3, signer, name = "wallet", desc = "owner wallet linked to the user")]
+    #[account(
+        4,
+        optional,
+        signer,
+        name = "approvers",
+        desc = "admin multisig PDA followed by threshold member signers, while it is enabled"
+    )]
+    LinkUserWallet,
+
+    #[account(0, signer, name = "authority account", desc = "platform administrator")]
+    #[account(
+        1,
+        writable,
+        name = "platform PDA",
+        desc = "account for on-chain platform management"
+    )]
+    #[account(2, writable, name = "user wallet PDA")]
+    #[account(
+        3,
+        optional,
+        signer,
+        name = "approvers",
+        desc = "admin multisig PDA followed by threshold member signers, while it is enabled"
+    )]
+    UnlinkUserWallet,
+
+    #[account(
+        0,
+        signer,
+        writable,
+        name = "authority account",
+        desc = "administrator or linked wallet, pays the session key PDA rent"
+    )]
+    #[account(
+        1,
+        name = "platform PDA",
+        desc = "account for on-chain platform management"
+    )]
+    #[account(
+        2,
+        writable,
+        name = "user wallet PDA",
+        desc = "counts its session keys"
+    )]
+    #[account(3, writable, name = "session key PDA")]
+    #[account(4, name = "system_program")]
+    CreateSessionKey { args: SessionKeyArgs },
+
+    #[account(
+        0,
+        signer,
+        writable,
+        name = "authority account",
+        desc = "administrator or linked wallet, receives the session key PDA rent"
+    )]
+    #[account(
+        1,
+        name = "platform PDA",
+        desc = "account for on-chain platform management"
+    )]
+    #[account(
+        2,
+        writable,
+        name = "user wallet PDA",
+        desc = "counts its session keys"
+    )]
+    #[account(3, writable, name = "session key PDA")]
+    RevokeSessionKey,
+
+    #[account(
+        0,
+        signer,
+        name = "authority account",
+        desc = "administrator, linked wallet or session key"
+    )]
+    #[account(
+        1,
+        name = "platform PDA",
+        desc = "account for on-chain platform management"
+    )]
+    #[account(2, name = "user PDA", desc = "owner of the holder token account")]
+    #[account(3, name = "mint PDA")]
+    #[account(4, writable, name = "metadata PDA", desc = "records the delegate")]
+    #[account(5, writable, name = "holder token account")]
+    #[account(6, name = "delegate")]
+    #[account(7, name = "token_program", desc = "Token-2022")]
+    #[account(
+        8,
+        optional,
+        name = "session key PDA",
+        desc = "required when a session key signs"
+    )]
+    ApproveNftDelegate,
+
+    #[account(
+        0,
+        signer,
+        name = "authority account",
+        desc = "administrator, linked wallet or session key"
+    )]
+    #[account(
+        1,
+        name = "platform PDA",
+        desc = "account for on-chain platform management"
+    )]
+    #[account(2, name = "user PDA", desc = "owner of the holder token account")]
+    #[account(3, name = "mint PDA")]
+    #[account(4, writable, name = "metadata PDA", desc = "records the delegate")]
+    #[account(5, writable, name = "holder token account")]
+    #[account(6, name = "token_program", desc = "Token-2022")]
+    #[account(
+        7,
+        optional,
+        name = "session key PDA",
+        desc = "required when a session key signs"
+    )]
+    RevokeNftDelegate,
+
+    #[account(
+        0,
+        name = "platform PDA",
+        desc = "account for on-chain platform management"
+    )]
+    #[account(1, name = "user PDA", desc = "user the NFT was minted for")]
+    #[account(2, name = "mint PDA")]
+    #[account(3, name = "metadata PDA")]
+    #[account(
+        4,
+        name = "holder token account",
+        desc = "Token-2022 account of the holder"
+    )]
+    VerifyOwnership { args: VerifyOwnershipArgs },
+
+    #[account(0, signer, name = "authority account", desc = "platform administrator")]
+    #[account(
+        1,
+        name = "platform PDA",
+        desc = "account for on-chain platform management"
+    )]
+    #[account(2, writable, name = "collection PDA")]
+    #[account(
+        3,
+        optional,
+        name = "admin multisig PDA",
+        desc = "required while the multisig is enabled, followed by threshold member signers"
+    )]
+    SetCollectionAllowlist { args: CollectionAllowlistArgs },
+
+    #[account(
+        0,
+        signer,
+        writable,
+        name = "authority account",
+        desc = "administrator or fee manager, pays the pending change PDA rent"
+    )]
+    #[account(
+        1,
+        writable,
+        name = "platform PDA",
+        desc = "account for on-chain platform management"
+    )]
+    #[account(2, name = "collection PDA", desc = "created when the change executes")]
+    #[account(3, writable, name = "pending collection change PDA")]
+    #[account(4, name = "system_program")]
+    #[account(
+        5,
+        optional,
+        name = "role PDA",
+        desc = "required when authority is a fee manager"
+    )]
+    QueueCollectionConfig { args: CollectionConfigArgs },
+
+    #[account(
+        0,
+        signer,
+        writable,
+        name = "authority account",
+        desc = "platform administrator, pays the pending change PDA rent"
+    )]
+    #[account(
+        1,
+        writable,
+        name = "platform PDA",
+        desc = "account for on-chain platform management"
+    )]
+    #[account(2, name = "collection PDA")]
+    #[account(3, writable, name = "pending collection change PDA")]
+    #[account(4, name = "system_program")]
+    #[account(
+        5,
+        optional,
+        name = "admin multisig PDA",
+        desc = "required while the multisig is enabled, followed by threshold member signers"
+    )]
+    QueueCollectionAllowlist { args: CollectionAllowlistArgs },
+
+    #[account(
+        0,
+        signer,
+        writable,
+        name = "authority account",
+        desc = "platform administrator, or a fee manager for a config change; pays the collection PDA rent"
+    )]
+    #[account(
+        1,
+        writable,
+        name = "platform PDA",
+        desc = "account for on-chain platform management"
+    )]
+    #[account(2, writable, name = "collection PDA")]
+    #[account(3, writable, name = "pending collection change PDA")]
+    #[account(
+        4,
+        writable,
+        name = "payer account",
+        desc = "account that queued the change, receives the pending change PDA rent"
+    )]
+    #[account(5, name = "system_program")]
+    #[account(
+        6,
+        optional,
+        name = "role PDA",
+        desc = "required when authority is a fee manager"
+    )]
+    ExecuteCollectionChange,
+
+    #[account(0, signer, name = "authority account", desc = "platform administrator")]
+    #[account(
+        1,
+        writable,
+        name = "platform PDA",
+        desc = "account for on-chain platform management"
+    )]
+    #[account(2, name = "collection PDA")]
+    #[account(3, writable, name = "pending collection change PDA")]
+    #[account(
+        4,
+        writable,
+        name = "payer account",
+        desc = "account that queued the change, receives the pending change PDA rent"
+    )]
+    #[account(
+        5,
+        optional,
+        signer,
+        name = "approvers",
+        desc = "admin multisig PDA followed by threshold member signers, while it is enabled"
+    )]
+    CancelCollectionChange,
+
+    #[account(
+        0,
+        signer,
+        name = "authority account",
+        desc = "administrator, linked wallet of the sending user or its session key"
+    )]
+    #[account(
+        1,
+        name = "platform PDA",
+        desc = "account for on-chain platform management"
+    )]
+    #[account(
+        2,
+        writable,
+        name = "sending user PDA",
+        desc = "holder of the NFT and owner of the source token account"
+    )]
+    #[account(
+        3,
+        writable,
+        name = "receiving user PDA",
+        desc = "owner of the destination token account, on the same platform"
+    )]
+    #[account(4, name = "mint PDA")]
+    #[account(
+        5,
+        writable,
+        name = "metadata PDA",
+        desc = "records the receiving user as holder and clears the delegate"
+    )]
+    #[account(6, writable, name = "source token account")]
+    #[account(7, writable, name = "destination token account")]
+    #[account(8, name = "token_program", desc = "Token-2022")]
+    #[account(
+        9,
+        optional,
+        name = "session key PDA",
+        desc = "required when a session key signs"
+    )]
+    TransferNFTInternal,
+
+    #[account(
+        0,
+        writable,
+        name = "administrator",
+        desc = "platform administrator, receives the rent; the relayer pays the fees"
+    )]
+    #[account(
+        1,
+        writable,
+        name = "platform PDA",
+        desc = "account for on-chain platform management"
+    )]
+    #[account(2, writable, name = "user PDA")]
+    #[account(3, writable, name = "mint PDA")]
+    #[account(4, writable, name = "metadata PDA")]
+    #[account(
+        5,
+        writable,
+        name = "holder token account",
+        desc = "token account of the user PDA holding the NFT"
+    )]
+    #[account(6, name = "token_program", desc = "Token-2022")]
+    #[account(
+        7,
+        name = "instructions sysvar",
+        desc = "finds the Ed25519 instruction verifying the intent signature"
+    )]
+    BurnNFTWithIntent { intent: UserIntent },
+
+    #[account(
+        0,
+        signer,
+        name = "authority account",
+        desc = "platform administrator or fee manager"
+    )]
+    #[account(
+        1,
+        name = "platform PDA",
+        desc = "account for on-chain platform management"
+    )]
+    #[account(2, writable, name = "user PDA")]
+    #[account(
+        3,
+        optional,
+        name = "role PDA",
+        desc = "required when authority is a fee manager"
+    )]
+    SetUserTier { args: SetUserTierArgs },
+
+    #[account(0, signer, name = "authority account", desc = "platform administrator")]
+    #[account(
+        1,
+        writable,
+        name = "platform PDA",
+        desc = "account for on-chain platform management"
+    )]
+    #[account(
+        2,
+        signer,
+        name = "new administrator",
+        desc = "key taking over the platform, the PDA stays seeded by the old one"
+    )]
+    #[account(
+        3,
+        optional,
+        signer,
+        name = "approvers",
+        desc = "admin multisig PDA followed by threshold member signers, while it is enabled"
+    )]
+    RotateAdministrator,
+    /* TODO
+    WithdrawNFT,
+    DepositNFT,
+    CreateAuction,
+    PlaceBid,
+    SettleAuction,
+    */
+}
+
+/// Read arguments of their full size or of `legacy_len`, a prefix older clients send,
+/// zero-filling the fields appended since
+fn parse_versioned<T: Pod>(data: &[u8], legacy_len: usize) -> Result<T, ProgramError> {
+    if data.len() != size_of::<T>() && data.len() != legacy_len {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let mut args = T::zeroed();
+    bytemuck::bytes_of_mut(&mut args)[..data.len()].copy_from_slice(data);
+    Ok(args)
+}
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Pod, Zeroable, ShankType)]
+pub struct UpdatePlatformArgs {
+    pub mint_fee: u64,
+    pub is_receiver_valid: u8,
+    pub fee_receiver: Pubkey,
+    pub is_fee_mint_valid: u8,
+    pub fee_mint: Pubkey, // all-zero key switches mint fees back to lamports
+    pub is_fee_schedule_valid: u8,
+    pub fee_share_count: u8,       // zero disables fee splitting
+    pub fee_shares: [FeeShare; 4], // MAX_FEE_SHARES, spelled out for shank
+    pub is_treasury_valid: u8,
+    pub treasury_enabled: u8,
+    pub treasury_max_withdrawal: u64, // bound on each WithdrawTreasury, not per period, 0 for none
+    pub is_fee_tiers_valid: u8,
+    pub fee_tier_count: u8,      // zero disables tiered pricing
+    pub fee_tiers: [FeeTier; 4], // MAX_FEE_TIERS, spelled out for shank
+    pub is_config_delay_valid: u8,
+    pub config_delay: i64, // zero lets UpdatePlatformConfig apply changes immediately
+}
+
+impl UpdatePlatformArgs {
+    /// Size of the arguments before fee mints, fee shares, the treasury, fee tiers and the
+    /// config delay were appended, still sent by older clients
+    pub const LEGACY_LEN: usize = core::mem::offset_of!(Self, is_fee_mint_valid);
+
+    /// Read current or legacy arguments, the fields missing from legacy ones left unset
+    pub fn parse(data: &[u8]) -> Result<Self, ProgramError> {
+        parse_versioned(data, Self::LEGACY_LEN)
+    }
+}
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Pod, Zeroable, ShankType)]
+pub struct WithdrawTreasuryArgs {
+    pub amount: u64,
+}
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Pod, Zeroable, ShankType)]
+pub struct MintNftArgs {
+    pub user_uuid: u128,
+    pub asset_name: [u8; 256],
+    pub desc: [u8; 256],
+    pub uri: [u8; 256],
+    pub collection: [u8; 64], // all-zero for NFTs outside any collection
+    pub attribute: [u8; 32],  // all-zero for none
+}
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Pod, Zeroable, ShankType)]
+pub struct SetUserTierArgs {
+    pub tier_level: u8, // matched against `FeeTier.min_level`, 0 for none
+}
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Pod, Zeroable, ShankType)]
+pub struct SetPauseFlagsArgs {
+    pub paused: u8, // bitset of the `PAUSE_*` flags in `state::platform`
+}
+
+/// Message signed off-chain by the wallet linked to a user
+#[repr(C, packed)]
+#[derive(Clone, Copy, Pod, Zeroable, ShankType)]
+pub struct UserIntent {
+    pub action: u8, // one of the `INTENT_*` values in `instructions::intent`
+    pub user: Pubkey,
+    pub generation: u64, // has to equal `User.generation`, so a re-created user starts afresh
+    pub mint: Pubkey,
+    pub nonce: u64,  // has to equal `User.nonce`
+    pub expiry: i64, // unix timestamp after which the intent is rejected
+}
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Pod, Zeroable, ShankType)]
+pub struct SessionKeyArgs {
+    pub key: Pubkey,
+    pub allowed_instructions: u64, // bit `n` allows the instruction with discriminator `n`
+    pub expiry_slot: u64,
+}
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Pod, Zeroable, ShankType)]
+pub struct RoleArgs {
+    pub role_id: u8, // one of the `ROLE_*` ids in `state::role`
+    pub grantee: Pubkey,
+}
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Pod, Zeroable, ShankType)]
+pub struct AdminMultisigArgs {
+    pub threshold: u8, // zero disables the multisig
+    pub signer_count: u8,
+    pub signers: [Pubkey; 5], // MAX_ADMIN_SIGNERS, spelled out for shank
+}
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Pod, Zeroable, ShankType)]
+pub struct CollectionConfigArgs {
+    pub name: [u8; 64],
+    pub mint_fee: u64,
+    pub has_mint_fee: u8,
+}
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Pod, Zeroable, ShankType)]
+pub struct CollectionAllowlistArgs {
+    pub name: [u8; 64],
+    pub allowlist_root: [u8; 32], // all-zero opens minting to every user
+    pub phase: u8,                // never lower than the current phase
+    pub phase_user_limit: u16,    // at least 1 with an allowlist
+    pub phase_supply: u32,        // 0 for no cap
+}
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Pod, Zeroable, ShankType)]
+pub struct NftMetaArgs {
+    pub name: [u8; 256],
+    pub collection: [u8; 64], // fixed at mint, updates must repeat it
+    pub uri: [u8; 256],
+    pub description: [u8; 256],
+    pub attribute: [u8; 32], // all-zero for none, needs version 3 metadata otherwise
+}
+
+impl NftMetaArgs {
+    /// Size of the arguments before `attribute` was appended, still sent by older clients
+    pub const LEGACY_LEN: usize = core::mem::offset_of!(Self, attribute);
+
+    /// Read current or legacy arguments, a legacy update clearing the attribute
+    pub fn parse(data: &[u8]) -> Result<Self, ProgramError> {
+        parse_versioned(data, Self::LEGACY_LEN)
+    }
+}
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Pod, Zeroable, ShankType)]
+pub struct VerifyOwnershipArgs {
+    pub holder: Pubkey,       // the user PDA or a wallet holding the NFT
+    pub collection: [u8; 64], // all-zero accepts NFTs of any collection
+    pub attribute: [u8; 32],  // all-zero accepts NFTs with any attribute
+}
+
+/// Return data of `VerifyOwnership`
+#[repr(C, packed)]
+#[derive(Clone, Copy, Pod, Zeroable, ShankType)]
+pub struct OwnershipProof {
+    pub platform: Pubkey,
+    pub user_uuid: u128,
+    pub mint: Pubkey,
+    pub holder: Pubkey,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn legacy_update_platform_args_leave_new_settings_unset() {
+        let mut data = [0u8; UpdatePlatformArgs::LEGACY_LEN];
+        data[..8].copy_from_slice(&7u64.to_le_bytes());
+        data[8] = 1;
+
+        let args = UpdatePlatformArgs::parse(&data).unwrap();
+        assert_eq!({ args.mint_fee }, 7);
+        assert_eq!(args.is_receiver_valid, 1);
+        assert_eq!(args.is_fee_mint_valid, 0);
+        assert_eq!(args.is_config_delay_valid, 0);
+    }
+
+    #[test]
+    fn legacy_nft_meta_args_clear_the_attribute() {
+        let data = [1u8; NftMetaArgs::LEGACY_LEN];
+
+        let args = NftMetaArgs::parse(&data).unwrap();
+        assert_eq!(args.name, [1; 256]);
+        assert_eq!(args.attribute, [0; 32]);
+    }
+
+    #[test]
+    fn args_of_other_sizes_are_rejected() {
+        let data = [0u8; NftMetaArgs::LEGACY_LEN + 1];
+        assert!(NftMetaArgs::parse(&data).is_err());
+    }
 }
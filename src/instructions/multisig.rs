@@ -0,0 +1,312 @@
+use bytemuck::try_from_bytes;
+use pinocchio::ProgramResult;
+use pinocchio::account_info::AccountInfo;
+use pinocchio::instruction::{Seed, Signer};
+use pinocchio::program_error::ProgramError;
+use pinocchio::pubkey::Pubkey;
+use pinocchio::sysvars::Sysvar;
+use pinocchio::sysvars::rent::Rent;
+use pinocchio_log::log;
+use pinocchio_system::instructions::CreateAccount;
+
+use crate::error::UniPinoNftErr;
+use crate::state::admin_multisig::{AdminMultisig, MAX_ADMIN_SIGNERS};
+use crate::state::platform::Platform;
+use crate::state::program_state::ProgramState;
+
+use super::accounts::{Pda, ProgramAccount, SignerAccount};
+use super::*;
+
+pub const ADMIN_MULTISIG_TOKEN: &[u8] = b"admin_multisig";
+
+/// Check `administrator` signed and owns the platform. While the administrator multisig
+/// is enabled, the remaining accounts must start with the multisig PDA followed by
+/// `threshold` distinct member signers; the accounts left after them are returned.
+pub fn authorize_admin<'a>(
+    administrator: &AccountInfo,
+    platform_pda: &AccountInfo,
+    platform: &Platform,
+    remaining: &'a [AccountInfo],
+) -> Result<&'a [AccountInfo], ProgramError> {
+    if !administrator.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if platform.administrator != administrator.key().as_ref() {
+        return Err(UniPinoNftErr::NotAdministrator.into());
+    }
+
+    if !platform.has_multisig() {
+        return Ok(remaining);
+    }
+
+    let [multisig_pda, rest @ ..] = remaining else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    verify_approvals(multisig_pda, platform_pda, rest)
+}
+
+/// Consume `threshold` member signers from the front of `accounts`
+fn verify_approvals<'a>(
+    multisig_pda: &AccountInfo,
+    platform_pda: &AccountInfo,
+    accounts: &'a [AccountInfo],
+) -> Result<&'a [AccountInfo], ProgramError> {
+    let multisig = *ProgramAccount::<AdminMultisig>::try_from(multisig_pda)?.load()?;
+    if multisig.platform != platform_pda.key().as_ref() {
+        return Err(UniPinoNftErr::PlatformMismatch.into());
+    }
+
+    let threshold = multisig.threshold as usize;
+    if accounts.len() < threshold {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    let (approvers, rest) = accounts.split_at(threshold);
+
+    for (i, approver) in approvers.iter().enumerate() {
+        if !approver.is_signer() {
+            return Err(UniPinoNftErr::NotEnoughApprovals.into());
+        }
+
+        let is_member = multisig.signers().contains(approver.key());
+        let is_repeated = approvers[..i]
+            .iter()
+            .any(|previous| previous.key() == approver.key());
+        if !is_member || is_repeated {
+            return Err(UniPinoNftErr::NotEnoughApprovals.into());
+        }
+    }
+
+    Ok(rest)
+}
+
+pub struct SetAdminMultisig<'a> {
+    pub administrator: SignerAccount<'a>,
+    pub platform_pda: ProgramAccount<'a, Platform>,
+    pub multisig_pda: Pda<'a>,
+    pub multisig_args: &'a AdminMultisigArgs,
+    pub approvers: &'a [AccountInfo],
+}
+
+impl<'a> SetAdminMultisig<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &11;
+
+    pub fn process(self) -> ProgramResult {
+        let mut platform = self.platform_pda.load_mut()?;
+        platform.ensure_administrator(self.administrator.key())?;
+
+        // changing an active signer set needs the approval of the current one
+        if platform.has_multisig() {
+            verify_approvals(&self.multisig_pda, &self.platform_pda, self.approvers)?;
+        }
+
+        let threshold = self.multisig_args.threshold as usize;
+        let signer_count = self.multisig_args.signer_count as usize;
+        if threshold == 0 {
+            log!("administrator multisig disabled");
+            platform.multisig_enabled = 0;
+            return Ok(());
+        }
+
+        if signer_count > MAX_ADMIN_SIGNERS || threshold > signer_count {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let signers = &self.multisig_args.signers[..signer_count];
+        for (i, signer) in signers.iter().enumerate() {
+            if *signer == Pubkey::default() || signers[..i].contains(signer) {
+                return Err(ProgramError::InvalidInstructionData);
+            }
+        }
+
+        let bump = self.multisig_pda.bump;
+        if !self.multisig_pda.is_initialized() {
+            let multisig_seeds = [
+                Seed::from(ADMIN_MULTISIG_TOKEN),
+                Seed::from(self.platform_pda.key().as_ref()),
+                Seed::from(core::slice::from_ref(&bump)),
+            ];
+            let multisig_signer = Signer::from(&multisig_seeds);
+
+            CreateAccount {
+                from: &self.administrator,
+                to: &self.multisig_pda,
+                lamports: Rent::get()?.minimum_balance(AdminMultisig::INIT_SPACE),
+                space: AdminMultisig::INIT_SPACE as u64,
+                owner: &ID,
+            }
+            .invoke_signed(&[multisig_signer])?;
+
+            let multisig = AdminMultisig::new(*self.platform_pda.key(), bump);
+            multisig.init(&self.multisig_pda)?;
+        }
+
+        let multisig_pda = ProgramAccount::<AdminMultisig>::try_from(self.multisig_pda.info())?;
+        let mut multisig = multisig_pda.load_mut()?;
+        multisig.threshold = threshold as u8;
+        multisig.signer_count = signer_count as u8;
+        multisig.signers = self.multisig_args.signers;
+        multisig.signers[signer_count..].fill(Pubkey::default());
+
+        platform.multisig_enabled = 1;
+
+        log!(
+            "administrator multisig set to {} of {}",
+            threshold,
+            signer_count
+        );
+        Ok(())
+    }
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &'a [u8])> for SetAdminMultisig<'a> {
+    type Error = ProgramError;
+
+    fn try_from(value: (&'a [AccountInfo], &'a [u8])) -> Result<Self, Self::Error> {
+        let (accounts, instruction_data) = value;
+
+        let [administrator, platform_pda, multisig_pda, _, approvers @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if instruction_data.len() != size_of::<AdminMultisigArgs>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let multisig_args = try_from_bytes::<AdminMultisigArgs>(instruction_data)
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+        let platform_pda = ProgramAccount::try_from(platform_pda)?;
+        let multisig_pda = Pda::find(
+            multisig_pda,
+            &[ADMIN_MULTISIG_TOKEN, platform_pda.key().as_ref()],
+            &ID,
+            UniPinoNftErr::PdaMismatch,
+        )?;
+
+        Ok(Self {
+            administrator: SignerAccount::try_from(administrator)?,
+            platform_pda,
+            multisig_pda,
+            multisig_args,
+            approvers,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::accounts::testing::{TestAccount, key, with_accounts};
+    use super::*;
+
+    const PLATFORM: Pubkey = [1; 32];
+    const MULTISIG: Pubkey = [2; 32];
+
+    /// Multisig PDA with members `[10, 11, 12]` and a threshold of 2, then `approvers`
+    fn multisig_accounts(
+        approvers: impl IntoIterator<Item = TestAccount>,
+    ) -> alloc::vec::Vec<TestAccount> {
+        let mut multisig = AdminMultisig::new(PLATFORM, 255);
+        multisig.threshold = 2;
+        multisig.signer_count = 3;
+        multisig.signers[..3].copy_from_slice(&[key(10), key(11), key(12)]);
+
+        let mut accounts = alloc::vec![
+            TestAccount::new(PLATFORM),
+            TestAccount::state(MULTISIG, &multisig),
+        ];
+        accounts.extend(approvers);
+        accounts
+    }
+
+    fn verify(accounts: &[TestAccount]) -> Result<usize, ProgramError> {
+        with_accounts(accounts, |infos| {
+            verify_approvals(&infos[1], &infos[0], &infos[2..]).map(<[_]>::len)
+        })
+    }
+
+    #[test]
+    fn threshold_member_signers_approve() {
+        let accounts = multisig_accounts([
+            TestAccount::signer(key(12)),
+            TestAccount::signer(key(10)),
+            TestAccount::new(key(99)),
+        ]);
+        // the account after the approvers is left for the instruction
+        assert_eq!(verify(&accounts), Ok(1));
+    }
+
+    #[test]
+    fn approvals_below_threshold_are_rejected() {
+        let accounts = multisig_accounts([TestAccount::signer(key(10))]);
+        assert_eq!(verify(&accounts), Err(ProgramError::NotEnoughAccountKeys));
+
+        // a member passed without signing does not count
+        let accounts = multisig_accounts([TestAccount::signer(key(10)), TestAccount::new(key(11))]);
+        assert_eq!(
+            verify(&accounts),
+            Err(UniPinoNftErr::NotEnoughApprovals.into())
+        );
+    }
+
+    #[test]
+    fn outsiders_and_repeated_members_are_rejected() {
+        let accounts =
+            multisig_accounts([TestAccount::signer(key(10)), TestAccount::signer(key(13))]);
+        assert_eq!(
+            verify(&accounts),
+            Err(UniPinoNftErr::NotEnoughApprovals.into())
+        );
+
+        let accounts =
+            multisig_accounts([TestAccount::signer(key(10)), TestAccount::signer(key(10))]);
+        assert_eq!(
+            verify(&accounts),
+            Err(UniPinoNftErr::NotEnoughApprovals.into())
+        );
+    }
+
+    #[test]
+    fn multisig_of_another_platform_is_rejected() {
+        let mut accounts =
+            multisig_accounts([TestAccount::signer(key(10)), TestAccount::signer(key(11))]);
+        accounts[0].key = key(3);
+        assert_eq!(
+            verify(&accounts),
+            Err(UniPinoNftErr::PlatformMismatch.into())
+        );
+
+        // a look-alike account not owned by the program
+        let mut accounts =
+            multisig_accounts([TestAccount::signer(key(10)), TestAccount::signer(key(11))]);
+        accounts[1].owner = key(4);
+        assert_eq!(verify(&accounts), Err(UniPinoNftErr::UninitPda.into()));
+    }
+
+    #[test]
+    fn administrator_without_multisig_needs_no_approvals() {
+        let mut platform = Platform::new(key(5), 255);
+        let accounts = [TestAccount::signer(key(5)), TestAccount::new(PLATFORM)];
+        let authorize = |platform: &Platform| {
+            with_accounts(&accounts, |infos| {
+                authorize_admin(&infos[0], &infos[1], platform, &infos[2..]).map(<[_]>::len)
+            })
+        };
+        assert_eq!(authorize(&platform), Ok(0));
+
+        // once enabled, the multisig PDA has to follow
+        platform.multisig_enabled = 1;
+        assert_eq!(
+            authorize(&platform),
+            Err(ProgramError::NotEnoughAccountKeys)
+        );
+
+        platform.multisig_enabled = 0;
+        platform.administrator = key(6);
+        assert_eq!(
+            authorize(&platform),
+            Err(UniPinoNftErr::NotAdministrator.into())
+        );
+    }
+}
@@ -1,127 +1,225 @@
-use alloc::string::ToString;
-use bytemuck::{bytes_of, try_from_bytes};
+use bytemuck::try_from_bytes;
+use pinocchio::ProgramResult;
 use pinocchio::account_info::AccountInfo;
-use pinocchio::instruction::{Seed, Signer};
+use pinocchio::cpi::invoke;
+use pinocchio::instruction::{AccountMeta, Instruction, Seed, Signer};
 use pinocchio::program_error::ProgramError;
+use pinocchio::pubkey::Pubkey;
 use pinocchio::sysvars::Sysvar;
 use pinocchio::sysvars::rent::Rent;
-use pinocchio::{ProgramResult, pubkey::try_find_program_address};
 use pinocchio_log::log;
-use pinocchio_system::instructions::{CreateAccount, Transfer};
-use pinocchio_token_2022::state::Mint;
+use pinocchio_pubkey::pubkey;
+use pinocchio_system::instructions::CreateAccount;
+use pinocchio_token_2022::state::TokenAccount;
 use pinocchio_token_2022::{
     ID as TOKEN_2022_ID,
-    instructions::{CloseAccount, InitializeMint2},
+    instructions::{Burn, CloseAccount, InitializeMint2, MintTo},
 };
 
 use crate::error::UniPinoNftErr;
+use crate::events::{Event, MetadataUpdated, NftBurned, NftMinted};
+use crate::instructions::intent::INTENT_BURN_NFT;
 use crate::state::nft_meta::NftMeta;
-use crate::state::platform::Platform;
+use crate::state::platform::{PAUSE_BURN, PAUSE_METADATA_UPDATE, PAUSE_MINT, Platform};
+use crate::state::program_state::ProgramState;
+use crate::state::role::{ROLE_METADATA_EDITOR, ROLE_MINTER};
 use crate::state::user::User;
 
+use super::accounts::{Pda, ProgramAccount, SignerAccount, UserSeeds};
 use super::*;
 
+/// Associated Token Account program, creating the token account each NFT is minted into
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey =
+    pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
+/// Create the Token-2022 associated token account of `owner` for `mint` unless it exists,
+/// the associated token program checking its address
+fn create_associated_token_account(
+    payer: &AccountInfo,
+    token_account: &AccountInfo,
+    owner: &AccountInfo,
+    mint: &AccountInfo,
+    system_program: &AccountInfo,
+    token_program: &AccountInfo,
+) -> ProgramResult {
+    let account_metas = [
+        AccountMeta::writable_signer(payer.key()),
+        AccountMeta::writable(token_account.key()),
+        AccountMeta::readonly(owner.key()),
+        AccountMeta::readonly(mint.key()),
+        AccountMeta::readonly(system_program.key()),
+        AccountMeta::readonly(token_program.key()),
+    ];
+
+    // `CreateIdempotent`
+    let instruction = Instruction {
+        program_id: &ASSOCIATED_TOKEN_PROGRAM_ID,
+        accounts: &account_metas,
+        data: &[1],
+    };
+    invoke(
+        &instruction,
+        &[
+            payer,
+            token_account,
+            owner,
+            mint,
+            system_program,
+            token_program,
+        ],
+    )
+}
+
+/// Size of an NFT mint, the base mint padded to a token account's size, the account type
+/// and the `MintCloseAuthority` extension
+const MINT_SPACE: usize = TokenAccount::BASE_LEN + 1 + 4 + 32;
+
+/// Let `close_authority` close `mint` once its supply is burned, before the mint is
+/// initialized
+fn initialize_mint_close_authority(mint: &AccountInfo, close_authority: &Pubkey) -> ProgramResult {
+    let account_metas = [AccountMeta::writable(mint.key())];
+
+    // `InitializeMintCloseAuthority` with `Some(close_authority)`
+    let mut data = [0; 34];
+    data[0] = 25;
+    data[1] = 1;
+    data[2..].copy_from_slice(close_authority);
+
+    let instruction = Instruction {
+        program_id: &TOKEN_2022_ID,
+        accounts: &account_metas,
+        data: &data,
+    };
+    invoke(&instruction, &[mint])
+}
+
 pub struct MintNft<'a> {
-    pub administrator: &'a AccountInfo,
-    pub platform_pda: &'a AccountInfo,
-    pub user_pda: &'a AccountInfo,
-    pub mint_pda: &'a AccountInfo,
-    pub metadata_pda: &'a AccountInfo,
+    pub authority: SignerAccount<'a>,
+    pub platform_pda: ProgramAccount<'a, Platform>,
+    pub user_pda: ProgramAccount<'a, User>,
+    pub mint_pda: Pda<'a>,
+    pub metadata_pda: Pda<'a>,
+    pub token_account: &'a AccountInfo,
     pub fee_receiver: &'a AccountInfo,
+    pub system_program: &'a AccountInfo,
+    pub token_program: &'a AccountInfo,
     pub mint_nft_args: &'a MintNftArgs,
+    pub proof: &'a [[u8; 32]],
+    pub remaining: &'a [AccountInfo],
 }
 
 impl<'a> MintNft<'a> {
     pub const DISCRIMINATOR: &'a u8 = &3;
 
     pub fn process(self) -> ProgramResult {
-        if !self.administrator.is_signer() {
-            return Err(ProgramError::MissingRequiredSignature);
-        }
-
-        if !self.platform_pda.is_owned_by(&ID)
-            || !self.user_pda.is_owned_by(&ID)
-            || self.platform_pda.lamports() == 0
-            || self.user_pda.lamports() == 0
-        {
-            return Err(UniPinoNftErr::UninitPda.into());
-        }
-
-        let mut platform_data_bytes = self.platform_pda.try_borrow_mut_data()?;
-        let platform = Platform::try_from_bytes_mut(platform_data_bytes.as_mut())?;
+        let mut platform = self.platform_pda.load_mut()?;
+        let mut user = self.user_pda.load_mut()?;
 
-        let mut user_data_bytes = self.user_pda.try_borrow_mut_data()?;
-        let user = User::try_from_bytes_mut(user_data_bytes.as_mut())?;
+        self.user_pda
+            .check_platform(&user, &self.platform_pda, &platform)?;
 
-        if platform.administrator != self.administrator.key().as_ref()
-            || user.owner != self.platform_pda.key().as_ref()
-        {
-            return Err(ProgramError::InvalidAccountOwner);
-        }
-
-        // Collect mint fee if configured
-        if platform.mint_fee > 0 {
-            // Validate fee_receiver matches platform configuration
-            if platform.fee_receiver != self.fee_receiver.key().as_ref() {
-                return Err(ProgramError::InvalidAccountOwner);
-            }
-
-            Transfer {
-                from: self.administrator,
-                to: self.fee_receiver,
-                lamports: platform.mint_fee,
-            }
-            .invoke()?;
-
-            log!("collected mint fee: {} lamports", platform.mint_fee);
+        // the mint PDA was derived from the uuid in the arguments
+        if { user.user_uuid } != self.mint_nft_args.user_uuid {
+            return Err(UniPinoNftErr::UserMismatch.into());
         }
 
-        let mint_pda_seeds = [
-            &self.mint_nft_args.user_uuid.to_le_bytes(),
-            self.user_pda.key().as_ref(),
-            &TOKEN_2022_ID,
-        ];
-        let (mint_pda, mint_bump) = try_find_program_address(&mint_pda_seeds, &TOKEN_2022_ID)
-            .ok_or(UniPinoNftErr::PdaErr)?;
+        // a minter passes its role PDA first in the remaining accounts
+        let remaining = role::authorize(
+            &self.authority,
+            &self.platform_pda,
+            &platform,
+            ROLE_MINTER,
+            self.remaining,
+        )?;
+
+        platform.ensure_not_paused(PAUSE_MINT)?;
+
+        // the collection PDA comes next when minting into one
+        let (collection, remaining) = if self.mint_nft_args.collection == [0; 64] {
+            (None, remaining)
+        } else {
+            let [collection_pda, rest @ ..] = remaining else {
+                return Err(ProgramError::NotEnoughAccountKeys);
+            };
+            let collection = collection::load_collection(
+                collection_pda,
+                &self.platform_pda,
+                &self.mint_nft_args.collection,
+            )?;
+
+            // followed by the claim PDA of the user when the collection has an allowlist
+            let rest = if collection.has_allowlist() {
+                let [claim_pda, rest @ ..] = rest else {
+                    return Err(ProgramError::NotEnoughAccountKeys);
+                };
+                allowlist::claim(
+                    &self.authority,
+                    collection_pda,
+                    &self.user_pda,
+                    &mut user,
+                    claim_pda,
+                    self.proof,
+                )?;
+                rest
+            } else {
+                rest
+            };
+            (Some(collection), rest)
+        };
 
-        if mint_pda != self.mint_pda.key().as_ref() {
-            return Err(ProgramError::InvalidSeeds);
-        }
+        // Collect mint fee if configured
+        let mint_fee = fee::mint_price(&platform, collection.as_ref(), user.tier_level)?;
+        fee::collect_fee(
+            &self.platform_pda,
+            &mut platform,
+            &self.authority,
+            self.fee_receiver,
+            mint_fee,
+            remaining,
+        )?;
 
-        let platform_seeds = [
-            Seed::from(platform::PLATFORM_TOKEN),
-            Seed::from(self.administrator.key().as_ref()),
-            Seed::from(core::slice::from_ref(&platform.bump)),
-        ];
-        let platform_signer = Signer::from(&platform_seeds);
+        // update mint count
+        platform.total_mints = platform
+            .total_mints
+            .checked_add(1)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        user.nft_count = user
+            .nft_count
+            .checked_add(1)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
 
-        let user_seeds = [
-            Seed::from(user::USER_TOKEN),
-            Seed::from(self.platform_pda.key().as_ref()),
-            Seed::from(core::slice::from_ref(&user.bump)),
+        // the user PDA owns the token account and signs `MintTo`, so the CPIs below need it
+        // unborrowed
+        let user_seeds = UserSeeds::new(&user, &self.platform_pda, &platform);
+        let user_uuid = { user.user_uuid }.to_le_bytes();
+        drop(user);
+
+        // the mint and metadata PDAs sign their own creation, with the seeds `Pda::nft_mint`
+        // and `Pda::nft_metadata` checked them against
+        let mint_seeds = [
+            Seed::from(&user_uuid),
+            Seed::from(self.user_pda.key().as_ref()),
+            Seed::from(TOKEN_2022_ID.as_ref()),
+            Seed::from(core::slice::from_ref(&self.mint_pda.bump)),
         ];
-        let user_signer = Signer::from(&user_seeds);
 
-        let mint_lamports = Rent::get()?.minimum_balance(Mint::BASE_LEN);
-        if self.mint_pda.lamports() == 0 {
+        let mint_lamports = Rent::get()?.minimum_balance(MINT_SPACE);
+        if !self.mint_pda.is_initialized() {
             CreateAccount {
-                from: self.administrator,
-                to: self.mint_pda,
+                from: &self.authority,
+                to: &self.mint_pda,
                 lamports: mint_lamports,
-                space: Mint::BASE_LEN as u64,
+                space: MINT_SPACE as u64,
                 owner: &TOKEN_2022_ID,
             }
-            .invoke_signed(&[platform_signer, user_signer])?
+            .invoke_signed(&[Signer::from(&mint_seeds)])?
         }
 
-        let binding = [
-            Seed::from(&mint_pda),
-            Seed::from(core::slice::from_ref(&mint_bump)),
-        ];
-        let mint_signer = Signer::from(&binding);
+        // the platform PDA closes the mint when the NFT is burned, whoever holds it then
+        initialize_mint_close_authority(&self.mint_pda, self.platform_pda.key())?;
 
         InitializeMint2 {
-            mint: self.mint_pda,
+            mint: &self.mint_pda,
             decimals: 0,
             mint_authority: self.user_pda.key(),
             freeze_authority: None,
@@ -129,59 +227,64 @@ impl<'a> MintNft<'a> {
         }
         .invoke()?;
 
-        let metadata_pda_seeds = [b"metadata", self.mint_pda.key().as_ref(), &TOKEN_2022_ID];
-        let (metadata_pda, _) = try_find_program_address(&metadata_pda_seeds, &TOKEN_2022_ID)
-            .ok_or(UniPinoNftErr::PdaErr)?;
-
-        if metadata_pda != self.metadata_pda.key().as_ref() {
-            return Err(ProgramError::InvalidSeeds);
+        // the user PDA holds the NFT in its associated token account
+        create_associated_token_account(
+            &self.authority,
+            self.token_account,
+            &self.user_pda,
+            &self.mint_pda,
+            self.system_program,
+            self.token_program,
+        )?;
+
+        MintTo {
+            mint: &self.mint_pda,
+            account: self.token_account,
+            mint_authority: &self.user_pda,
+            amount: 1,
+            token_program: &TOKEN_2022_ID,
         }
-
+        .invoke_signed(&[Signer::from(&user_seeds.signer_seeds())])?;
+
+        let metadata = NftMeta::new(
+            self.mint_nft_args.asset_name,
+            self.mint_nft_args.collection,
+            self.mint_nft_args.uri,
+            self.mint_nft_args.desc,
+            *self.user_pda.key(),
+            self.mint_nft_args.attribute,
+        );
         let metadata_lamport = Rent::get()?.minimum_balance(NftMeta::INIT_SPACE);
-        if self.metadata_pda.lamports() == 0 {
-            let platform_seeds = [
-                Seed::from(platform::PLATFORM_TOKEN),
-                Seed::from(self.administrator.key().as_ref()),
-                Seed::from(core::slice::from_ref(&platform.bump)),
+        if !self.metadata_pda.is_initialized() {
+            let metadata_seeds = [
+                Seed::from(b"metadata"),
+                Seed::from(self.mint_pda.key().as_ref()),
+                Seed::from(TOKEN_2022_ID.as_ref()),
+                Seed::from(core::slice::from_ref(&self.metadata_pda.bump)),
             ];
-            let platform_signer = Signer::from(&platform_seeds);
-
-            let user_seeds = [
-                Seed::from(user::USER_TOKEN),
-                Seed::from(self.platform_pda.key().as_ref()),
-                Seed::from(core::slice::from_ref(&user.bump)),
-            ];
-            let user_signer = Signer::from(&user_seeds);
 
             CreateAccount {
-                from: self.administrator,
-                to: self.metadata_pda,
+                from: &self.authority,
+                to: &self.metadata_pda,
                 lamports: metadata_lamport,
                 space: NftMeta::INIT_SPACE as u64,
                 owner: &ID,
             }
-            .invoke_signed(&[platform_signer, user_signer, mint_signer])?;
+            .invoke_signed(&[Signer::from(&metadata_seeds)])?;
+
+            metadata.init(&self.metadata_pda)?;
+        } else {
+            metadata.write_fields(self.metadata_pda.try_borrow_mut_data()?.as_mut())?;
         }
-        let metadata = NftMeta {
-            discriminator: NftMeta::DISCRIMINATOR,
-            name: self.mint_nft_args.asset_name,
-            collection: [0; 64],
-            uri: self.mint_nft_args.uri,
-            description: self.mint_nft_args.desc,
-        };
-        self.metadata_pda
-            .try_borrow_mut_data()?
-            .copy_from_slice(bytes_of(&metadata));
 
-        // update mint count
-        platform.total_mints = platform
-            .total_mints
-            .checked_add(1)
-            .ok_or(ProgramError::ArithmeticOverflow)?;
-        user.nft_count = user
-            .nft_count
-            .checked_add(1)
-            .ok_or(ProgramError::ArithmeticOverflow)?;
+        NftMinted {
+            platform: *self.platform_pda.key(),
+            user: *self.user_pda.key(),
+            mint: *self.mint_pda.key(),
+            collection: self.mint_nft_args.collection,
+            fee: mint_fee,
+        }
+        .emit();
 
         log!("mint nft success");
         Ok(())
@@ -195,94 +298,115 @@ impl<'a> TryFrom<(&'a [AccountInfo], &'a [u8])> for MintNft<'a> {
         let (accounts, instruction_data) = value;
 
         let [
-            administrator,
+            authority,
             platform_pda,
             user_pda,
             mint_pda,
             metadata_pda,
+            token_account,
             fee_receiver,
-            _,
+            system_program,
+            token_program,
+            associated_token_program,
+            remaining @ ..,
         ] = accounts
         else {
             return Err(ProgramError::NotEnoughAccountKeys);
         };
 
-        if instruction_data.len() != size_of::<MintNftArgs>() {
-            return Err(ProgramError::InvalidInstructionData);
+        if token_program.key() != &TOKEN_2022_ID
+            || associated_token_program.key() != &ASSOCIATED_TOKEN_PROGRAM_ID
+        {
+            return Err(ProgramError::IncorrectProgramId);
         }
 
-        let mint_nft_args = try_from_bytes::<MintNftArgs>(instruction_data)
+        // the merkle proof of an allowlisted collection follows the arguments
+        let (args_data, proof_data) = instruction_data
+            .split_at_checked(size_of::<MintNftArgs>())
+            .ok_or(ProgramError::InvalidInstructionData)?;
+
+        let mint_nft_args = try_from_bytes::<MintNftArgs>(args_data)
             .map_err(|_| ProgramError::InvalidInstructionData)?;
 
+        let user_pda = ProgramAccount::try_from(user_pda)?;
+        let mint_pda = Pda::nft_mint(mint_pda, mint_nft_args.user_uuid, &user_pda)?;
+
         Ok(Self {
-            administrator,
-            platform_pda,
+            authority: SignerAccount::try_from(authority)?,
+            platform_pda: ProgramAccount::try_from(platform_pda)?,
             user_pda,
             mint_pda,
-            metadata_pda,
+            metadata_pda: Pda::nft_metadata(metadata_pda, &mint_pda)?,
+            token_account,
             fee_receiver,
+            system_program,
+            token_program,
             mint_nft_args,
+            proof: allowlist::proof_nodes(proof_data)?,
+            remaining,
         })
     }
 }
 
 pub struct UpdateNFTMetadata<'a> {
-    pub administrator: &'a AccountInfo,
-    pub platform_pda: &'a AccountInfo,
-    pub user_pda: &'a AccountInfo,
+    pub authority: SignerAccount<'a>,
+    pub platform_pda: ProgramAccount<'a, Platform>,
+    pub user_pda: ProgramAccount<'a, User>,
     pub mint_pda: &'a AccountInfo,
-    pub metadata_pda: &'a AccountInfo,
-    pub nft_meta: &'a super::NftMeta,
+    pub metadata_pda: ProgramAccount<'a, NftMeta>,
+    pub nft_meta: super::NftMetaArgs,
+    pub remaining: &'a [AccountInfo],
 }
 
 impl<'a> UpdateNFTMetadata<'a> {
     pub const DISCRIMINATOR: &'a u8 = &4;
 
     pub fn process(self) -> ProgramResult {
-        if !self.administrator.is_signer() {
-            return Err(ProgramError::MissingRequiredSignature);
-        }
-
-        if !self.platform_pda.is_owned_by(&ID)
-            || !self.user_pda.is_owned_by(&ID)
-            || !self.metadata_pda.is_owned_by(&ID)
-            || self.platform_pda.lamports() == 0
-            || self.user_pda.lamports() == 0
-            || self.metadata_pda.lamports() == 0
-        {
-            return Err(UniPinoNftErr::UninitPda.into());
-        }
-
-        let platform_data_bytes = self.platform_pda.try_borrow_data()?;
-        let platform = Platform::try_from_bytes(platform_data_bytes.as_ref())?;
-
-        let user_data_bytes = self.user_pda.try_borrow_data()?;
-        let user = User::try_from_bytes(user_data_bytes.as_ref())?;
-
-        if platform.administrator != self.administrator.key().as_ref()
-            || user.owner != self.platform_pda.key().as_ref()
-        {
-            return Err(ProgramError::InvalidAccountOwner);
+        let platform = self.platform_pda.load()?;
+        let user = self.user_pda.load()?;
+
+        self.user_pda
+            .check_platform(&user, &self.platform_pda, &platform)?;
+
+        role::authorize(
+            &self.authority,
+            &self.platform_pda,
+            &platform,
+            ROLE_METADATA_EDITOR,
+            self.remaining,
+        )?;
+
+        platform.ensure_not_paused(PAUSE_METADATA_UPDATE)?;
+
+        // the user has to hold the NFT, its metadata was checked against the mint when parsed
+        self.user_pda
+            .check_holder(&user, self.mint_pda, &self.metadata_pda)?;
+
+        // the collection is fixed at mint, where its price, allowlist and supply were
+        // enforced; moving the NFT into another collection would bypass them
+        let collection = NftMeta::collection_of(self.metadata_pda.try_borrow_data()?.as_ref())?;
+        if collection != self.nft_meta.collection {
+            return Err(UniPinoNftErr::CollectionMismatch.into());
         }
 
-        let metadata_pda_seeds = [b"metadata", self.mint_pda.key().as_ref(), &TOKEN_2022_ID];
-        let (metadata_pda, _) = try_find_program_address(&metadata_pda_seeds, &TOKEN_2022_ID)
-            .ok_or(UniPinoNftErr::PdaErr)?;
-
-        if metadata_pda != self.metadata_pda.key().as_ref() {
-            return Err(ProgramError::InvalidSeeds);
+        // legacy accounts keep their size until MigrateAccount upgrades them, and the
+        // recorded delegate and holder are kept
+        let updated_metadata = NftMeta::new(
+            self.nft_meta.name,
+            self.nft_meta.collection,
+            self.nft_meta.uri,
+            self.nft_meta.description,
+            *self.user_pda.key(),
+            self.nft_meta.attribute,
+        );
+        updated_metadata.write_fields(self.metadata_pda.try_borrow_mut_data()?.as_mut())?;
+
+        MetadataUpdated {
+            platform: *self.platform_pda.key(),
+            user: *self.user_pda.key(),
+            mint: *self.mint_pda.key(),
         }
-
-        let updated_metadata = NftMeta {
-            discriminator: NftMeta::DISCRIMINATOR,
-            name: self.nft_meta.name,
-            collection: self.nft_meta.collection,
-            uri: self.nft_meta.uri,
-            description: self.nft_meta.description,
-        };
-        self.metadata_pda
-            .try_borrow_mut_data()?
-            .copy_from_slice(bytes_of(&updated_metadata));
+        .emit();
 
         log!("nft metadata updated");
         Ok(())
@@ -296,154 +420,261 @@ impl<'a> TryFrom<(&'a [AccountInfo], &'a [u8])> for UpdateNFTMetadata<'a> {
         let (accounts, instruction_data) = value;
 
         let [
-            administrator,
+            authority,
             platform_pda,
             user_pda,
             mint_pda,
             metadata_pda,
             _,
+            remaining @ ..,
         ] = accounts
         else {
             return Err(ProgramError::NotEnoughAccountKeys);
         };
 
-        if instruction_data.len() != size_of::<super::NftMeta>() {
-            return Err(ProgramError::InvalidInstructionData);
-        }
+        let nft_meta = super::NftMetaArgs::parse(instruction_data)?;
 
-        let nft_meta = try_from_bytes::<super::NftMeta>(instruction_data)
-            .map_err(|_| ProgramError::InvalidInstructionData)?;
+        Pda::nft_metadata(metadata_pda, mint_pda)?;
 
         Ok(Self {
-            administrator,
-            platform_pda,
-            user_pda,
+            authority: SignerAccount::try_from(authority)?,
+            platform_pda: ProgramAccount::try_from(platform_pda)?,
+            user_pda: ProgramAccount::try_from(user_pda)?,
             mint_pda,
-            metadata_pda,
+            metadata_pda: ProgramAccount::try_from(metadata_pda)?,
             nft_meta,
+            remaining,
         })
     }
 }
 
 pub struct BurnNft<'a> {
     pub administrator: &'a AccountInfo,
-    pub platform_pda: &'a AccountInfo,
-    pub user_pda: &'a AccountInfo,
+    pub platform_pda: ProgramAccount<'a, Platform>,
+    pub user_pda: ProgramAccount<'a, User>,
     pub mint_pda: &'a AccountInfo,
-    pub metadata_pda: &'a AccountInfo,
+    pub metadata_pda: ProgramAccount<'a, NftMeta>,
+    pub token_account: &'a AccountInfo,
+    pub token_program: &'a AccountInfo,
+    pub authority: &'a AccountInfo,
+    pub intent: Option<(&'a UserIntent, &'a AccountInfo)>,
+    pub remaining: &'a [AccountInfo],
 }
 
 impl<'a> BurnNft<'a> {
     pub const DISCRIMINATOR: &'a u8 = &5;
 
     pub fn process(self) -> ProgramResult {
-        if !self.administrator.is_signer() {
-            return Err(ProgramError::MissingRequiredSignature);
+        let mut platform = self.platform_pda.load_mut()?;
+        let mut user = self.user_pda.load_mut()?;
+
+        platform.ensure_administrator(self.administrator.key())?;
+        self.user_pda
+            .check_platform(&user, &self.platform_pda, &platform)?;
+
+        // a relayed intent signed by the user's wallet stands in for the administrator
+        match self.intent {
+            None if self.authority.key() == self.administrator.key() => {
+                multisig::authorize_admin(
+                    self.administrator,
+                    &self.platform_pda,
+                    &platform,
+                    self.remaining,
+                )?;
+            }
+            Some((burn_intent, instructions_sysvar)) => intent::consume(
+                instructions_sysvar,
+                &self.user_pda,
+                &mut user,
+                self.mint_pda,
+                INTENT_BURN_NFT,
+                burn_intent,
+            )?,
+            None => {
+                session::authorize(
+                    self.authority,
+                    &platform,
+                    &self.user_pda,
+                    &user,
+                    *Self::DISCRIMINATOR,
+                    self.remaining,
+                )?;
+            }
         }
 
-        if !self.platform_pda.is_owned_by(&ID)
-            || !self.user_pda.is_owned_by(&ID)
-            || !self.metadata_pda.is_owned_by(&ID)
-            || self.platform_pda.lamports() == 0
-            || self.user_pda.lamports() == 0
-            || self.metadata_pda.lamports() == 0
-        {
-            return Err(UniPinoNftErr::UninitPda.into());
-        }
+        platform.ensure_not_paused(PAUSE_BURN)?;
 
-        let mut platform_data_bytes = self.platform_pda.try_borrow_mut_data()?;
-        let platform = Platform::try_from_bytes_mut(platform_data_bytes.as_mut())?;
+        // the user has to hold the NFT, its metadata was checked against the mint when parsed
+        self.user_pda
+            .check_holder(&user, self.mint_pda, &self.metadata_pda)?;
+        fee::validate_token_account(
+            self.token_account,
+            self.mint_pda,
+            self.token_program,
+            self.user_pda.key(),
+        )?;
 
-        let mut user_data_bytes = self.user_pda.try_borrow_mut_data()?;
-        let user = User::try_from_bytes_mut(user_data_bytes.as_mut())?;
+        // Update mint counts
+        platform.total_mints = platform
+            .total_mints
+            .checked_sub(1)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        user.nft_count = user
+            .nft_count
+            .checked_sub(1)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
 
-        if platform.administrator != self.administrator.key().as_ref()
-            || user.owner != self.platform_pda.key().as_ref()
-        {
-            return Err(ProgramError::InvalidAccountOwner);
+        // the user PDA owns the token account and the platform PDA closes the mint, so the
+        // CPIs below need both unborrowed
+        let user_seeds = UserSeeds::new(&user, &self.platform_pda, &platform);
+        let seed_key = platform.seed_key;
+        let platform_bump = platform.bump;
+        drop(user);
+        drop(platform);
+
+        let user_signer_seeds = user_seeds.signer_seeds();
+        let user_signer = Signer::from(&user_signer_seeds);
+        Burn {
+            account: self.token_account,
+            mint: self.mint_pda,
+            authority: &self.user_pda,
+            amount: 1,
+            token_program: &TOKEN_2022_ID,
         }
-
-        // Validate metadata PDA is derived from the mint
-        let metadata_pda_seeds = [b"metadata", self.mint_pda.key().as_ref(), &TOKEN_2022_ID];
-        let (metadata_pda, _) = try_find_program_address(&metadata_pda_seeds, &TOKEN_2022_ID)
-            .ok_or(UniPinoNftErr::PdaErr)?;
-
-        if metadata_pda != self.metadata_pda.key().as_ref() {
-            return Err(ProgramError::InvalidSeeds);
+        .invoke_signed(core::slice::from_ref(&user_signer))?;
+        CloseAccount {
+            account: self.token_account,
+            destination: self.administrator,
+            authority: &self.user_pda,
+            token_program: &TOKEN_2022_ID,
         }
+        .invoke_signed(core::slice::from_ref(&user_signer))?;
 
-        // Close the mint account via Token-2022 CPI
-        // The user_pda is the mint authority, so we need it to sign
-        let user_uuid_val = { user.user_uuid }; // Copy from packed struct
-        let user_uuid_str = user_uuid_val.to_string();
-        let user_seeds = [
-            Seed::from(user::USER_TOKEN),
-            Seed::from(user_uuid_str.as_bytes()),
-            Seed::from(self.platform_pda.key().as_ref()),
-            Seed::from(core::slice::from_ref(&platform.bump)),
-            Seed::from(core::slice::from_ref(&user.bump)),
+        let platform_seeds = [
+            Seed::from(platform::PLATFORM_TOKEN),
+            Seed::from(seed_key.as_ref()),
+            Seed::from(core::slice::from_ref(&platform_bump)),
         ];
-        let user_signer = Signer::from(&user_seeds);
-
         CloseAccount {
             account: self.mint_pda,
             destination: self.administrator,
-            authority: self.user_pda,
+            authority: &self.platform_pda,
             token_program: &TOKEN_2022_ID,
         }
-        .invoke_signed(&[user_signer])?;
+        .invoke_signed(&[Signer::from(&platform_seeds)])?;
 
         // Close metadata account by transferring lamports to administrator
-        let metadata_lamports = self.metadata_pda.lamports();
-        unsafe {
-            *self.metadata_pda.borrow_mut_lamports_unchecked() = 0;
-            *self.administrator.borrow_mut_lamports_unchecked() = self
-                .administrator
-                .lamports()
-                .checked_add(metadata_lamports)
-                .ok_or(ProgramError::ArithmeticOverflow)?;
-        }
-
-        // Zero out the metadata account data
-        self.metadata_pda.try_borrow_mut_data()?.fill(0);
+        accounts::close(&self.metadata_pda, self.administrator)?;
 
-        // Update mint counts
-        platform.total_mints = platform
-            .total_mints
-            .checked_sub(1)
-            .ok_or(ProgramError::ArithmeticOverflow)?;
-        user.nft_count = user
-            .nft_count
-            .checked_sub(1)
-            .ok_or(ProgramError::ArithmeticOverflow)?;
+        NftBurned {
+            platform: *self.platform_pda.key(),
+            user: *self.user_pda.key(),
+            mint: *self.mint_pda.key(),
+        }
+        .emit();
 
         log!("burn nft success");
         Ok(())
     }
 }
 
-impl<'a> TryFrom<&'a [AccountInfo]> for BurnNft<'a> {
-    type Error = ProgramError;
-
-    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+impl<'a> BurnNft<'a> {
+    /// Parse the accounts both burn instructions share, returning the ones after them
+    fn parse(accounts: &'a [AccountInfo]) -> Result<(Self, &'a [AccountInfo]), ProgramError> {
         let [
             administrator,
             platform_pda,
             user_pda,
             mint_pda,
             metadata_pda,
-            _,
+            token_account,
+            token_program,
+            remaining @ ..,
         ] = accounts
         else {
             return Err(ProgramError::NotEnoughAccountKeys);
         };
 
-        Ok(Self {
+        if token_program.key() != &TOKEN_2022_ID {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        Pda::nft_metadata(metadata_pda, mint_pda)?;
+
+        let burn = Self {
             administrator,
-            platform_pda,
-            user_pda,
+            platform_pda: ProgramAccount::try_from(platform_pda)?,
+            user_pda: ProgramAccount::try_from(user_pda)?,
             mint_pda,
-            metadata_pda,
+            metadata_pda: ProgramAccount::try_from(metadata_pda)?,
+            token_account,
+            token_program,
+            authority: administrator,
+            intent: None,
+            remaining: &[],
+        };
+        Ok((burn, remaining))
+    }
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &'a [u8])> for BurnNft<'a> {
+    type Error = ProgramError;
+
+    fn try_from(value: (&'a [AccountInfo], &'a [u8])) -> Result<Self, Self::Error> {
+        let (accounts, instruction_data) = value;
+
+        // intents go through `BurnNftWithIntent`
+        if !instruction_data.is_empty() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        // a linked wallet or session key signing in place of the administrator comes
+        // first in the remaining accounts, otherwise they hold the multisig approvals
+        let (burn, remaining) = Self::parse(accounts)?;
+        Ok(match remaining {
+            [authority, rest @ ..]
+                if authority.is_signer() && authority.key() != burn.administrator.key() =>
+            {
+                Self {
+                    authority,
+                    remaining: rest,
+                    ..burn
+                }
+            }
+            _ => Self { remaining, ..burn },
         })
     }
 }
+
+/// `BurnNft` authorized by an intent signed with the user's linked wallet, relayed
+/// without the administrator signature
+pub struct BurnNftWithIntent<'a>(BurnNft<'a>);
+
+impl<'a> BurnNftWithIntent<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &31;
+
+    pub fn process(self) -> ProgramResult {
+        self.0.process()
+    }
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &'a [u8])> for BurnNftWithIntent<'a> {
+    type Error = ProgramError;
+
+    fn try_from(value: (&'a [AccountInfo], &'a [u8])) -> Result<Self, Self::Error> {
+        let (accounts, instruction_data) = value;
+
+        let burn_intent = try_from_bytes::<UserIntent>(instruction_data)
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+        let (burn, remaining) = BurnNft::parse(accounts)?;
+        let [instructions_sysvar, ..] = remaining else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        Ok(Self(BurnNft {
+            intent: Some((burn_intent, instructions_sysvar)),
+            ..burn
+        }))
+    }
+}
@@ -0,0 +1,123 @@
+use bytemuck::{bytes_of, try_from_bytes};
+use pinocchio::ProgramResult;
+use pinocchio::account_info::AccountInfo;
+use pinocchio::cpi::set_return_data;
+use pinocchio::program_error::ProgramError;
+use pinocchio_log::log;
+use pinocchio_token_2022::ID as TOKEN_2022_ID;
+use pinocchio_token_2022::state::TokenAccount;
+
+use crate::error::UniPinoNftErr;
+use crate::state::nft_meta::NftMeta;
+use crate::state::platform::Platform;
+use crate::state::user::User;
+
+use super::accounts::{Pda, ProgramAccount};
+use super::*;
+
+pub struct VerifyOwnership<'a> {
+    pub platform_pda: ProgramAccount<'a, Platform>,
+    pub user_pda: ProgramAccount<'a, User>,
+    pub mint_pda: &'a AccountInfo,
+    pub metadata_pda: ProgramAccount<'a, NftMeta>,
+    pub token_account: &'a AccountInfo,
+    pub verify_args: &'a VerifyOwnershipArgs,
+}
+
+impl<'a> VerifyOwnership<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &24;
+
+    /// Check the holder owns the NFT held by the user on the platform, within the
+    /// requested collection and with the requested attribute if any
+    pub fn verify(&self) -> Result<OwnershipProof, ProgramError> {
+        let platform = self.platform_pda.load()?;
+        let user = self.user_pda.load()?;
+        self.user_pda
+            .check_platform(&user, &self.platform_pda, &platform)?;
+
+        self.user_pda
+            .check_holder(&user, self.mint_pda, &self.metadata_pda)?;
+
+        let collection = &self.verify_args.collection;
+        if *collection != [0; 64]
+            && NftMeta::collection_of(self.metadata_pda.try_borrow_data()?.as_ref())? != *collection
+        {
+            return Err(UniPinoNftErr::CollectionMismatch.into());
+        }
+
+        let attribute = &self.verify_args.attribute;
+        if *attribute != [0; 32]
+            && NftMeta::attribute_of(self.metadata_pda.try_borrow_data()?.as_ref())? != *attribute
+        {
+            return Err(UniPinoNftErr::AttributeMismatch.into());
+        }
+
+        if !self.token_account.is_owned_by(&TOKEN_2022_ID)
+            || self.token_account.data_len() < TokenAccount::BASE_LEN
+        {
+            return Err(UniPinoNftErr::WrongTokenAccount.into());
+        }
+
+        let data = self.token_account.try_borrow_data()?;
+        // SAFETY: owner and length are checked above, and `TokenAccount` has an alignment of 1
+        let token_account = unsafe { TokenAccount::from_bytes_unchecked(&data) };
+        if token_account.mint() != self.mint_pda.key()
+            || token_account.owner() != &self.verify_args.holder
+            || token_account.amount() != 1
+        {
+            return Err(UniPinoNftErr::WrongTokenAccount.into());
+        }
+
+        Ok(OwnershipProof {
+            platform: *self.platform_pda.key(),
+            user_uuid: user.user_uuid,
+            mint: *self.mint_pda.key(),
+            holder: self.verify_args.holder,
+        })
+    }
+
+    pub fn process(self) -> ProgramResult {
+        let proof = self.verify()?;
+        set_return_data(bytes_of(&proof));
+
+        log!("ownership verified for {}", &proof.holder);
+        Ok(())
+    }
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &'a [u8])> for VerifyOwnership<'a> {
+    type Error = ProgramError;
+
+    fn try_from(value: (&'a [AccountInfo], &'a [u8])) -> Result<Self, Self::Error> {
+        let (accounts, instruction_data) = value;
+
+        let [
+            platform_pda,
+            user_pda,
+            mint_pda,
+            metadata_pda,
+            token_account,
+        ] = accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if instruction_data.len() != size_of::<VerifyOwnershipArgs>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let verify_args = try_from_bytes::<VerifyOwnershipArgs>(instruction_data)
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+        Ok(Self {
+            platform_pda: ProgramAccount::try_from(platform_pda)?,
+            user_pda: ProgramAccount::try_from(user_pda)?,
+            mint_pda,
+            metadata_pda: ProgramAccount::try_from(
+                Pda::nft_metadata(metadata_pda, mint_pda)?.info(),
+            )?,
+            token_account,
+            verify_args,
+        })
+    }
+}
@@ -1,53 +1,51 @@
-use bytemuck::{bytes_of, try_from_bytes};
+use bytemuck::{Zeroable, try_from_bytes};
+use core::slice::Iter;
 use pinocchio::ProgramResult;
 use pinocchio::account_info::AccountInfo;
 use pinocchio::instruction::{Seed, Signer};
 use pinocchio::program_error::ProgramError;
-use pinocchio::pubkey::try_find_program_address;
+use pinocchio::pubkey::Pubkey;
 use pinocchio::sysvars::Sysvar;
 use pinocchio::sysvars::rent::Rent;
 
 use pinocchio_log::log;
 use pinocchio_system::instructions::CreateAccount;
 
+use super::accounts::{Pda, ProgramAccount, SignerAccount};
 use super::*;
 use crate::error::UniPinoNftErr;
-use crate::state::platform::Platform;
+use crate::events::{ConfigUpdated, Event, PlatformInitialized};
+use crate::state::collection::Collection;
+use crate::state::fee_schedule::{FeeSchedule, FeeShare, MAX_BPS, MAX_FEE_SHARES};
+use crate::state::pending_collection::PendingCollectionChange;
+use crate::state::platform::{FeeTier, MAX_FEE_TIERS, PAUSE_ALL, Platform};
+use crate::state::program_state::ProgramState;
+use crate::state::role::Role;
 
 pub const PLATFORM_TOKEN: &[u8] = b"administer";
 
 pub struct InitPlatform<'a> {
-    administrator: &'a AccountInfo,
-    platform_pda: &'a AccountInfo,
+    administrator: SignerAccount<'a>,
+    platform_pda: Pda<'a>,
 }
 
 pub struct UpdatePlatformConfig<'a> {
-    administrator: &'a AccountInfo,
-    platform_pda: &'a AccountInfo,
-    update_args: &'a UpdatePlatformArgs,
+    pub administrator: &'a AccountInfo,
+    pub platform_pda: ProgramAccount<'a, Platform>,
+    pub update_args: UpdatePlatformArgs,
+    pub remaining: &'a [AccountInfo],
 }
 
 impl<'a> InitPlatform<'a> {
     pub const DISCRIMINATOR: &'a u8 = &0;
 
     pub fn process(self) -> ProgramResult {
-        // authority is the administration wallet which is configured by server backend
-        if !self.administrator.is_signer() {
-            return Err(ProgramError::MissingRequiredSignature);
-        }
-
-        let (pda, bump) =
-            try_find_program_address(&[PLATFORM_TOKEN, self.administrator.key().as_ref()], &ID)
-                .ok_or(UniPinoNftErr::PdaErr)?;
-
-        if pda != self.platform_pda.key().as_ref() {
-            return Err(ProgramError::InvalidSeeds);
-        }
-
-        if self.platform_pda.lamports() > 0 {
+        if self.platform_pda.is_initialized() {
             return Err(UniPinoNftErr::ReInitPda.into());
         }
 
+        let bump = self.platform_pda.bump;
+
         let signer_seeds = [
             Seed::from(PLATFORM_TOKEN),
             Seed::from(self.administrator.key().as_ref()),
@@ -69,9 +67,13 @@ impl<'a> InitPlatform<'a> {
 
         // fill data field in PDA
         let platform_init_state = Platform::new(*self.administrator.key(), bump);
-        self.platform_pda
-            .try_borrow_mut_data()?
-            .copy_from_slice(bytes_of(&platform_init_state));
+        platform_init_state.init(&self.platform_pda)?;
+
+        PlatformInitialized {
+            platform: *self.platform_pda.key(),
+            administrator: *self.administrator.key(),
+        }
+        .emit();
 
         log!("platform pda created");
         Ok(())
@@ -86,9 +88,18 @@ impl<'a> TryFrom<&'a [AccountInfo]> for InitPlatform<'a> {
             return Err(ProgramError::NotEnoughAccountKeys);
         };
 
+        // authority is the administration wallet which is configured by server backend
+        let administrator = SignerAccount::try_from(administrator)?;
+        let platform_pda = Pda::find(
+            platform_pda,
+            &[PLATFORM_TOKEN, administrator.key().as_ref()],
+            &ID,
+            UniPinoNftErr::PdaMismatch,
+        )?;
+
         Ok(Self {
-            administrator: administrator,
-            platform_pda: platform_pda,
+            administrator,
+            platform_pda,
         })
     }
 }
@@ -97,28 +108,44 @@ impl<'a> UpdatePlatformConfig<'a> {
     pub const DISCRIMINATOR: &'a u8 = &1;
 
     pub fn process(self) -> ProgramResult {
-        if !self.administrator.is_signer() {
-            return Err(ProgramError::MissingRequiredSignature);
+        let mut platform_state = self.platform_pda.load_mut()?;
+
+        let remaining = multisig::authorize_admin(
+            self.administrator,
+            &self.platform_pda,
+            &platform_state,
+            self.remaining,
+        )?;
+
+        let platform_address = Pda::find(
+            &self.platform_pda,
+            &[PLATFORM_TOKEN, platform_state.seed_key.as_ref()],
+            &ID,
+            UniPinoNftErr::PdaMismatch,
+        )?;
+        if platform_address.bump != platform_state.bump {
+            return Err(UniPinoNftErr::PdaMismatch.into());
         }
 
-        if !self.platform_pda.is_owned_by(&ID) || self.platform_pda.lamports() == 0 {
-            return Err(UniPinoNftErr::UninitPda.into());
+        // with a delay configured, changes go through QueueConfigChange instead
+        if platform_state.config_delay > 0 {
+            return Err(UniPinoNftErr::ConfigTimelocked.into());
         }
 
-        let mut platform_data_bytes = self.platform_pda.try_borrow_mut_data()?;
-        let platform_state = Platform::try_from_bytes_mut(platform_data_bytes.as_mut())?;
+        self.apply(&mut platform_state, remaining)?;
 
-        if platform_state.administrator != self.administrator.key().as_ref() {
-            return Err(ProgramError::InvalidAccountOwner);
-        }
-
-        let (pda, bump) =
-            try_find_program_address(&[PLATFORM_TOKEN, self.administrator.key().as_ref()], &ID)
-                .ok_or(UniPinoNftErr::PdaErr)?;
+        log!("platform config updated");
+        Ok(())
+    }
 
-        if pda != self.platform_pda.key().as_ref() || bump != platform_state.bump {
-            return Err(ProgramError::InvalidSeeds);
-        }
+    /// Apply `update_args` to an already authorized platform
+    pub(crate) fn apply(
+        &self,
+        platform_state: &mut Platform,
+        remaining: &'a [AccountInfo],
+    ) -> ProgramResult {
+        // optional accounts are consumed in order by the sections that need them
+        let mut remaining = remaining.iter();
 
         // update plateform state
         if self.update_args.is_receiver_valid != 0 {
@@ -127,9 +154,192 @@ impl<'a> UpdatePlatformConfig<'a> {
                 .fee_receiver
                 .copy_from_slice(&self.update_args.fee_receiver);
         }
+        if self.update_args.is_fee_mint_valid != 0 {
+            // token counters are in units of the fee mint, so they have to balance before
+            // fees start arriving in another mint
+            if platform_state.fee_mint != self.update_args.fee_mint
+                && platform_state.treasury_tokens_collected
+                    > platform_state.treasury_tokens_withdrawn
+            {
+                return Err(UniPinoNftErr::TreasuryNotDrained.into());
+            }
+            log!("update fee_mint to {}", &self.update_args.fee_mint);
+            platform_state
+                .fee_mint
+                .copy_from_slice(&self.update_args.fee_mint);
+        }
+        if self.update_args.is_fee_schedule_valid != 0 {
+            self.update_fee_schedule(platform_state, &mut remaining)?;
+        }
+        if self.update_args.is_treasury_valid != 0 {
+            self.update_treasury(platform_state, &mut remaining)?;
+        }
+        if self.update_args.is_fee_tiers_valid != 0 {
+            self.update_fee_tiers(platform_state)?;
+        }
+        if self.update_args.is_config_delay_valid != 0 {
+            if self.update_args.config_delay < 0 {
+                return Err(ProgramError::InvalidInstructionData);
+            }
+            log!("update config_delay to {}", self.update_args.config_delay);
+            platform_state.config_delay = self.update_args.config_delay;
+        }
         platform_state.mint_fee = self.update_args.mint_fee;
 
-        log!("platform config updated");
+        ConfigUpdated {
+            platform: *self.platform_pda.key(),
+            mint_fee: platform_state.mint_fee,
+            fee_receiver: platform_state.fee_receiver,
+            fee_mint: platform_state.fee_mint,
+            fee_schedule_enabled: platform_state.fee_schedule_enabled,
+            treasury_enabled: platform_state.treasury_enabled,
+            fee_tier_count: platform_state.fee_tier_count,
+            config_delay: platform_state.config_delay,
+        }
+        .emit();
+
+        Ok(())
+    }
+
+    fn update_fee_schedule(
+        &self,
+        platform_state: &mut Platform,
+        remaining: &mut Iter<'a, AccountInfo>,
+    ) -> ProgramResult {
+        let share_count = self.update_args.fee_share_count as usize;
+        if share_count == 0 {
+            log!("fee splitting disabled");
+            platform_state.fee_schedule_enabled = 0;
+            return Ok(());
+        }
+
+        if share_count > MAX_FEE_SHARES {
+            return Err(UniPinoNftErr::InvalidFeeConfig.into());
+        }
+
+        let shares = &self.update_args.fee_shares[..share_count];
+        let mut total_bps: u16 = 0;
+        for share in shares {
+            if share.receiver == Pubkey::default() {
+                return Err(UniPinoNftErr::InvalidFeeConfig.into());
+            }
+            total_bps = total_bps
+                .checked_add(share.bps)
+                .ok_or(UniPinoNftErr::FeeTooHigh)?;
+        }
+        if total_bps > MAX_BPS {
+            return Err(UniPinoNftErr::FeeTooHigh.into());
+        }
+
+        let fee_schedule_pda = Pda::find(
+            remaining.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+            &[fee::FEE_SCHEDULE_TOKEN, self.platform_pda.key().as_ref()],
+            &ID,
+            UniPinoNftErr::PdaMismatch,
+        )?;
+
+        if !fee_schedule_pda.is_initialized() {
+            let signer_seeds = [
+                Seed::from(fee::FEE_SCHEDULE_TOKEN),
+                Seed::from(self.platform_pda.key().as_ref()),
+                Seed::from(core::slice::from_ref(&fee_schedule_pda.bump)),
+            ];
+            let signer = Signer::from(&signer_seeds);
+
+            CreateAccount {
+                from: self.administrator,
+                to: &fee_schedule_pda,
+                lamports: Rent::get()?.minimum_balance(FeeSchedule::INIT_SPACE),
+                space: FeeSchedule::INIT_SPACE as u64,
+                owner: &ID,
+            }
+            .invoke_signed(&[signer])?;
+
+            FeeSchedule::new(*self.platform_pda.key(), fee_schedule_pda.bump)
+                .init(&fee_schedule_pda)?;
+        }
+
+        let fee_schedule_pda = ProgramAccount::<FeeSchedule>::try_from(fee_schedule_pda.info())?;
+        let mut fee_schedule = fee_schedule_pda.load_mut()?;
+        fee_schedule.share_count = share_count as u8;
+        fee_schedule.shares = self.update_args.fee_shares;
+        fee_schedule.shares[share_count..].fill(FeeShare::zeroed());
+
+        platform_state.fee_schedule_enabled = 1;
+        log!("fee schedule updated with {} receivers", share_count);
+        Ok(())
+    }
+
+    fn update_fee_tiers(&self, platform_state: &mut Platform) -> ProgramResult {
+        let tier_count = self.update_args.fee_tier_count as usize;
+        if tier_count > MAX_FEE_TIERS {
+            return Err(UniPinoNftErr::InvalidFeeConfig.into());
+        }
+
+        let tiers = &self.update_args.fee_tiers[..tier_count];
+        for (i, tier) in tiers.iter().enumerate() {
+            if tier.discount_bps > MAX_BPS {
+                return Err(UniPinoNftErr::FeeTooHigh.into());
+            }
+            // strictly ascending thresholds keep tier lookup unambiguous
+            if i > 0 && tiers[i - 1].min_level >= tier.min_level {
+                return Err(UniPinoNftErr::InvalidFeeConfig.into());
+            }
+        }
+
+        platform_state.fee_tier_count = tier_count as u8;
+        platform_state.fee_tiers = self.update_args.fee_tiers;
+        platform_state.fee_tiers[tier_count..].fill(FeeTier::zeroed());
+
+        log!("fee tiers updated with {} tiers", tier_count);
+        Ok(())
+    }
+
+    fn update_treasury(
+        &self,
+        platform_state: &mut Platform,
+        remaining: &mut Iter<'a, AccountInfo>,
+    ) -> ProgramResult {
+        platform_state.treasury_max_withdrawal = self.update_args.treasury_max_withdrawal;
+
+        if self.update_args.treasury_enabled == 0 {
+            log!("treasury disabled");
+            platform_state.treasury_enabled = 0;
+            return Ok(());
+        }
+
+        let treasury_pda = Pda::find(
+            remaining.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+            &[treasury::TREASURY_TOKEN, self.platform_pda.key().as_ref()],
+            &ID,
+            UniPinoNftErr::PdaMismatch,
+        )?;
+        let bump = treasury_pda.bump;
+
+        if !treasury_pda.is_initialized() {
+            let signer_seeds = [
+                Seed::from(treasury::TREASURY_TOKEN),
+                Seed::from(self.platform_pda.key().as_ref()),
+                Seed::from(core::slice::from_ref(&bump)),
+            ];
+            let signer = Signer::from(&signer_seeds);
+
+            // data-less but program owned, so withdrawals can debit it directly
+            CreateAccount {
+                from: self.administrator,
+                to: &treasury_pda,
+                lamports: Rent::get()?.minimum_balance(0),
+                space: 0,
+                owner: &ID,
+            }
+            .invoke_signed(&[signer])?;
+        } else if !treasury_pda.is_owned_by(&ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        platform_state.treasury_enabled = 1;
+        platform_state.treasury_bump = bump;
+        log!("treasury enabled");
         Ok(())
     }
 }
@@ -140,21 +350,312 @@ impl<'a> TryFrom<(&'a [AccountInfo], &'a [u8])> for UpdatePlatformConfig<'a> {
     fn try_from(value: (&'a [AccountInfo], &'a [u8])) -> Result<Self, Self::Error> {
         let (accounts, instruction_data) = value;
 
-        let [administrator, platform_pda, _] = accounts else {
+        let [administrator, platform_pda, _, remaining @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        Ok(Self {
+            administrator,
+            platform_pda: ProgramAccount::try_from(platform_pda)?,
+            update_args: UpdatePlatformArgs::parse(instruction_data)?,
+            remaining,
+        })
+    }
+}
+
+pub struct SetPauseFlags<'a> {
+    administrator: &'a AccountInfo,
+    platform_pda: ProgramAccount<'a, Platform>,
+    pause_args: &'a SetPauseFlagsArgs,
+    remaining: &'a [AccountInfo],
+}
+
+impl<'a> SetPauseFlags<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &8;
+
+    pub fn process(self) -> ProgramResult {
+        let mut platform_state = self.platform_pda.load_mut()?;
+
+        multisig::authorize_admin(
+            self.administrator,
+            &self.platform_pda,
+            &platform_state,
+            self.remaining,
+        )?;
+
+        if self.pause_args.paused & !PAUSE_ALL != 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        platform_state.paused = self.pause_args.paused;
+
+        log!("platform pause flags set to {}", platform_state.paused);
+        Ok(())
+    }
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &'a [u8])> for SetPauseFlags<'a> {
+    type Error = ProgramError;
+
+    fn try_from(value: (&'a [AccountInfo], &'a [u8])) -> Result<Self, Self::Error> {
+        let (accounts, instruction_data) = value;
+
+        let [administrator, platform_pda, remaining @ ..] = accounts else {
             return Err(ProgramError::NotEnoughAccountKeys);
         };
 
-        if instruction_data.len() != size_of::<UpdatePlatformArgs>() {
+        if instruction_data.len() != size_of::<SetPauseFlagsArgs>() {
             return Err(ProgramError::InvalidInstructionData);
         }
 
-        let update_platform_args = try_from_bytes::<UpdatePlatformArgs>(instruction_data)
+        let pause_args = try_from_bytes::<SetPauseFlagsArgs>(instruction_data)
             .map_err(|_| ProgramError::InvalidInstructionData)?;
 
         Ok(Self {
-            administrator: administrator,
-            platform_pda: platform_pda,
-            update_args: &update_platform_args,
+            administrator,
+            platform_pda: ProgramAccount::try_from(platform_pda)?,
+            pause_args,
+            remaining,
+        })
+    }
+}
+
+pub struct RotateAdministrator<'a> {
+    pub administrator: &'a AccountInfo,
+    pub platform_pda: ProgramAccount<'a, Platform>,
+    pub new_administrator: SignerAccount<'a>,
+    pub remaining: &'a [AccountInfo],
+}
+
+impl<'a> RotateAdministrator<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &33;
+
+    pub fn process(self) -> ProgramResult {
+        let mut platform_state = self.platform_pda.load_mut()?;
+
+        multisig::authorize_admin(
+            self.administrator,
+            &self.platform_pda,
+            &platform_state,
+            self.remaining,
+        )?;
+
+        // the PDA stays seeded by `seed_key`, only the key authorized as administrator moves
+        platform_state.administrator = *self.new_administrator.key();
+
+        log!("administrator rotated to {}", self.new_administrator.key());
+        Ok(())
+    }
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for RotateAdministrator<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [
+            administrator,
+            platform_pda,
+            new_administrator,
+            remaining @ ..,
+        ] = accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        Ok(Self {
+            administrator,
+            platform_pda: ProgramAccount::try_from(platform_pda)?,
+            // the new key signs, so the platform cannot be handed to a key nobody holds
+            new_administrator: SignerAccount::try_from(new_administrator)?,
+            remaining,
+        })
+    }
+}
+
+pub struct ClosePlatform<'a> {
+    pub administrator: &'a AccountInfo,
+    pub platform_pda: ProgramAccount<'a, Platform>,
+    pub multisig_pda: Pda<'a>,
+    pub fee_schedule_pda: Pda<'a>,
+    pub pending_config_pda: Pda<'a>,
+    pub treasury_pda: Pda<'a>,
+    pub remaining: &'a [AccountInfo],
+}
+
+impl<'a> ClosePlatform<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &16;
+
+    pub fn process(self) -> ProgramResult {
+        {
+            let mut platform_state = self.platform_pda.load_mut()?;
+
+            let children = multisig::authorize_admin(
+                self.administrator,
+                &self.platform_pda,
+                &platform_state,
+                self.remaining,
+            )?;
+
+            // users and NFTs still point at the platform PDA, and the treasury can only be
+            // drained while it exists
+            if platform_state.total_users != 0
+                || platform_state.total_mints != 0
+                || platform_state.has_undrained_treasury()
+            {
+                return Err(UniPinoNftErr::AccountNotEmpty.into());
+            }
+
+            // a platform created again at the same address would find every account left
+            // behind still pointing at it, so all of them have to close with it
+            for child in children {
+                self.close_child(&mut platform_state, child)?;
+            }
+            if platform_state.role_count != 0
+                || platform_state.collection_count != 0
+                || platform_state.collection_change_count != 0
+            {
+                return Err(UniPinoNftErr::AccountNotEmpty.into());
+            }
+
+            for pda in [
+                &self.multisig_pda,
+                &self.fee_schedule_pda,
+                &self.pending_config_pda,
+                &self.treasury_pda,
+            ] {
+                if pda.is_owned_by(&ID) && pda.is_initialized() {
+                    accounts::close(pda, self.administrator)?;
+                }
+            }
+        }
+
+        accounts::close(&self.platform_pda, self.administrator)?;
+
+        log!("platform pda closed");
+        Ok(())
+    }
+
+    /// Close a role, collection or queued collection change PDA of the platform
+    fn close_child(&self, platform_state: &mut Platform, child: &AccountInfo) -> ProgramResult {
+        let owner = if let Ok(role_pda) = ProgramAccount::<Role>::try_from(child) {
+            platform_state.role_count = platform_state
+                .role_count
+                .checked_sub(1)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            role_pda.load()?.platform
+        } else if let Ok(collection_pda) = ProgramAccount::<Collection>::try_from(child) {
+            platform_state.collection_count = platform_state
+                .collection_count
+                .checked_sub(1)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            collection_pda.load()?.platform
+        } else {
+            let pending_pda = ProgramAccount::<PendingCollectionChange>::try_from(child)?;
+            platform_state.collection_change_count = platform_state
+                .collection_change_count
+                .checked_sub(1)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            pending_pda.load()?.platform
+        };
+
+        if owner != self.platform_pda.key().as_ref() {
+            return Err(UniPinoNftErr::PlatformMismatch.into());
+        }
+
+        accounts::close(child, self.administrator)
+    }
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for ClosePlatform<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [
+            administrator,
+            platform_pda,
+            multisig_pda,
+            fee_schedule_pda,
+            pending_config_pda,
+            treasury_pda,
+            remaining @ ..,
+        ] = accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        let platform_pda = ProgramAccount::try_from(platform_pda)?;
+        let platform_key = platform_pda.key().as_ref();
+        let find = |info, token| {
+            Pda::find(
+                info,
+                &[token, platform_key],
+                &ID,
+                UniPinoNftErr::PdaMismatch,
+            )
+        };
+
+        Ok(Self {
+            administrator,
+            multisig_pda: find(multisig_pda, multisig::ADMIN_MULTISIG_TOKEN)?,
+            fee_schedule_pda: find(fee_schedule_pda, fee::FEE_SCHEDULE_TOKEN)?,
+            pending_config_pda: find(pending_config_pda, timelock::CONFIG_CHANGE_TOKEN)?,
+            treasury_pda: find(treasury_pda, treasury::TREASURY_TOKEN)?,
+            platform_pda,
+            remaining,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::accounts::testing::{TestAccount, key, with_accounts};
+    use super::*;
+
+    fn set_pause_flags(platform: &Platform) -> Result<u8, ProgramError> {
+        let accounts = [
+            TestAccount::signer(key(5)),
+            TestAccount::state(key(1), platform),
+        ];
+        with_accounts(&accounts, |infos| {
+            SetPauseFlags::try_from((infos, [PAUSE_ALL].as_slice()))?.process()?;
+            let platform_pda = ProgramAccount::<Platform>::try_from(&infos[1])?;
+            Ok(platform_pda.load()?.paused)
+        })
+    }
+
+    #[test]
+    fn pause_flags_need_multisig_approvals() {
+        let mut platform = Platform::new(key(5), 255);
+        assert_eq!(set_pause_flags(&platform), Ok(PAUSE_ALL));
+
+        platform.multisig_enabled = 1;
+        assert_eq!(
+            set_pause_flags(&platform),
+            Err(ProgramError::NotEnoughAccountKeys)
+        );
+    }
+
+    #[test]
+    fn rotation_needs_the_new_administrator_signature() {
+        let platform = Platform::new(key(5), 255);
+        let rotate = |new_administrator: TestAccount| {
+            let accounts = [
+                TestAccount::signer(key(5)),
+                TestAccount::state(key(1), &platform),
+                new_administrator,
+            ];
+            with_accounts(&accounts, |infos| {
+                RotateAdministrator::try_from(infos)?.process()?;
+                let platform_pda = ProgramAccount::<Platform>::try_from(&infos[1])?;
+                let platform = platform_pda.load()?;
+                Ok((platform.administrator, platform.seed_key))
+            })
+        };
+
+        assert_eq!(
+            rotate(TestAccount::new(key(6))),
+            Err(ProgramError::MissingRequiredSignature)
+        );
+        assert_eq!(rotate(TestAccount::signer(key(6))), Ok((key(6), key(5))));
+    }
+}
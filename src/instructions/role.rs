@@ -0,0 +1,236 @@
+use bytemuck::try_from_bytes;
+use pinocchio::ProgramResult;
+use pinocchio::account_info::AccountInfo;
+use pinocchio::instruction::{Seed, Signer};
+use pinocchio::program_error::ProgramError;
+use pinocchio::sysvars::Sysvar;
+use pinocchio::sysvars::rent::Rent;
+use pinocchio_log::log;
+use pinocchio_system::instructions::CreateAccount;
+
+use crate::cpi;
+use crate::error::UniPinoNftErr;
+use crate::state::platform::Platform;
+use crate::state::program_state::ProgramState;
+use crate::state::role::{ROLE_MINTER, ROLE_PROGRAM_MINTER, Role};
+
+use super::accounts::{Pda, ProgramAccount};
+use super::*;
+
+pub const ROLE_TOKEN: &[u8] = b"role";
+
+/// Check `authority` signed and is either the platform administrator or a holder of
+/// `role_id`. Role holders pass their role PDA at the front of `remaining`; the
+/// accounts left after it are returned. While the administrator multisig is enabled
+/// the administrator key alone is not enough and needs a role PDA as well. A
+/// `ROLE_PROGRAM_MINTER` grant lets the minter authority PDA of the granted program
+/// act as a minter.
+pub fn authorize<'a>(
+    authority: &AccountInfo,
+    platform_pda: &AccountInfo,
+    platform: &Platform,
+    role_id: u8,
+    remaining: &'a [AccountInfo],
+) -> Result<&'a [AccountInfo], ProgramError> {
+    if !authority.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if !platform.has_multisig() && platform.administrator == authority.key().as_ref() {
+        return Ok(remaining);
+    }
+
+    let [role_pda, rest @ ..] = remaining else {
+        return Err(UniPinoNftErr::MissingRole.into());
+    };
+
+    let role_pda = ProgramAccount::<Role>::try_from(role_pda)?;
+    let role = role_pda.load()?;
+    if role.platform != platform_pda.key().as_ref() {
+        return Err(UniPinoNftErr::MissingRole.into());
+    }
+
+    let is_granted = if role_id == ROLE_MINTER && role.role_id == ROLE_PROGRAM_MINTER {
+        cpi::minter_authority(platform_pda.key(), &role.grantee, role.authority_bump)?
+            == *authority.key()
+    } else {
+        role.role_id == role_id && role.grantee == authority.key().as_ref()
+    };
+    if !is_granted {
+        return Err(UniPinoNftErr::MissingRole.into());
+    }
+
+    Ok(rest)
+}
+
+pub struct GrantRole<'a> {
+    pub administrator: &'a AccountInfo,
+    pub platform_pda: ProgramAccount<'a, Platform>,
+    pub role_pda: Pda<'a>,
+    pub role_args: &'a RoleArgs,
+    pub remaining: &'a [AccountInfo],
+}
+
+impl<'a> GrantRole<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &9;
+
+    pub fn process(self) -> ProgramResult {
+        let mut platform = self.platform_pda.load_mut()?;
+
+        multisig::authorize_admin(
+            self.administrator,
+            &self.platform_pda,
+            &platform,
+            self.remaining,
+        )?;
+
+        if self.role_pda.is_initialized() {
+            return Err(UniPinoNftErr::ReInitPda.into());
+        }
+
+        let role_id = self.role_args.role_id;
+        let bump = self.role_pda.bump;
+
+        // a partner program signs with its minter authority PDA, whose bump is stored
+        let authority_bump = if role_id == ROLE_PROGRAM_MINTER {
+            cpi::find_minter_authority(self.platform_pda.key(), &self.role_args.grantee)
+                .ok_or(UniPinoNftErr::PdaErr)?
+                .1
+        } else {
+            0
+        };
+
+        let role_seeds = [
+            Seed::from(ROLE_TOKEN),
+            Seed::from(self.platform_pda.key().as_ref()),
+            Seed::from(core::slice::from_ref(&role_id)),
+            Seed::from(self.role_args.grantee.as_ref()),
+            Seed::from(core::slice::from_ref(&bump)),
+        ];
+        let role_signer = Signer::from(&role_seeds);
+
+        CreateAccount {
+            from: self.administrator,
+            to: &self.role_pda,
+            lamports: Rent::get()?.minimum_balance(Role::INIT_SPACE),
+            space: Role::INIT_SPACE as u64,
+            owner: &ID,
+        }
+        .invoke_signed(&[role_signer])?;
+
+        let role = Role::new(
+            *self.platform_pda.key(),
+            self.role_args.grantee,
+            role_id,
+            bump,
+            authority_bump,
+        );
+        role.init(&self.role_pda)?;
+        platform.role_count = platform
+            .role_count
+            .checked_add(1)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+
+        log!("granted role {} to {}", role_id, &self.role_args.grantee);
+        Ok(())
+    }
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &'a [u8])> for GrantRole<'a> {
+    type Error = ProgramError;
+
+    fn try_from(value: (&'a [AccountInfo], &'a [u8])) -> Result<Self, Self::Error> {
+        let (accounts, instruction_data) = value;
+
+        let [administrator, platform_pda, role_pda, _, remaining @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if instruction_data.len() != size_of::<RoleArgs>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let role_args = try_from_bytes::<RoleArgs>(instruction_data)
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+        if !Role::is_valid_id(role_args.role_id) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let platform_pda = ProgramAccount::try_from(platform_pda)?;
+        let role_pda = Pda::find(
+            role_pda,
+            &[
+                ROLE_TOKEN,
+                platform_pda.key().as_ref(),
+                core::slice::from_ref(&role_args.role_id),
+                role_args.grantee.as_ref(),
+            ],
+            &ID,
+            UniPinoNftErr::PdaMismatch,
+        )?;
+
+        Ok(Self {
+            administrator,
+            platform_pda,
+            role_pda,
+            role_args,
+            remaining,
+        })
+    }
+}
+
+pub struct RevokeRole<'a> {
+    pub administrator: &'a AccountInfo,
+    pub platform_pda: ProgramAccount<'a, Platform>,
+    pub role_pda: ProgramAccount<'a, Role>,
+    pub remaining: &'a [AccountInfo],
+}
+
+impl<'a> RevokeRole<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &10;
+
+    pub fn process(self) -> ProgramResult {
+        let mut platform = self.platform_pda.load_mut()?;
+
+        multisig::authorize_admin(
+            self.administrator,
+            &self.platform_pda,
+            &platform,
+            self.remaining,
+        )?;
+
+        {
+            let role = self.role_pda.load()?;
+            if role.platform != self.platform_pda.key().as_ref() {
+                return Err(UniPinoNftErr::PlatformMismatch.into());
+            }
+        }
+
+        accounts::close(&self.role_pda, self.administrator)?;
+        platform.role_count = platform
+            .role_count
+            .checked_sub(1)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+
+        log!("role revoked");
+        Ok(())
+    }
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for RevokeRole<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [administrator, platform_pda, role_pda, remaining @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        Ok(Self {
+            administrator,
+            platform_pda: ProgramAccount::try_from(platform_pda)?,
+            role_pda: ProgramAccount::try_from(role_pda)?,
+            remaining,
+        })
+    }
+}
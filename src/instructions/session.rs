@@ -0,0 +1,342 @@
+use bytemuck::try_from_bytes;
+use pinocchio::ProgramResult;
+use pinocchio::account_info::AccountInfo;
+use pinocchio::instruction::{Seed, Signer};
+use pinocchio::program_error::ProgramError;
+use pinocchio::pubkey::Pubkey;
+use pinocchio::sysvars::Sysvar;
+use pinocchio::sysvars::clock::Clock;
+use pinocchio::sysvars::rent::Rent;
+use pinocchio_log::log;
+use pinocchio_system::instructions::CreateAccount;
+
+use crate::error::UniPinoNftErr;
+use crate::state::platform::Platform;
+use crate::state::program_state::ProgramState;
+use crate::state::session_key::SessionKey;
+use crate::state::user::User;
+
+use super::accounts::{Pda, ProgramAccount, SignerAccount};
+use super::*;
+
+pub const SESSION_TOKEN: &[u8] = b"session";
+
+/// Check `authority` signed and is the administrator, the wallet linked to `user` or
+/// one of its session keys allowed to sign the instruction with `discriminator`.
+/// Session keys pass their `SessionKey` PDA at the front of `remaining`; the accounts
+/// left after it are returned.
+pub fn authorize<'a>(
+    authority: &AccountInfo,
+    platform: &Platform,
+    user_pda: &AccountInfo,
+    user: &User,
+    discriminator: u8,
+    remaining: &'a [AccountInfo],
+) -> Result<&'a [AccountInfo], ProgramError> {
+    if !authority.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if user::authorize_holder(authority, platform, user).is_ok() {
+        return Ok(remaining);
+    }
+
+    let [session_pda, rest @ ..] = remaining else {
+        return Err(UniPinoNftErr::NotUserHolder.into());
+    };
+
+    let session_pda = ProgramAccount::<SessionKey>::try_from(session_pda)?;
+    let session = session_pda.load()?;
+    if session.user != user_pda.key().as_ref()
+        || session.generation != user.generation
+        || session.key != authority.key().as_ref()
+    {
+        return Err(UniPinoNftErr::SessionMismatch.into());
+    }
+
+    if !session.allows(discriminator, Clock::get()?.slot) {
+        return Err(UniPinoNftErr::SessionNotAllowed.into());
+    }
+
+    Ok(rest)
+}
+
+/// Load the user PDA of `platform_pda` and check `authority` may manage its sessions
+fn authorize_owner(
+    authority: &AccountInfo,
+    platform_pda: &ProgramAccount<Platform>,
+    user_pda: &ProgramAccount<User>,
+) -> ProgramResult {
+    let platform = platform_pda.load()?;
+    let user = user_pda.load()?;
+    user_pda.check_platform(&user, platform_pda, &platform)?;
+
+    // session keys cannot register or revoke other session keys
+    user::authorize_holder(authority, &platform, &user)
+}
+
+pub struct CreateSessionKey<'a> {
+    pub authority: SignerAccount<'a>,
+    pub platform_pda: ProgramAccount<'a, Platform>,
+    pub user_pda: ProgramAccount<'a, User>,
+    pub session_pda: Pda<'a>,
+    pub session_args: &'a SessionKeyArgs,
+}
+
+impl<'a> CreateSessionKey<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &20;
+
+    pub fn process(self) -> ProgramResult {
+        authorize_owner(&self.authority, &self.platform_pda, &self.user_pda)?;
+
+        if self.session_pda.is_initialized() {
+            return Err(UniPinoNftErr::ReInitPda.into());
+        }
+        let bump = self.session_pda.bump;
+
+        let session_seeds = [
+            Seed::from(SESSION_TOKEN),
+            Seed::from(self.user_pda.key().as_ref()),
+            Seed::from(self.session_args.key.as_ref()),
+            Seed::from(core::slice::from_ref(&bump)),
+        ];
+        let session_signer = Signer::from(&session_seeds);
+
+        CreateAccount {
+            from: &self.authority,
+            to: &self.session_pda,
+            lamports: Rent::get()?.minimum_balance(SessionKey::INIT_SPACE),
+            space: SessionKey::INIT_SPACE as u64,
+            owner: &ID,
+        }
+        .invoke_signed(&[session_signer])?;
+
+        let mut user = self.user_pda.load_mut()?;
+        let session = SessionKey::new(
+            *self.user_pda.key(),
+            self.session_args.key,
+            self.session_args.allowed_instructions,
+            self.session_args.expiry_slot,
+            bump,
+            user.generation,
+        );
+        session.init(&self.session_pda)?;
+
+        user.session_count = user
+            .session_count
+            .checked_add(1)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+
+        let expiry_slot = self.session_args.expiry_slot;
+        log!("session key created, expires at slot {}", expiry_slot);
+        Ok(())
+    }
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &'a [u8])> for CreateSessionKey<'a> {
+    type Error = ProgramError;
+
+    fn try_from(value: (&'a [AccountInfo], &'a [u8])) -> Result<Self, Self::Error> {
+        let (accounts, instruction_data) = value;
+
+        let [authority, platform_pda, user_pda, session_pda, _] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if instruction_data.len() != size_of::<SessionKeyArgs>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let session_args = try_from_bytes::<SessionKeyArgs>(instruction_data)
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+        if session_args.key == Pubkey::default() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let user_pda = ProgramAccount::try_from(user_pda)?;
+        let session_pda = Pda::find(
+            session_pda,
+            &[
+                SESSION_TOKEN,
+                user_pda.key().as_ref(),
+                session_args.key.as_ref(),
+            ],
+            &ID,
+            UniPinoNftErr::PdaMismatch,
+        )?;
+
+        Ok(Self {
+            authority: SignerAccount::try_from(authority)?,
+            platform_pda: ProgramAccount::try_from(platform_pda)?,
+            user_pda,
+            session_pda,
+            session_args,
+        })
+    }
+}
+
+pub struct RevokeSessionKey<'a> {
+    pub authority: SignerAccount<'a>,
+    pub platform_pda: ProgramAccount<'a, Platform>,
+    pub user_pda: ProgramAccount<'a, User>,
+    pub session_pda: ProgramAccount<'a, SessionKey>,
+}
+
+impl<'a> RevokeSessionKey<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &21;
+
+    pub fn process(self) -> ProgramResult {
+        authorize_owner(&self.authority, &self.platform_pda, &self.user_pda)?;
+
+        let generation = {
+            let session = self.session_pda.load()?;
+            if session.user != self.user_pda.key().as_ref() {
+                return Err(UniPinoNftErr::SessionMismatch.into());
+            }
+            session.generation
+        };
+
+        // Close session account by transferring lamports to authority
+        let session_lamports = self.session_pda.lamports();
+        *self.session_pda.try_borrow_mut_lamports()? = 0;
+        let mut authority_lamports = self.authority.try_borrow_mut_lamports()?;
+        *authority_lamports = authority_lamports
+            .checked_add(session_lamports)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+
+        // Zero out the session account data
+        self.session_pda.try_borrow_mut_data()?.fill(0);
+
+        // keys of an earlier user at the same PDA are not in the count
+        let mut user = self.user_pda.load_mut()?;
+        if generation == user.generation {
+            user.session_count = user
+                .session_count
+                .checked_sub(1)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+        }
+
+        log!("session key revoked");
+        Ok(())
+    }
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for RevokeSessionKey<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [authority, platform_pda, user_pda, session_pda] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        Ok(Self {
+            authority: SignerAccount::try_from(authority)?,
+            platform_pda: ProgramAccount::try_from(platform_pda)?,
+            user_pda: ProgramAccount::try_from(user_pda)?,
+            session_pda: ProgramAccount::try_from(session_pda)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::accounts::testing::{TestAccount, key, with_accounts};
+    use super::*;
+
+    const USER: Pubkey = [1; 32];
+    const SESSION: Pubkey = [2; 32];
+
+    fn user() -> User {
+        let mut user = User::new(key(3), 42, 255, 7);
+        user.authority = key(4);
+        user
+    }
+
+    fn authorize_with(authority: TestAccount, session: &SessionKey) -> Result<usize, ProgramError> {
+        let platform = Platform::new(key(5), 255);
+        let accounts = [
+            authority,
+            TestAccount::new(USER),
+            TestAccount::state(SESSION, session),
+        ];
+        with_accounts(&accounts, |infos| {
+            authorize(&infos[0], &platform, &infos[1], &user(), 9, &infos[2..]).map(<[_]>::len)
+        })
+    }
+
+    #[test]
+    fn holders_sign_without_a_session_key() {
+        let session = SessionKey::zeroed();
+        // the linked wallet and the administrator leave the session account unused
+        assert_eq!(authorize_with(TestAccount::signer(key(4)), &session), Ok(1));
+        assert_eq!(authorize_with(TestAccount::signer(key(5)), &session), Ok(1));
+        assert_eq!(
+            authorize_with(TestAccount::new(key(4)), &session),
+            Err(ProgramError::MissingRequiredSignature)
+        );
+    }
+
+    #[test]
+    fn session_key_has_to_belong_to_the_user_generation() {
+        let session = SessionKey::new(USER, key(6), u64::MAX, u64::MAX, 255, 7);
+
+        for session in [
+            SessionKey {
+                user: key(8),
+                ..session
+            },
+            SessionKey {
+                key: key(8),
+                ..session
+            },
+            // a key left behind by a closed user at the same PDA
+            SessionKey {
+                generation: 6,
+                ..session
+            },
+        ] {
+            assert_eq!(
+                authorize_with(TestAccount::signer(key(6)), &session),
+                Err(UniPinoNftErr::SessionMismatch.into())
+            );
+        }
+    }
+
+    #[test]
+    fn unlinking_the_wallet_retires_its_session_keys() {
+        let session = SessionKey::new(USER, key(6), u64::MAX, u64::MAX, 255, 7);
+        let mut user = user();
+        user.session_count = 1;
+        user.unlink_wallet(8);
+        assert_eq!({ user.session_count }, 0);
+
+        let platform = Platform::new(key(5), 255);
+        let accounts = [
+            TestAccount::signer(key(6)),
+            TestAccount::new(USER),
+            TestAccount::state(SESSION, &session),
+        ];
+        let result = with_accounts(&accounts, |infos| {
+            authorize(&infos[0], &platform, &infos[1], &user, 9, &infos[2..]).map(<[_]>::len)
+        });
+        assert_eq!(result, Err(UniPinoNftErr::SessionMismatch.into()));
+
+        // nor does the unlinked wallet act for the user any more
+        let accounts = [TestAccount::signer(key(4)), TestAccount::new(USER)];
+        let result = with_accounts(&accounts, |infos| {
+            authorize(&infos[0], &platform, &infos[1], &user, 9, &infos[2..]).map(<[_]>::len)
+        });
+        assert_eq!(result, Err(UniPinoNftErr::NotUserHolder.into()));
+    }
+
+    #[test]
+    fn other_signers_need_a_session_key() {
+        let platform = Platform::new(key(5), 255);
+        let accounts = [TestAccount::signer(key(6)), TestAccount::new(USER)];
+        let result = with_accounts(&accounts, |infos| {
+            authorize(&infos[0], &platform, &infos[1], &user(), 9, &infos[2..]).map(<[_]>::len)
+        });
+        assert_eq!(result, Err(UniPinoNftErr::NotUserHolder.into()));
+    }
+}
@@ -0,0 +1,660 @@
+use bytemuck::try_from_bytes;
+use pinocchio::ProgramResult;
+use pinocchio::account_info::AccountInfo;
+use pinocchio::instruction::{Seed, Signer};
+use pinocchio::program_error::ProgramError;
+use pinocchio::sysvars::Sysvar;
+use pinocchio::sysvars::clock::Clock;
+use pinocchio::sysvars::rent::Rent;
+use pinocchio_log::log;
+use pinocchio_system::instructions::CreateAccount;
+
+use crate::error::UniPinoNftErr;
+use crate::state::collection::Collection;
+use crate::state::pending_collection::{
+    COLLECTION_CHANGE_ALLOWLIST, COLLECTION_CHANGE_CONFIG, PendingCollectionChange,
+};
+use crate::state::pending_config::PendingConfig;
+use crate::state::platform::Platform;
+use crate::state::program_state::ProgramState;
+use crate::state::role::ROLE_FEE_MANAGER;
+
+use super::accounts::{Pda, ProgramAccount, SignerAccount};
+use super::*;
+
+pub const CONFIG_CHANGE_TOKEN: &[u8] = b"config_change";
+pub const COLLECTION_CHANGE_TOKEN: &[u8] = b"collection_change";
+
+/// Load the pending change of `platform_pda`
+fn load_pending(
+    pending_pda: &ProgramAccount<PendingConfig>,
+    platform_pda: &AccountInfo,
+) -> Result<PendingConfig, ProgramError> {
+    let pending = *pending_pda.load()?;
+    if pending.platform != platform_pda.key().as_ref() {
+        return Err(UniPinoNftErr::PlatformMismatch.into());
+    }
+
+    Ok(pending)
+}
+
+pub struct QueueConfigChange<'a> {
+    pub administrator: &'a AccountInfo,
+    pub platform_pda: ProgramAccount<'a, Platform>,
+    pub pending_pda: Pda<'a>,
+    pub update_args: &'a UpdatePlatformArgs,
+    pub remaining: &'a [AccountInfo],
+}
+
+impl<'a> QueueConfigChange<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &12;
+
+    pub fn process(self) -> ProgramResult {
+        let platform = self.platform_pda.load()?;
+
+        multisig::authorize_admin(
+            self.administrator,
+            &self.platform_pda,
+            &platform,
+            self.remaining,
+        )?;
+
+        // only one change can be pending, a queued one has to execute or be cancelled first
+        if self.pending_pda.is_initialized() {
+            return Err(UniPinoNftErr::ReInitPda.into());
+        }
+        let bump = self.pending_pda.bump;
+
+        let eta = Clock::get()?
+            .unix_timestamp
+            .checked_add(platform.config_delay)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+
+        let pending_seeds = [
+            Seed::from(CONFIG_CHANGE_TOKEN),
+            Seed::from(self.platform_pda.key().as_ref()),
+            Seed::from(core::slice::from_ref(&bump)),
+        ];
+        let pending_signer = Signer::from(&pending_seeds);
+
+        CreateAccount {
+            from: self.administrator,
+            to: &self.pending_pda,
+            lamports: Rent::get()?.minimum_balance(PendingConfig::INIT_SPACE),
+            space: PendingConfig::INIT_SPACE as u64,
+            owner: &ID,
+        }
+        .invoke_signed(&[pending_signer])?;
+
+        let pending = PendingConfig::new(*self.platform_pda.key(), eta, bump, *self.update_args);
+        pending.init(&self.pending_pda)?;
+
+        log!("config change queued, executable at {}", eta);
+        Ok(())
+    }
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &'a [u8])> for QueueConfigChange<'a> {
+    type Error = ProgramError;
+
+    fn try_from(value: (&'a [AccountInfo], &'a [u8])) -> Result<Self, Self::Error> {
+        let (accounts, instruction_data) = value;
+
+        let [administrator, platform_pda, pending_pda, _, remaining @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if instruction_data.len() != size_of::<UpdatePlatformArgs>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let update_args = try_from_bytes::<UpdatePlatformArgs>(instruction_data)
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+        let platform_pda = ProgramAccount::try_from(platform_pda)?;
+        let pending_pda = Pda::find(
+            pending_pda,
+            &[CONFIG_CHANGE_TOKEN, platform_pda.key().as_ref()],
+            &ID,
+            UniPinoNftErr::PdaMismatch,
+        )?;
+
+        Ok(Self {
+            administrator,
+            platform_pda,
+            pending_pda,
+            update_args,
+            remaining,
+        })
+    }
+}
+
+pub struct ExecuteConfigChange<'a> {
+    pub administrator: SignerAccount<'a>,
+    pub platform_pda: ProgramAccount<'a, Platform>,
+    pub pending_pda: ProgramAccount<'a, PendingConfig>,
+    pub remaining: &'a [AccountInfo],
+}
+
+impl<'a> ExecuteConfigChange<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &13;
+
+    pub fn process(self) -> ProgramResult {
+        let mut platform = self.platform_pda.load_mut()?;
+
+        // approvals were collected when the change was queued
+        platform.ensure_administrator(self.administrator.key())?;
+
+        let pending = load_pending(&self.pending_pda, &self.platform_pda)?;
+        if Clock::get()?.unix_timestamp < pending.eta {
+            return Err(UniPinoNftErr::TimelockNotElapsed.into());
+        }
+
+        let update_args = pending.args;
+        platform::UpdatePlatformConfig {
+            administrator: self.administrator.info(),
+            platform_pda: self.platform_pda,
+            update_args,
+            remaining: self.remaining,
+        }
+        .apply(&mut platform, self.remaining)?;
+
+        accounts::close(&self.pending_pda, &self.administrator)?;
+
+        log!("queued config change executed");
+        Ok(())
+    }
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for ExecuteConfigChange<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [administrator, platform_pda, pending_pda, _, remaining @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        Ok(Self {
+            administrator: SignerAccount::try_from(administrator)?,
+            platform_pda: ProgramAccount::try_from(platform_pda)?,
+            pending_pda: ProgramAccount::try_from(pending_pda)?,
+            remaining,
+        })
+    }
+}
+
+pub struct CancelConfigChange<'a> {
+    pub administrator: &'a AccountInfo,
+    pub platform_pda: ProgramAccount<'a, Platform>,
+    pub pending_pda: ProgramAccount<'a, PendingConfig>,
+    pub remaining: &'a [AccountInfo],
+}
+
+impl<'a> CancelConfigChange<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &14;
+
+    pub fn process(self) -> ProgramResult {
+        multisig::authorize_admin(
+            self.administrator,
+            &self.platform_pda,
+            &*self.platform_pda.load()?,
+            self.remaining,
+        )?;
+
+        load_pending(&self.pending_pda, &self.platform_pda)?;
+        accounts::close(&self.pending_pda, self.administrator)?;
+
+        log!("queued config change cancelled");
+        Ok(())
+    }
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for CancelConfigChange<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [administrator, platform_pda, pending_pda, remaining @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        Ok(Self {
+            administrator,
+            platform_pda: ProgramAccount::try_from(platform_pda)?,
+            pending_pda: ProgramAccount::try_from(pending_pda)?,
+            remaining,
+        })
+    }
+}
+
+/// Load the pending collection change of `collection_pda` and check `payer` is the
+/// account its rent goes back to
+fn load_pending_collection(
+    pending_pda: &ProgramAccount<PendingCollectionChange>,
+    platform_pda: &AccountInfo,
+    collection_pda: &AccountInfo,
+    payer: &AccountInfo,
+) -> Result<PendingCollectionChange, ProgramError> {
+    let pending = *pending_pda.load()?;
+    if pending.platform != platform_pda.key().as_ref() {
+        return Err(UniPinoNftErr::PlatformMismatch.into());
+    }
+
+    if pending.collection != collection_pda.key().as_ref() {
+        return Err(UniPinoNftErr::CollectionMismatch.into());
+    }
+
+    if pending.payer != payer.key().as_ref() {
+        return Err(UniPinoNftErr::PayerMismatch.into());
+    }
+
+    Ok(pending)
+}
+
+/// Create the pending change PDA of a collection holding `pending`, paid by its payer,
+/// and count it on the platform
+fn queue_collection_change(
+    platform: &mut Platform,
+    payer: &AccountInfo,
+    collection_pda: &AccountInfo,
+    pending_pda: &Pda,
+    pending: &PendingCollectionChange,
+) -> ProgramResult {
+    // only one change per collection can be pending, it has to execute or be cancelled first
+    if pending_pda.is_initialized() {
+        return Err(UniPinoNftErr::ReInitPda.into());
+    }
+    let bump = pending_pda.bump;
+
+    let pending_seeds = [
+        Seed::from(COLLECTION_CHANGE_TOKEN),
+        Seed::from(collection_pda.key().as_ref()),
+        Seed::from(core::slice::from_ref(&bump)),
+    ];
+    let pending_signer = Signer::from(&pending_seeds);
+
+    CreateAccount {
+        from: payer,
+        to: pending_pda,
+        lamports: Rent::get()?.minimum_balance(PendingCollectionChange::INIT_SPACE),
+        space: PendingCollectionChange::INIT_SPACE as u64,
+        owner: &ID,
+    }
+    .invoke_signed(&[pending_signer])?;
+
+    pending.init(pending_pda)?;
+    platform.collection_change_count = platform
+        .collection_change_count
+        .checked_add(1)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    log!("collection change queued, executable at {}", pending.eta);
+    Ok(())
+}
+
+/// Pending collection change PDA of `collection_pda`
+fn find_pending_collection<'a>(
+    pending_pda: &'a AccountInfo,
+    collection_pda: &AccountInfo,
+) -> Result<Pda<'a>, ProgramError> {
+    Pda::find(
+        pending_pda,
+        &[COLLECTION_CHANGE_TOKEN, collection_pda.key().as_ref()],
+        &ID,
+        UniPinoNftErr::PdaMismatch,
+    )
+}
+
+/// Earliest time a change queued now can execute at
+fn eta(platform: &Platform) -> Result<i64, ProgramError> {
+    Clock::get()?
+        .unix_timestamp
+        .checked_add(platform.config_delay)
+        .ok_or(ProgramError::ArithmeticOverflow)
+}
+
+pub struct QueueCollectionConfig<'a> {
+    pub authority: SignerAccount<'a>,
+    pub platform_pda: ProgramAccount<'a, Platform>,
+    pub collection_pda: Pda<'a>,
+    pub pending_pda: Pda<'a>,
+    pub config_args: &'a CollectionConfigArgs,
+    pub remaining: &'a [AccountInfo],
+}
+
+impl<'a> QueueCollectionConfig<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &26;
+
+    pub fn process(self) -> ProgramResult {
+        let mut platform = self.platform_pda.load_mut()?;
+
+        role::authorize(
+            &self.authority,
+            &self.platform_pda,
+            &platform,
+            ROLE_FEE_MANAGER,
+            self.remaining,
+        )?;
+
+        let mut pending = PendingCollectionChange::new(
+            *self.platform_pda.key(),
+            *self.collection_pda.key(),
+            *self.authority.key(),
+            eta(&platform)?,
+            self.pending_pda.bump,
+        );
+        pending.kind = COLLECTION_CHANGE_CONFIG;
+        pending.config = *self.config_args;
+
+        queue_collection_change(
+            &mut platform,
+            &self.authority,
+            &self.collection_pda,
+            &self.pending_pda,
+            &pending,
+        )
+    }
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &'a [u8])> for QueueCollectionConfig<'a> {
+    type Error = ProgramError;
+
+    fn try_from(value: (&'a [AccountInfo], &'a [u8])) -> Result<Self, Self::Error> {
+        let (accounts, instruction_data) = value;
+
+        let [
+            authority,
+            platform_pda,
+            collection_pda,
+            pending_pda,
+            _,
+            remaining @ ..,
+        ] = accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if instruction_data.len() != size_of::<CollectionConfigArgs>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let config_args = try_from_bytes::<CollectionConfigArgs>(instruction_data)
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+        collection::validate_config_args(config_args)?;
+
+        // the collection PDA may not exist yet, executing the change creates it
+        let platform_pda = ProgramAccount::try_from(platform_pda)?;
+        let collection_pda =
+            collection::find_collection(collection_pda, &platform_pda, &config_args.name)?;
+        let pending_pda = find_pending_collection(pending_pda, &collection_pda)?;
+
+        Ok(Self {
+            authority: SignerAccount::try_from(authority)?,
+            platform_pda,
+            collection_pda,
+            pending_pda,
+            config_args,
+            remaining,
+        })
+    }
+}
+
+pub struct QueueCollectionAllowlist<'a> {
+    pub administrator: &'a AccountInfo,
+    pub platform_pda: ProgramAccount<'a, Platform>,
+    pub collection_pda: ProgramAccount<'a, Collection>,
+    pub pending_pda: Pda<'a>,
+    pub allowlist_args: &'a CollectionAllowlistArgs,
+    pub remaining: &'a [AccountInfo],
+}
+
+impl<'a> QueueCollectionAllowlist<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &27;
+
+    pub fn process(self) -> ProgramResult {
+        let mut platform = self.platform_pda.load_mut()?;
+
+        multisig::authorize_admin(
+            self.administrator,
+            &self.platform_pda,
+            &platform,
+            self.remaining,
+        )?;
+
+        let collection = collection::load_collection(
+            &self.collection_pda,
+            &self.platform_pda,
+            &self.allowlist_args.name,
+        )?;
+        // checked again on execution, failing early saves queuing a change that cannot apply
+        if self.allowlist_args.phase < collection.phase {
+            return Err(UniPinoNftErr::PhaseNotIncreasing.into());
+        }
+
+        let mut pending = PendingCollectionChange::new(
+            *self.platform_pda.key(),
+            *self.collection_pda.key(),
+            *self.administrator.key(),
+            eta(&platform)?,
+            self.pending_pda.bump,
+        );
+        pending.kind = COLLECTION_CHANGE_ALLOWLIST;
+        pending.allowlist = *self.allowlist_args;
+
+        queue_collection_change(
+            &mut platform,
+            self.administrator,
+            &self.collection_pda,
+            &self.pending_pda,
+            &pending,
+        )
+    }
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &'a [u8])> for QueueCollectionAllowlist<'a> {
+    type Error = ProgramError;
+
+    fn try_from(value: (&'a [AccountInfo], &'a [u8])) -> Result<Self, Self::Error> {
+        let (accounts, instruction_data) = value;
+
+        let [
+            administrator,
+            platform_pda,
+            collection_pda,
+            pending_pda,
+            _,
+            remaining @ ..,
+        ] = accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if instruction_data.len() != size_of::<CollectionAllowlistArgs>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let allowlist_args = try_from_bytes::<CollectionAllowlistArgs>(instruction_data)
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+        collection::validate_allowlist_args(allowlist_args)?;
+
+        let collection_pda = ProgramAccount::try_from(collection_pda)?;
+        let pending_pda = find_pending_collection(pending_pda, &collection_pda)?;
+
+        Ok(Self {
+            administrator,
+            platform_pda: ProgramAccount::try_from(platform_pda)?,
+            collection_pda,
+            pending_pda,
+            allowlist_args,
+            remaining,
+        })
+    }
+}
+
+pub struct ExecuteCollectionChange<'a> {
+    pub authority: SignerAccount<'a>,
+    pub platform_pda: ProgramAccount<'a, Platform>,
+    pub collection_pda: &'a AccountInfo,
+    pub pending_pda: ProgramAccount<'a, PendingCollectionChange>,
+    pub payer: &'a AccountInfo,
+    pub remaining: &'a [AccountInfo],
+}
+
+impl<'a> ExecuteCollectionChange<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &28;
+
+    pub fn process(self) -> ProgramResult {
+        let mut platform = self.platform_pda.load_mut()?;
+
+        let pending = load_pending_collection(
+            &self.pending_pda,
+            &self.platform_pda,
+            self.collection_pda,
+            self.payer,
+        )?;
+        if Clock::get()?.unix_timestamp < pending.eta {
+            return Err(UniPinoNftErr::TimelockNotElapsed.into());
+        }
+
+        // approvals were collected when the change was queued
+        match pending.kind {
+            COLLECTION_CHANGE_CONFIG => {
+                role::authorize(
+                    &self.authority,
+                    &self.platform_pda,
+                    &platform,
+                    ROLE_FEE_MANAGER,
+                    self.remaining,
+                )?;
+
+                let collection_pda = collection::find_collection(
+                    self.collection_pda,
+                    &self.platform_pda,
+                    &pending.config.name,
+                )?;
+                collection::apply_config(
+                    &self.authority,
+                    &self.platform_pda,
+                    &mut platform,
+                    &collection_pda,
+                    &pending.config,
+                )?;
+            }
+            COLLECTION_CHANGE_ALLOWLIST => {
+                platform.ensure_administrator(self.authority.key())?;
+
+                let collection_pda = ProgramAccount::<Collection>::try_from(self.collection_pda)?;
+                collection::load_collection(
+                    &collection_pda,
+                    &self.platform_pda,
+                    &pending.allowlist.name,
+                )?;
+                let mut collection = collection_pda.load_mut()?;
+                collection::apply_allowlist(&mut collection, &pending.allowlist)?;
+            }
+            _ => return Err(ProgramError::InvalidAccountData),
+        }
+
+        accounts::close(&self.pending_pda, self.payer)?;
+        platform.collection_change_count = platform
+            .collection_change_count
+            .checked_sub(1)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+
+        log!("queued collection change executed");
+        Ok(())
+    }
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for ExecuteCollectionChange<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [
+            authority,
+            platform_pda,
+            collection_pda,
+            pending_pda,
+            payer,
+            _,
+            remaining @ ..,
+        ] = accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        Ok(Self {
+            authority: SignerAccount::try_from(authority)?,
+            platform_pda: ProgramAccount::try_from(platform_pda)?,
+            collection_pda,
+            pending_pda: ProgramAccount::try_from(pending_pda)?,
+            payer,
+            remaining,
+        })
+    }
+}
+
+pub struct CancelCollectionChange<'a> {
+    pub administrator: &'a AccountInfo,
+    pub platform_pda: ProgramAccount<'a, Platform>,
+    pub collection_pda: &'a AccountInfo,
+    pub pending_pda: ProgramAccount<'a, PendingCollectionChange>,
+    pub payer: &'a AccountInfo,
+    pub remaining: &'a [AccountInfo],
+}
+
+impl<'a> CancelCollectionChange<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &29;
+
+    pub fn process(self) -> ProgramResult {
+        let mut platform = self.platform_pda.load_mut()?;
+
+        multisig::authorize_admin(
+            self.administrator,
+            &self.platform_pda,
+            &platform,
+            self.remaining,
+        )?;
+
+        load_pending_collection(
+            &self.pending_pda,
+            &self.platform_pda,
+            self.collection_pda,
+            self.payer,
+        )?;
+        accounts::close(&self.pending_pda, self.payer)?;
+        platform.collection_change_count = platform
+            .collection_change_count
+            .checked_sub(1)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+
+        log!("queued collection change cancelled");
+        Ok(())
+    }
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for CancelCollectionChange<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [
+            administrator,
+            platform_pda,
+            collection_pda,
+            pending_pda,
+            payer,
+            remaining @ ..,
+        ] = accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        Ok(Self {
+            administrator,
+            platform_pda: ProgramAccount::try_from(platform_pda)?,
+            collection_pda,
+            pending_pda: ProgramAccount::try_from(pending_pda)?,
+            payer,
+            remaining,
+        })
+    }
+}
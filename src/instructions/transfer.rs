@@ -0,0 +1,160 @@
+use pinocchio::ProgramResult;
+use pinocchio::account_info::AccountInfo;
+use pinocchio::instruction::Signer;
+use pinocchio::program_error::ProgramError;
+use pinocchio::pubkey::Pubkey;
+use pinocchio_log::log;
+use pinocchio_token_2022::ID as TOKEN_2022_ID;
+use pinocchio_token_2022::instructions::TransferChecked;
+
+use crate::events::{Event, NftTransferred};
+use crate::state::nft_meta::NftMeta;
+use crate::state::platform::{PAUSE_TRANSFER, Platform};
+use crate::state::user::User;
+
+use super::accounts::{Pda, ProgramAccount, SignerAccount, UserSeeds};
+use super::*;
+
+pub struct TransferNftInternal<'a> {
+    pub authority: SignerAccount<'a>,
+    pub platform_pda: ProgramAccount<'a, Platform>,
+    pub from_user_pda: ProgramAccount<'a, User>,
+    pub to_user_pda: ProgramAccount<'a, User>,
+    pub mint_pda: &'a AccountInfo,
+    pub metadata_pda: ProgramAccount<'a, NftMeta>,
+    pub from_token_account: &'a AccountInfo,
+    pub to_token_account: &'a AccountInfo,
+    pub token_program: &'a AccountInfo,
+    pub remaining: &'a [AccountInfo],
+}
+
+impl<'a> TransferNftInternal<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &30;
+
+    pub fn process(self) -> ProgramResult {
+        let platform = self.platform_pda.load()?;
+        platform.ensure_not_paused(PAUSE_TRANSFER)?;
+
+        let from_user = self.from_user_pda.load()?;
+        self.from_user_pda
+            .check_platform(&from_user, &self.platform_pda, &platform)?;
+        let to_user = self.to_user_pda.load()?;
+        self.to_user_pda
+            .check_platform(&to_user, &self.platform_pda, &platform)?;
+
+        session::authorize(
+            &self.authority,
+            &platform,
+            &self.from_user_pda,
+            &from_user,
+            *Self::DISCRIMINATOR,
+            self.remaining,
+        )?;
+
+        if self.token_program.key() != &TOKEN_2022_ID {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        // both holder token accounts are owned by user PDAs, the NFT never leaves the platform
+        fee::validate_token_account(
+            self.from_token_account,
+            self.mint_pda,
+            self.token_program,
+            self.from_user_pda.key(),
+        )?;
+        fee::validate_token_account(
+            self.to_token_account,
+            self.mint_pda,
+            self.token_program,
+            self.to_user_pda.key(),
+        )?;
+
+        // the sender has to hold the NFT, its metadata was checked against the mint when
+        // parsed
+        self.from_user_pda
+            .check_holder(&from_user, self.mint_pda, &self.metadata_pda)?;
+
+        let user_seeds = UserSeeds::new(&from_user, &self.platform_pda, &platform);
+        drop(from_user);
+        drop(to_user);
+
+        TransferChecked {
+            from: self.from_token_account,
+            mint: self.mint_pda,
+            to: self.to_token_account,
+            authority: &self.from_user_pda,
+            amount: 1,
+            decimals: 0,
+            token_program: &TOKEN_2022_ID,
+        }
+        .invoke_signed(&[Signer::from(&user_seeds.signer_seeds())])?;
+
+        // `nft_count` counts the NFTs a user holds, so it moves with the NFT
+        let mut from_user = self.from_user_pda.load_mut()?;
+        from_user.nft_count = from_user
+            .nft_count
+            .checked_sub(1)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        drop(from_user);
+        let mut to_user = self.to_user_pda.load_mut()?;
+        to_user.nft_count = to_user
+            .nft_count
+            .checked_add(1)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        drop(to_user);
+
+        // a delegate approved by the previous holder cannot move the NFT any more; legacy
+        // metadata has no holder or delegate slot and has to be migrated first
+        let mut metadata = self.metadata_pda.load_mut()?;
+        metadata.holder = *self.to_user_pda.key();
+        metadata.delegate = Pubkey::default();
+        drop(metadata);
+
+        NftTransferred {
+            platform: *self.platform_pda.key(),
+            from: *self.from_user_pda.key(),
+            to: *self.to_user_pda.key(),
+            mint: *self.mint_pda.key(),
+        }
+        .emit();
+
+        log!("nft transferred to {}", self.to_user_pda.key());
+        Ok(())
+    }
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for TransferNftInternal<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [
+            authority,
+            platform_pda,
+            from_user_pda,
+            to_user_pda,
+            mint_pda,
+            metadata_pda,
+            from_token_account,
+            to_token_account,
+            token_program,
+            remaining @ ..,
+        ] = accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        Ok(Self {
+            authority: SignerAccount::try_from(authority)?,
+            platform_pda: ProgramAccount::try_from(platform_pda)?,
+            from_user_pda: ProgramAccount::try_from(from_user_pda)?,
+            to_user_pda: ProgramAccount::try_from(to_user_pda)?,
+            mint_pda,
+            metadata_pda: ProgramAccount::try_from(
+                Pda::nft_metadata(metadata_pda, mint_pda)?.info(),
+            )?,
+            from_token_account,
+            to_token_account,
+            token_program,
+            remaining,
+        })
+    }
+}
@@ -0,0 +1,185 @@
+use bytemuck::try_from_bytes;
+use pinocchio::ProgramResult;
+use pinocchio::account_info::AccountInfo;
+use pinocchio::instruction::{Seed, Signer};
+use pinocchio::program_error::ProgramError;
+use pinocchio::pubkey::{Pubkey, create_program_address};
+use pinocchio::sysvars::Sysvar;
+use pinocchio::sysvars::rent::Rent;
+use pinocchio_log::log;
+use pinocchio_token_2022::instructions::TransferChecked;
+
+use crate::error::UniPinoNftErr;
+use crate::state::platform::Platform;
+
+use super::accounts::ProgramAccount;
+use super::*;
+
+pub const TREASURY_TOKEN: &[u8] = b"treasury";
+
+/// Address of the platform treasury PDA for a known bump
+pub fn treasury_address(platform_pda: &Pubkey, bump: u8) -> Result<Pubkey, ProgramError> {
+    create_program_address(
+        &[
+            TREASURY_TOKEN,
+            platform_pda.as_ref(),
+            core::slice::from_ref(&bump),
+        ],
+        &ID,
+    )
+}
+
+pub struct WithdrawTreasury<'a> {
+    pub administrator: &'a AccountInfo,
+    pub platform_pda: ProgramAccount<'a, Platform>,
+    pub treasury_pda: &'a AccountInfo,
+    pub destination: &'a AccountInfo,
+    pub withdraw_args: &'a WithdrawTreasuryArgs,
+    pub remaining: &'a [AccountInfo],
+}
+
+impl<'a> WithdrawTreasury<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &6;
+
+    pub fn process(self) -> ProgramResult {
+        if !self.treasury_pda.is_owned_by(&ID) {
+            return Err(UniPinoNftErr::UninitPda.into());
+        }
+
+        let mut platform = self.platform_pda.load_mut()?;
+
+        let remaining = multisig::authorize_admin(
+            self.administrator,
+            &self.platform_pda,
+            &platform,
+            self.remaining,
+        )?;
+
+        // the treasury stays withdrawable after being disabled, so only its address is checked
+        let treasury = treasury_address(self.platform_pda.key(), platform.treasury_bump)?;
+        if treasury != self.treasury_pda.key().as_ref() {
+            return Err(UniPinoNftErr::PdaMismatch.into());
+        }
+
+        // zero leaves withdrawals uncapped
+        let amount = self.withdraw_args.amount;
+        if platform.treasury_max_withdrawal != 0 && amount > platform.treasury_max_withdrawal {
+            return Err(UniPinoNftErr::MaxWithdrawalExceeded.into());
+        }
+
+        if remaining.is_empty() {
+            self.withdraw_lamports(amount)?;
+            platform.treasury_lamports_withdrawn = platform
+                .treasury_lamports_withdrawn
+                .checked_add(amount)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+        } else {
+            self.withdraw_tokens(&platform, amount, remaining)?;
+            platform.treasury_tokens_withdrawn = platform
+                .treasury_tokens_withdrawn
+                .checked_add(amount)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+        }
+
+        log!("withdrew {} from treasury", amount);
+        Ok(())
+    }
+
+    fn withdraw_lamports(&self, amount: u64) -> ProgramResult {
+        // keep the treasury rent exempt so it can keep receiving fees
+        let available = self
+            .treasury_pda
+            .lamports()
+            .saturating_sub(Rent::get()?.minimum_balance(self.treasury_pda.data_len()));
+        if amount > available {
+            return Err(ProgramError::InsufficientFunds);
+        }
+
+        *self.treasury_pda.try_borrow_mut_lamports()? -= amount;
+        let mut destination_lamports = self.destination.try_borrow_mut_lamports()?;
+        *destination_lamports = destination_lamports
+            .checked_add(amount)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+
+        Ok(())
+    }
+
+    /// Transfer `amount` tokens of the platform fee mint out of the treasury
+    fn withdraw_tokens(
+        &self,
+        platform: &Platform,
+        amount: u64,
+        remaining: &[AccountInfo],
+    ) -> ProgramResult {
+        let [mint, treasury_token_account, token_program, ..] = remaining else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // the token counters are in units of the fee mint only
+        if platform.fee_mint != mint.key().as_ref() {
+            return Err(UniPinoNftErr::WrongFeeMint.into());
+        }
+
+        let decimals = fee::mint_decimals(mint, token_program)?;
+        fee::validate_token_account(
+            treasury_token_account,
+            mint,
+            token_program,
+            self.treasury_pda.key(),
+        )?;
+
+        let treasury_seeds = [
+            Seed::from(TREASURY_TOKEN),
+            Seed::from(self.platform_pda.key().as_ref()),
+            Seed::from(core::slice::from_ref(&platform.treasury_bump)),
+        ];
+        let treasury_signer = Signer::from(&treasury_seeds);
+
+        TransferChecked {
+            from: treasury_token_account,
+            mint,
+            to: self.destination,
+            authority: self.treasury_pda,
+            amount,
+            decimals,
+            token_program: token_program.key(),
+        }
+        .invoke_signed(&[treasury_signer])
+    }
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &'a [u8])> for WithdrawTreasury<'a> {
+    type Error = ProgramError;
+
+    fn try_from(value: (&'a [AccountInfo], &'a [u8])) -> Result<Self, Self::Error> {
+        let (accounts, instruction_data) = value;
+
+        let [
+            administrator,
+            platform_pda,
+            treasury_pda,
+            destination,
+            _,
+            remaining @ ..,
+        ] = accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if instruction_data.len() != size_of::<WithdrawTreasuryArgs>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let withdraw_args = try_from_bytes::<WithdrawTreasuryArgs>(instruction_data)
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+        Ok(Self {
+            administrator,
+            platform_pda: ProgramAccount::try_from(platform_pda)?,
+            treasury_pda,
+            destination,
+            withdraw_args,
+            remaining,
+        })
+    }
+}
@@ -1,107 +1,127 @@
-use alloc::string::ToString;
-use bytemuck::{bytes_of, try_from_bytes};
+use bytemuck::try_from_bytes;
 use pinocchio::ProgramResult;
 use pinocchio::account_info::AccountInfo;
 use pinocchio::instruction::{Seed, Signer};
 use pinocchio::program_error::ProgramError;
-use pinocchio::pubkey::try_find_program_address;
 use pinocchio::sysvars::Sysvar;
 use pinocchio::sysvars::rent::Rent;
 use pinocchio_log::log;
 use pinocchio_system::instructions::CreateAccount;
 
 use crate::error::UniPinoNftErr;
+use crate::events::{Event, UserCreated};
+use crate::state::claim::Claim;
 use crate::state::platform::Platform;
+use crate::state::program_state::ProgramState;
+use crate::state::role::ROLE_FEE_MANAGER;
 use crate::state::user::User;
 
+use super::accounts::{ProgramAccount, SignerAccount, UserSeeds};
 use super::*;
 
 pub const USER_TOKEN: &[u8] = b"user_wallet";
 
+/// Check `authority` signed and is either the platform administrator or the wallet
+/// linked to `user`, the check for actions on a user's own NFTs
+pub fn authorize_holder(
+    authority: &AccountInfo,
+    platform: &Platform,
+    user: &User,
+) -> Result<(), ProgramError> {
+    if !authority.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if platform.administrator == authority.key().as_ref()
+        || (user.has_authority() && user.authority == authority.key().as_ref())
+    {
+        return Ok(());
+    }
+
+    Err(UniPinoNftErr::NotUserHolder.into())
+}
+
 pub struct CreateUser<'a> {
     pub administrator: &'a AccountInfo,
-    pub platform_pda: &'a AccountInfo,
+    pub platform_pda: ProgramAccount<'a, Platform>,
     pub user_pda: &'a AccountInfo,
     pub user_uuid: &'a u128,
+    pub remaining: &'a [AccountInfo],
 }
 
 impl<'a> CreateUser<'a> {
     pub const DISCRIMINATOR: &'a u8 = &2;
 
     pub fn process(self) -> ProgramResult {
-        if !self.administrator.is_signer() {
-            return Err(ProgramError::MissingRequiredSignature);
-        }
-
-        if !self.platform_pda.is_owned_by(&ID) || self.platform_pda.lamports() == 0 {
-            return Err(UniPinoNftErr::UninitPda.into());
-        }
+        let mut platform_state = self.platform_pda.load_mut()?;
 
-        let mut platform_data_bytes = self.platform_pda.try_borrow_mut_data()?;
-        let platform_state = Platform::try_from_bytes_mut(platform_data_bytes.as_mut())?;
-
-        if platform_state.administrator != self.administrator.key().as_ref() {
-            return Err(ProgramError::InvalidAccountOwner);
-        }
+        multisig::authorize_admin(
+            self.administrator,
+            &self.platform_pda,
+            &platform_state,
+            self.remaining,
+        )?;
 
         if self.user_pda.lamports() > 0 {
             return Err(UniPinoNftErr::ReInitPda.into());
         }
 
-        let (user_pda, user_bump) = try_find_program_address(
-            &[
-                USER_TOKEN,
-                self.user_uuid.to_string().as_bytes(),
-                self.platform_pda.key().as_ref(),
-                core::slice::from_ref(&platform_state.bump),
-            ],
-            &ID,
-        )
-        .ok_or(UniPinoNftErr::PdaErr)?;
-
-        if user_pda != self.user_pda.key().as_ref() {
-            return Err(ProgramError::InvalidSeeds);
-        }
+        let seeds = UserSeeds::find(
+            self.user_pda,
+            *self.user_uuid,
+            &self.platform_pda,
+            &platform_state,
+        )?;
 
         let min_lamports = Rent::get()?.minimum_balance(User::INIT_SPACE);
         log!("Init user PDA requires min balance: {}", min_lamports);
 
         let platform_seeds = [
             Seed::from(platform::PLATFORM_TOKEN),
-            Seed::from(self.administrator.key().as_ref()),
+            Seed::from(platform_state.seed_key.as_ref()),
             Seed::from(core::slice::from_ref(&platform_state.bump)),
         ];
         let platform_signer = Signer::from(&platform_seeds);
 
-        let user_uuid_str = self.user_uuid.to_string();
-        let user_seeds = [
-            Seed::from(USER_TOKEN),
-            Seed::from(user_uuid_str.as_bytes()),
-            Seed::from(self.platform_pda.key().as_ref()),
-            Seed::from(core::slice::from_ref(&platform_state.bump)),
-            Seed::from(core::slice::from_ref(&user_bump)),
-        ];
+        let user_seeds = seeds.signer_seeds();
         let user_signer = Signer::from(&user_seeds);
 
         CreateAccount {
-            from: &self.administrator,
-            to: &self.user_pda,
+            from: self.administrator,
+            to: self.user_pda,
             lamports: min_lamports,
             space: User::INIT_SPACE as u64,
             owner: &ID,
         }
         .invoke_signed(&[platform_signer, user_signer])?;
 
-        let user_meta = User::new(*self.platform_pda.key(), *self.user_uuid, user_bump);
-        self.user_pda
-            .try_borrow_mut_data()?
-            .copy_from_slice(bytes_of(&user_meta));
+        platform_state.last_generation = platform_state
+            .last_generation
+            .checked_add(1)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+
+        // a user closed and created again gets a new generation, so intents and session
+        // keys of the closed one do not carry over
+        let user_meta = User::new(
+            *self.platform_pda.key(),
+            *self.user_uuid,
+            seeds.bump(),
+            platform_state.last_generation,
+        );
+        user_meta.init(self.user_pda)?;
 
         platform_state.total_users = platform_state
             .total_users
             .checked_add(1)
             .ok_or(ProgramError::ArithmeticOverflow)?;
 
+        UserCreated {
+            platform: *self.platform_pda.key(),
+            user: *self.user_pda.key(),
+            user_uuid: *self.user_uuid,
+        }
+        .emit();
+
         log!("platform pda created");
         Ok(())
     }
@@ -113,7 +133,7 @@ impl<'a> TryFrom<(&'a [AccountInfo], &'a [u8])> for CreateUser<'a> {
     fn try_from(value: (&'a [AccountInfo], &'a [u8])) -> Result<Self, Self::Error> {
         let (accounts, instruction_data) = value;
 
-        let [administrator, platform_pda, user_pda, _] = accounts else {
+        let [administrator, platform_pda, user_pda, _, remaining @ ..] = accounts else {
             return Err(ProgramError::NotEnoughAccountKeys);
         };
 
@@ -125,10 +145,264 @@ impl<'a> TryFrom<(&'a [AccountInfo], &'a [u8])> for CreateUser<'a> {
             .map_err(|_| ProgramError::InvalidInstructionData)?;
 
         Ok(Self {
-            administrator: administrator,
-            platform_pda: platform_pda,
-            user_pda: user_pda,
-            user_uuid: user_uuid,
+            administrator,
+            platform_pda: ProgramAccount::try_from(platform_pda)?,
+            user_pda,
+            user_uuid,
+            remaining,
+        })
+    }
+}
+
+pub struct CloseUser<'a> {
+    pub administrator: &'a AccountInfo,
+    pub platform_pda: ProgramAccount<'a, Platform>,
+    pub user_pda: ProgramAccount<'a, User>,
+    pub remaining: &'a [AccountInfo],
+}
+
+impl<'a> CloseUser<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &15;
+
+    pub fn process(self) -> ProgramResult {
+        let mut platform_state = self.platform_pda.load_mut()?;
+
+        // the claim PDAs to close follow the multisig approvals
+        let claim_pdas = multisig::authorize_admin(
+            self.administrator,
+            &self.platform_pda,
+            &platform_state,
+            self.remaining,
+        )?;
+
+        {
+            let mut user = self.user_pda.load_mut()?;
+            self.user_pda
+                .check_platform(&user, &self.platform_pda, &platform_state)?;
+
+            // the NFTs it holds have to be burned or transferred first, so none is left
+            // recorded as held by a closed user
+            if user.nft_count != 0 {
+                return Err(UniPinoNftErr::AccountNotEmpty.into());
+            }
+
+            // allowlist claims would count against a user created again at the same PDA
+            for claim_pda in claim_pdas {
+                {
+                    let claim_pda = ProgramAccount::<Claim>::try_from(claim_pda)?;
+                    if claim_pda.load()?.user != self.user_pda.key().as_ref() {
+                        return Err(UniPinoNftErr::UserMismatch.into());
+                    }
+                }
+                accounts::close(claim_pda, self.administrator)?;
+                user.claim_count = user
+                    .claim_count
+                    .checked_sub(1)
+                    .ok_or(ProgramError::ArithmeticOverflow)?;
+            }
+
+            // session keys are the user's to revoke, they get the rent back
+            if user.claim_count != 0 || user.session_count != 0 {
+                return Err(UniPinoNftErr::AccountNotEmpty.into());
+            }
+        }
+
+        accounts::close(&self.user_pda, self.administrator)?;
+
+        platform_state.total_users = platform_state
+            .total_users
+            .checked_sub(1)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+
+        log!("user pda closed");
+        Ok(())
+    }
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for CloseUser<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [administrator, platform_pda, user_pda, remaining @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        Ok(Self {
+            administrator,
+            platform_pda: ProgramAccount::try_from(platform_pda)?,
+            user_pda: ProgramAccount::try_from(user_pda)?,
+            remaining,
+        })
+    }
+}
+
+pub struct LinkUserWallet<'a> {
+    pub administrator: &'a AccountInfo,
+    pub platform_pda: ProgramAccount<'a, Platform>,
+    pub user_pda: ProgramAccount<'a, User>,
+    pub wallet: SignerAccount<'a>,
+    pub remaining: &'a [AccountInfo],
+}
+
+impl<'a> LinkUserWallet<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &18;
+
+    pub fn process(self) -> ProgramResult {
+        let platform_state = self.platform_pda.load()?;
+
+        multisig::authorize_admin(
+            self.administrator,
+            &self.platform_pda,
+            &platform_state,
+            self.remaining,
+        )?;
+
+        let mut user = self.user_pda.load_mut()?;
+        self.user_pda
+            .check_platform(&user, &self.platform_pda, &platform_state)?;
+
+        user.authority = *self.wallet.key();
+
+        log!("user linked to wallet {}", self.wallet.key());
+        Ok(())
+    }
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for LinkUserWallet<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [
+            administrator,
+            platform_pda,
+            user_pda,
+            wallet,
+            remaining @ ..,
+        ] = accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        Ok(Self {
+            administrator,
+            platform_pda: ProgramAccount::try_from(platform_pda)?,
+            user_pda: ProgramAccount::try_from(user_pda)?,
+            // the wallet proves it agrees to act for this user
+            wallet: SignerAccount::try_from(wallet)?,
+            remaining,
+        })
+    }
+}
+
+pub struct UnlinkUserWallet<'a> {
+    pub administrator: &'a AccountInfo,
+    pub platform_pda: ProgramAccount<'a, Platform>,
+    pub user_pda: ProgramAccount<'a, User>,
+    pub remaining: &'a [AccountInfo],
+}
+
+impl<'a> UnlinkUserWallet<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &19;
+
+    pub fn process(self) -> ProgramResult {
+        let mut platform_state = self.platform_pda.load_mut()?;
+
+        multisig::authorize_admin(
+            self.administrator,
+            &self.platform_pda,
+            &platform_state,
+            self.remaining,
+        )?;
+
+        let mut user = self.user_pda.load_mut()?;
+        self.user_pda
+            .check_platform(&user, &self.platform_pda, &platform_state)?;
+
+        platform_state.last_generation = platform_state
+            .last_generation
+            .checked_add(1)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        user.unlink_wallet(platform_state.last_generation);
+
+        log!("user wallet unlinked");
+        Ok(())
+    }
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for UnlinkUserWallet<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [administrator, platform_pda, user_pda, remaining @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        Ok(Self {
+            administrator,
+            platform_pda: ProgramAccount::try_from(platform_pda)?,
+            user_pda: ProgramAccount::try_from(user_pda)?,
+            remaining,
+        })
+    }
+}
+
+pub struct SetUserTier<'a> {
+    pub authority: SignerAccount<'a>,
+    pub platform_pda: ProgramAccount<'a, Platform>,
+    pub user_pda: ProgramAccount<'a, User>,
+    pub tier_args: &'a SetUserTierArgs,
+    pub remaining: &'a [AccountInfo],
+}
+
+impl<'a> SetUserTier<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &32;
+
+    pub fn process(self) -> ProgramResult {
+        let platform_state = self.platform_pda.load()?;
+
+        // a fee manager passes its role PDA in the remaining accounts
+        role::authorize(
+            &self.authority,
+            &self.platform_pda,
+            &platform_state,
+            ROLE_FEE_MANAGER,
+            self.remaining,
+        )?;
+
+        let mut user = self.user_pda.load_mut()?;
+        self.user_pda
+            .check_platform(&user, &self.platform_pda, &platform_state)?;
+
+        user.tier_level = self.tier_args.tier_level;
+
+        log!("user tier level set to {}", self.tier_args.tier_level);
+        Ok(())
+    }
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &'a [u8])> for SetUserTier<'a> {
+    type Error = ProgramError;
+
+    fn try_from(value: (&'a [AccountInfo], &'a [u8])) -> Result<Self, Self::Error> {
+        let (accounts, instruction_data) = value;
+
+        let [authority, platform_pda, user_pda, remaining @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if instruction_data.len() != size_of::<SetUserTierArgs>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let tier_args = try_from_bytes::<SetUserTierArgs>(instruction_data)
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+        Ok(Self {
+            authority: SignerAccount::try_from(authority)?,
+            platform_pda: ProgramAccount::try_from(platform_pda)?,
+            user_pda: ProgramAccount::try_from(user_pda)?,
+            tier_args,
+            remaining,
         })
     }
 }
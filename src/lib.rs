@@ -3,13 +3,11 @@
 
 extern crate alloc;
 
-mod error;
-mod instructions;
-mod state {
-    pub mod nft_meta;
-    pub mod platform;
-    pub mod user;
-}
+pub mod cpi;
+pub mod error;
+pub mod events;
+pub mod instructions;
+pub mod state;
 
 #[cfg(feature = "bpf-entrypoint")]
 mod entrypoint {
@@ -18,11 +16,10 @@ mod entrypoint {
         pubkey::Pubkey,
     };
 
-    use crate::instructions::{nft::*, platform::*, user::*};
-
-    use pinocchio_pubkey::declare_id;
-
-    declare_id!("6jpuWYTM3ARc5CHrMBtR1c7gyjkMTsJoYT7PqqhMpRWh");
+    use crate::instructions::{
+        collection::*, delegate::*, migrate::*, multisig::*, nft::*, ownership::*, platform::*,
+        role::*, session::*, timelock::*, transfer::*, treasury::*, user::*,
+    };
 
     entrypoint!(process_instruction);
 
@@ -43,7 +40,85 @@ mod entrypoint {
             Some((UpdateNFTMetadata::DISCRIMINATOR, data)) => {
                 UpdateNFTMetadata::try_from((accounts, data))?.process()
             }
-            Some((BurnNft::DISCRIMINATOR, _)) => BurnNft::try_from(accounts)?.process(),
+            Some((BurnNft::DISCRIMINATOR, data)) => BurnNft::try_from((accounts, data))?.process(),
+            Some((WithdrawTreasury::DISCRIMINATOR, data)) => {
+                WithdrawTreasury::try_from((accounts, data))?.process()
+            }
+            Some((SetCollectionConfig::DISCRIMINATOR, data)) => {
+                SetCollectionConfig::try_from((accounts, data))?.process()
+            }
+            Some((SetPauseFlags::DISCRIMINATOR, data)) => {
+                SetPauseFlags::try_from((accounts, data))?.process()
+            }
+            Some((GrantRole::DISCRIMINATOR, data)) => {
+                GrantRole::try_from((accounts, data))?.process()
+            }
+            Some((RevokeRole::DISCRIMINATOR, _)) => RevokeRole::try_from(accounts)?.process(),
+            Some((SetAdminMultisig::DISCRIMINATOR, data)) => {
+                SetAdminMultisig::try_from((accounts, data))?.process()
+            }
+            Some((QueueConfigChange::DISCRIMINATOR, data)) => {
+                QueueConfigChange::try_from((accounts, data))?.process()
+            }
+            Some((ExecuteConfigChange::DISCRIMINATOR, _)) => {
+                ExecuteConfigChange::try_from(accounts)?.process()
+            }
+            Some((CancelConfigChange::DISCRIMINATOR, _)) => {
+                CancelConfigChange::try_from(accounts)?.process()
+            }
+            Some((CloseUser::DISCRIMINATOR, _)) => CloseUser::try_from(accounts)?.process(),
+            Some((ClosePlatform::DISCRIMINATOR, _)) => ClosePlatform::try_from(accounts)?.process(),
+            Some((MigrateAccount::DISCRIMINATOR, _)) => {
+                MigrateAccount::try_from(accounts)?.process()
+            }
+            Some((LinkUserWallet::DISCRIMINATOR, _)) => {
+                LinkUserWallet::try_from(accounts)?.process()
+            }
+            Some((UnlinkUserWallet::DISCRIMINATOR, _)) => {
+                UnlinkUserWallet::try_from(accounts)?.process()
+            }
+            Some((CreateSessionKey::DISCRIMINATOR, data)) => {
+                CreateSessionKey::try_from((accounts, data))?.process()
+            }
+            Some((RevokeSessionKey::DISCRIMINATOR, _)) => {
+                RevokeSessionKey::try_from(accounts)?.process()
+            }
+            Some((ApproveNftDelegate::DISCRIMINATOR, _)) => {
+                ApproveNftDelegate::try_from(accounts)?.process()
+            }
+            Some((RevokeNftDelegate::DISCRIMINATOR, _)) => {
+                RevokeNftDelegate::try_from(accounts)?.process()
+            }
+            Some((VerifyOwnership::DISCRIMINATOR, data)) => {
+                VerifyOwnership::try_from((accounts, data))?.process()
+            }
+            Some((SetCollectionAllowlist::DISCRIMINATOR, data)) => {
+                SetCollectionAllowlist::try_from((accounts, data))?.process()
+            }
+            Some((QueueCollectionConfig::DISCRIMINATOR, data)) => {
+                QueueCollectionConfig::try_from((accounts, data))?.process()
+            }
+            Some((QueueCollectionAllowlist::DISCRIMINATOR, data)) => {
+                QueueCollectionAllowlist::try_from((accounts, data))?.process()
+            }
+            Some((ExecuteCollectionChange::DISCRIMINATOR, _)) => {
+                ExecuteCollectionChange::try_from(accounts)?.process()
+            }
+            Some((CancelCollectionChange::DISCRIMINATOR, _)) => {
+                CancelCollectionChange::try_from(accounts)?.process()
+            }
+            Some((TransferNftInternal::DISCRIMINATOR, _)) => {
+                TransferNftInternal::try_from(accounts)?.process()
+            }
+            Some((BurnNftWithIntent::DISCRIMINATOR, data)) => {
+                BurnNftWithIntent::try_from((accounts, data))?.process()
+            }
+            Some((SetUserTier::DISCRIMINATOR, data)) => {
+                SetUserTier::try_from((accounts, data))?.process()
+            }
+            Some((RotateAdministrator::DISCRIMINATOR, _)) => {
+                RotateAdministrator::try_from(accounts)?.process()
+            }
             _ => Err(ProgramError::InvalidInstructionData),
         }
     }
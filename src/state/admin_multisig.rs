@@ -0,0 +1,46 @@
+use bytemuck::{Pod, Zeroable};
+use pinocchio::pubkey::Pubkey;
+use shank::ShankAccount;
+
+use crate::state::program_state::program_state;
+
+pub const MAX_ADMIN_SIGNERS: usize = 5;
+
+/// Administrator signer set, PDA seeds `["admin_multisig", platform]`
+#[repr(C, packed)]
+#[derive(Copy, Clone, Pod, Zeroable, ShankAccount)]
+pub struct AdminMultisig {
+    pub discriminator: [u8; 8],
+    pub platform: Pubkey, // platform PDA governed by this signer set
+    pub threshold: u8,
+    pub signer_count: u8,
+    pub bump: u8,
+    pub signers: [Pubkey; 5], // MAX_ADMIN_SIGNERS, spelled out for shank
+    #[padding]
+    pub reserved: [u8; 32],
+}
+
+program_state! {
+    AdminMultisig {
+        discriminator: *b"adminmsg",
+    }
+}
+
+impl AdminMultisig {
+    pub fn new(platform_pda: Pubkey, bump: u8) -> Self {
+        Self {
+            discriminator: Self::DISCRIMINATOR,
+            platform: platform_pda,
+            threshold: 0,
+            signer_count: 0,
+            bump,
+            signers: [Pubkey::default(); MAX_ADMIN_SIGNERS],
+            reserved: [0; 32],
+        }
+    }
+
+    /// Members of the signer set
+    pub fn signers(&self) -> &[Pubkey] {
+        &self.signers[..(self.signer_count as usize).min(MAX_ADMIN_SIGNERS)]
+    }
+}
@@ -0,0 +1,44 @@
+use bytemuck::{Pod, Zeroable};
+use pinocchio::pubkey::Pubkey;
+use shank::ShankAccount;
+
+use crate::state::program_state::program_state;
+
+/// Allowlist mints of a user in a collection, PDA seeds `["claim", collection, user]`
+#[repr(C, packed)]
+#[derive(Copy, Clone, Pod, Zeroable, ShankAccount)]
+pub struct Claim {
+    pub discriminator: [u8; 8],
+    pub collection: Pubkey, // collection PDA the allowlist belongs to
+    pub user: Pubkey,       // user PDA the NFTs were minted for
+    pub phase: u8,          // collection phase `count` refers to
+    pub count: u16,         // allowlist mints of the user during `phase`
+    pub bump: u8,
+    #[padding]
+    pub reserved: [u8; 32],
+}
+
+program_state! {
+    Claim {
+        discriminator: *b"claim\0\0\0",
+    }
+}
+
+impl Claim {
+    pub fn new(collection_pda: Pubkey, user_pda: Pubkey, bump: u8) -> Self {
+        Self {
+            discriminator: Self::DISCRIMINATOR,
+            collection: collection_pda,
+            user: user_pda,
+            phase: 0,
+            count: 0,
+            bump,
+            reserved: [0; 32],
+        }
+    }
+
+    /// Mints of the user during `phase`, earlier phases do not count
+    pub fn count_in(&self, phase: u8) -> u16 {
+        if self.phase == phase { self.count } else { 0 }
+    }
+}
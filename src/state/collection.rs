@@ -0,0 +1,59 @@
+use bytemuck::{Pod, Zeroable};
+use pinocchio::pubkey::Pubkey;
+use shank::ShankAccount;
+
+use crate::state::program_state::program_state;
+
+/// Collection config, PDA seeds `["collection", platform, name[..32], name[32..]]`
+#[repr(C, packed)]
+#[derive(Copy, Clone, Pod, Zeroable, ShankAccount)]
+pub struct Collection {
+    pub discriminator: [u8; 8],
+    pub platform: Pubkey, // platform PDA this collection belongs to
+    pub name: [u8; 64],
+    pub mint_fee: u64,
+    pub has_mint_fee: u8, // when set, `mint_fee` overrides `Platform.mint_fee`
+    pub bump: u8,
+    pub allowlist_root: [u8; 32], // merkle root of allowlisted user PDAs, all-zero for open mints
+    pub phase: u8,                // changing the phase resets every allowlist count
+    pub phase_user_limit: u16,    // allowlist mints per user during the phase
+    pub phase_supply: u32,        // allowlist mints during the phase, 0 for no cap
+    pub phase_minted: u32,
+    #[padding]
+    pub reserved: [u8; 85],
+}
+
+program_state! {
+    Collection {
+        discriminator: *b"collectn",
+    }
+}
+
+impl Collection {
+    pub fn new(platform_pda: Pubkey, name: [u8; 64], bump: u8) -> Self {
+        Self {
+            discriminator: Self::DISCRIMINATOR,
+            platform: platform_pda,
+            name,
+            mint_fee: 0,
+            has_mint_fee: 0,
+            bump,
+            allowlist_root: [0; 32],
+            phase: 0,
+            phase_user_limit: 0,
+            phase_supply: 0,
+            phase_minted: 0,
+            reserved: [0; 85],
+        }
+    }
+
+    /// Collection specific mint fee, if one is configured
+    pub fn mint_fee_override(&self) -> Option<u64> {
+        (self.has_mint_fee != 0).then_some(self.mint_fee)
+    }
+
+    /// Whether minting into the collection needs an allowlist proof
+    pub fn has_allowlist(&self) -> bool {
+        self.allowlist_root != [0; 32]
+    }
+}
@@ -0,0 +1,64 @@
+use bytemuck::{Pod, Zeroable};
+use pinocchio::program_error::ProgramError;
+use pinocchio::pubkey::Pubkey;
+use shank::{ShankAccount, ShankType};
+
+use crate::state::program_state::program_state;
+
+pub const MAX_FEE_SHARES: usize = 4;
+pub const MAX_BPS: u16 = 10_000;
+
+#[repr(C, packed)]
+#[derive(Copy, Clone, Pod, Zeroable, ShankType)]
+pub struct FeeShare {
+    pub receiver: Pubkey,
+    pub bps: u16,
+}
+
+impl FeeShare {
+    /// Portion of `amount` owed to this receiver, rounded down
+    pub fn portion(&self, amount: u64) -> Result<u64, ProgramError> {
+        let portion = (amount as u128)
+            .checked_mul(self.bps as u128)
+            .ok_or(ProgramError::ArithmeticOverflow)?
+            / MAX_BPS as u128;
+        u64::try_from(portion).map_err(|_| ProgramError::ArithmeticOverflow)
+    }
+}
+
+/// Fee split receivers, PDA seeds `["fee_schedule", platform]`
+#[repr(C, packed)]
+#[derive(Copy, Clone, Pod, Zeroable, ShankAccount)]
+pub struct FeeSchedule {
+    pub discriminator: [u8; 8],
+    pub platform: Pubkey, // platform PDA this schedule belongs to
+    pub share_count: u8,
+    pub bump: u8,
+    pub shares: [FeeShare; 4], // MAX_FEE_SHARES, spelled out for shank
+    #[padding]
+    pub reserved: [u8; 32],
+}
+
+program_state! {
+    FeeSchedule {
+        discriminator: *b"feesched",
+    }
+}
+
+impl FeeSchedule {
+    pub fn new(platform_pda: Pubkey, bump: u8) -> Self {
+        Self {
+            discriminator: Self::DISCRIMINATOR,
+            platform: platform_pda,
+            share_count: 0,
+            bump,
+            shares: [FeeShare::zeroed(); MAX_FEE_SHARES],
+            reserved: [0; 32],
+        }
+    }
+
+    /// Configured shares, in the order their receivers must be passed
+    pub fn shares(&self) -> &[FeeShare] {
+        &self.shares[..(self.share_count as usize).min(MAX_FEE_SHARES)]
+    }
+}
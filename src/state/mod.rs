@@ -0,0 +1,12 @@
+pub mod admin_multisig;
+pub mod claim;
+pub mod collection;
+pub mod fee_schedule;
+pub mod nft_meta;
+pub mod pending_collection;
+pub mod pending_config;
+pub mod platform;
+pub mod program_state;
+pub mod role;
+pub mod session_key;
+pub mod user;
@@ -1,36 +1,181 @@
-use bytemuck::{Pod, Zeroable, try_from_bytes, try_from_bytes_mut};
+use bytemuck::{Pod, Zeroable, bytes_of};
+use pinocchio::ProgramResult;
 use pinocchio::program_error::ProgramError;
+use pinocchio::pubkey::Pubkey;
+use shank::ShankAccount;
 
+use crate::error::UniPinoNftErr;
+use crate::state::program_state::ProgramState;
+
+/// NFT metadata, PDA seeds `["metadata", mint, Token-2022 id]`
 #[repr(C, packed)]
-#[derive(Copy, Clone, Pod, Zeroable)]
+#[derive(Copy, Clone, Pod, Zeroable, ShankAccount)]
 pub struct NftMeta {
     pub discriminator: [u8; 8],
     pub name: [u8; 256],
     pub collection: [u8; 64],
     pub uri: [u8; 256],
     pub description: [u8; 256],
+    pub version: u8,         // missing from accounts created before versioning
+    pub delegate: Pubkey,    // approved to transfer the NFT once, all-zero key when none
+    pub holder: Pubkey,      // user PDA holding the NFT, all-zero before version 2
+    pub attribute: [u8; 32], // matched by `VerifyOwnership`, all-zero for none
+    #[padding]
+    pub reserved: [u8; 31],
 }
 
 impl NftMeta {
     pub const DISCRIMINATOR: [u8; 8] = *b"nftmeta\0";
     pub const INIT_SPACE: usize = core::mem::size_of::<Self>();
+    pub const CURRENT_VERSION: u8 = 3;
+    /// Size of accounts created before the version byte was appended
+    pub const LEGACY_SPACE: usize = core::mem::offset_of!(Self, version);
+    /// Size of version 1 accounts, which end with the reserved bytes right after `delegate`
+    pub const V1_SPACE: usize = core::mem::offset_of!(Self, holder) + 31;
+    /// Size of version 2 accounts, which end with the reserved bytes right after `holder`
+    pub const V2_SPACE: usize = core::mem::offset_of!(Self, attribute) + 31;
 
-    /// Deserialize and validate discriminator for immutable access
-    pub fn try_from_bytes(data: &[u8]) -> Result<&Self, ProgramError> {
-        let state = try_from_bytes::<Self>(data).map_err(|_| ProgramError::InvalidAccountData)?;
-        if state.discriminator != Self::DISCRIMINATOR {
-            return Err(ProgramError::InvalidAccountData);
+    pub fn new(
+        name: [u8; 256],
+        collection: [u8; 64],
+        uri: [u8; 256],
+        description: [u8; 256],
+        holder: Pubkey,
+        attribute: [u8; 32],
+    ) -> Self {
+        Self {
+            discriminator: Self::DISCRIMINATOR,
+            name,
+            collection,
+            uri,
+            description,
+            version: Self::CURRENT_VERSION,
+            delegate: Pubkey::default(),
+            holder,
+            attribute,
+            reserved: [0; 31],
         }
-        Ok(state)
     }
 
-    /// Deserialize and validate discriminator for mutable access
-    pub fn try_from_bytes_mut(data: &mut [u8]) -> Result<&mut Self, ProgramError> {
-        let state =
-            try_from_bytes_mut::<Self>(data).map_err(|_| ProgramError::InvalidAccountData)?;
-        if state.discriminator != Self::DISCRIMINATOR {
+    /// Layout version of raw metadata account data, 0 for legacy accounts
+    pub fn version_of(data: &[u8]) -> Result<u8, ProgramError> {
+        if data.len() < Self::LEGACY_SPACE || data[..8] != Self::DISCRIMINATOR {
             return Err(ProgramError::InvalidAccountData);
         }
-        Ok(state)
+
+        if data.len() == Self::LEGACY_SPACE {
+            return Ok(0);
+        }
+
+        let version = data[Self::LEGACY_SPACE];
+        let space = match version {
+            1 => Self::V1_SPACE,
+            2 => Self::V2_SPACE,
+            Self::CURRENT_VERSION => Self::INIT_SPACE,
+            _ => return Err(UniPinoNftErr::UnsupportedVersion.into()),
+        };
+        if data.len() != space {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(version)
+    }
+
+    /// Holder recorded in raw metadata account data, `None` when the metadata predates
+    /// holders and the NFT is still with the user it was minted for
+    pub fn holder_of(data: &[u8]) -> Result<Option<Pubkey>, ProgramError> {
+        if Self::version_of(data)? < 2 {
+            return Ok(None);
+        }
+        let offset = core::mem::offset_of!(Self, holder);
+        let holder: Pubkey = data[offset..offset + 32]
+            .try_into()
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+        Ok((holder != Pubkey::default()).then_some(holder))
+    }
+
+    /// Collection of raw metadata account data of any supported version
+    pub fn collection_of(data: &[u8]) -> Result<[u8; 64], ProgramError> {
+        Self::version_of(data)?;
+        let offset = core::mem::offset_of!(Self, collection);
+        data[offset..offset + 64]
+            .try_into()
+            .map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    /// Attribute of raw metadata account data of any supported version, all-zero before
+    /// version 3
+    pub fn attribute_of(data: &[u8]) -> Result<[u8; 32], ProgramError> {
+        if Self::version_of(data)? < 3 {
+            return Ok([0; 32]);
+        }
+        let offset = core::mem::offset_of!(Self, attribute);
+        data[offset..offset + 32]
+            .try_into()
+            .map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    /// Overwrite the metadata fields and attribute of an account of any supported
+    /// version, keeping its version, delegate, holder and reserved bytes. Accounts from
+    /// before version 3 have no attribute slot and only take an all-zero attribute
+    pub fn write_fields(&self, data: &mut [u8]) -> Result<(), ProgramError> {
+        if Self::version_of(data)? < 3 {
+            if self.attribute != [0; 32] {
+                return Err(UniPinoNftErr::UnsupportedVersion.into());
+            }
+        } else {
+            let offset = core::mem::offset_of!(Self, attribute);
+            data[offset..offset + 32].copy_from_slice(&self.attribute);
+        }
+        data[..Self::LEGACY_SPACE].copy_from_slice(&bytes_of(self)[..Self::LEGACY_SPACE]);
+        Ok(())
+    }
+}
+
+impl ProgramState for NftMeta {
+    const DISCRIMINATOR: [u8; 8] = Self::DISCRIMINATOR;
+    const CURRENT_VERSION: u8 = Self::CURRENT_VERSION;
+
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    // legacy metadata stays shorter than `NftMeta` until it is migrated
+    fn validate(data: &[u8]) -> ProgramResult {
+        Self::version_of(data).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata(attribute: [u8; 32]) -> NftMeta {
+        NftMeta::new([1; 256], [2; 64], [3; 256], [4; 256], [5; 32], attribute)
+    }
+
+    #[test]
+    fn attribute_needs_version_3() {
+        let mut data = bytes_of(&metadata([0; 32])).to_vec();
+        data.truncate(NftMeta::V2_SPACE);
+        data[NftMeta::LEGACY_SPACE] = 2;
+
+        assert_eq!(NftMeta::attribute_of(&data), Ok([0; 32]));
+        assert_eq!(NftMeta::holder_of(&data), Ok(Some([5; 32])));
+        assert!(metadata([0; 32]).write_fields(&mut data).is_ok());
+        assert_eq!(
+            metadata([6; 32]).write_fields(&mut data),
+            Err(UniPinoNftErr::UnsupportedVersion.into())
+        );
+    }
+
+    #[test]
+    fn write_fields_keeps_the_holder() {
+        let mut data = bytes_of(&metadata([6; 32])).to_vec();
+        let mut update = metadata([7; 32]);
+        update.holder = [8; 32];
+
+        update.write_fields(&mut data).unwrap();
+        assert_eq!(NftMeta::attribute_of(&data), Ok([7; 32]));
+        assert_eq!(NftMeta::holder_of(&data), Ok(Some([5; 32])));
     }
 }
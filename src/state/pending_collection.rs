@@ -0,0 +1,56 @@
+use bytemuck::{Pod, Zeroable};
+use pinocchio::pubkey::Pubkey;
+use shank::ShankAccount;
+
+use crate::instructions::{CollectionAllowlistArgs, CollectionConfigArgs};
+use crate::state::program_state::program_state;
+
+/// `PendingCollectionChange.kind` values
+pub const COLLECTION_CHANGE_CONFIG: u8 = 0;
+pub const COLLECTION_CHANGE_ALLOWLIST: u8 = 1;
+
+/// Queued collection change, PDA seeds `["collection_change", collection]`
+#[repr(C, packed)]
+#[derive(Copy, Clone, Pod, Zeroable, ShankAccount)]
+pub struct PendingCollectionChange {
+    pub discriminator: [u8; 8],
+    pub platform: Pubkey,   // platform PDA the collection belongs to
+    pub collection: Pubkey, // collection PDA the change applies to
+    pub payer: Pubkey,      // account that queued the change, gets the rent back
+    pub eta: i64,           // earliest unix timestamp the change can execute at
+    pub bump: u8,
+    pub kind: u8, // which of `config` and `allowlist` is queued
+    pub config: CollectionConfigArgs,
+    pub allowlist: CollectionAllowlistArgs,
+    #[padding]
+    pub reserved: [u8; 32],
+}
+
+program_state! {
+    PendingCollectionChange {
+        discriminator: *b"pendcol\0",
+    }
+}
+
+impl PendingCollectionChange {
+    pub fn new(
+        platform_pda: Pubkey,
+        collection_pda: Pubkey,
+        payer: Pubkey,
+        eta: i64,
+        bump: u8,
+    ) -> Self {
+        Self {
+            discriminator: Self::DISCRIMINATOR,
+            platform: platform_pda,
+            collection: collection_pda,
+            payer,
+            eta,
+            bump,
+            kind: COLLECTION_CHANGE_CONFIG,
+            config: CollectionConfigArgs::zeroed(),
+            allowlist: CollectionAllowlistArgs::zeroed(),
+            reserved: [0; 32],
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use bytemuck::{Pod, Zeroable};
+use pinocchio::pubkey::Pubkey;
+use shank::ShankAccount;
+
+use crate::instructions::UpdatePlatformArgs;
+use crate::state::program_state::program_state;
+
+/// Queued config change, PDA seeds `["config_change", platform]`
+#[repr(C, packed)]
+#[derive(Copy, Clone, Pod, Zeroable, ShankAccount)]
+pub struct PendingConfig {
+    pub discriminator: [u8; 8],
+    pub platform: Pubkey, // platform PDA the change applies to
+    pub eta: i64,         // earliest unix timestamp the change can execute at
+    pub bump: u8,
+    pub args: UpdatePlatformArgs,
+    #[padding]
+    pub reserved: [u8; 32],
+}
+
+program_state! {
+    PendingConfig {
+        discriminator: *b"pendcfg\0",
+    }
+}
+
+impl PendingConfig {
+    pub fn new(platform_pda: Pubkey, eta: i64, bump: u8, args: UpdatePlatformArgs) -> Self {
+        Self {
+            discriminator: Self::DISCRIMINATOR,
+            platform: platform_pda,
+            eta,
+            bump,
+            args,
+            reserved: [0; 32],
+        }
+    }
+}
@@ -1,23 +1,72 @@
-use bytemuck::{Pod, Zeroable, try_from_bytes, try_from_bytes_mut};
+use bytemuck::{Pod, Zeroable, bytes_of_mut};
+use pinocchio::ProgramResult;
 use pinocchio::program_error::ProgramError;
 use pinocchio::pubkey::Pubkey;
+use shank::{ShankAccount, ShankType};
 
+use crate::error::UniPinoNftErr;
+use crate::state::program_state::ProgramState;
+
+pub const MAX_FEE_TIERS: usize = 4;
+
+// `Platform.paused` flags, each halting one kind of operation
+pub const PAUSE_MINT: u8 = 1 << 0;
+pub const PAUSE_METADATA_UPDATE: u8 = 1 << 1;
+pub const PAUSE_BURN: u8 = 1 << 2;
+pub const PAUSE_TRANSFER: u8 = 1 << 3;
+pub const PAUSE_SALE: u8 = 1 << 4;
+pub const PAUSE_ALL: u8 =
+    PAUSE_MINT | PAUSE_METADATA_UPDATE | PAUSE_BURN | PAUSE_TRANSFER | PAUSE_SALE;
+
+#[repr(C, packed)]
+#[derive(Copy, Clone, Pod, Zeroable, ShankType)]
+pub struct FeeTier {
+    pub min_level: u32, // tier applies to users whose `User.tier_level` reaches this
+    pub discount_bps: u16,
+}
+
+/// Platform configuration, PDA seeds `["administer", seed_key]`
 #[repr(C, packed)]
-#[derive(Copy, Clone, Pod, Zeroable)]
+#[derive(Copy, Clone, Pod, Zeroable, ShankAccount)]
 pub struct Platform {
     pub discriminator: [u8; 8],
-    pub administrator: Pubkey, // this PDA should also be owned by authority
+    pub administrator: Pubkey, // current administrator, see `seed_key` for the PDA seeds
     pub fee_receiver: Pubkey,
     pub total_users: u64,
     pub total_mints: u64,
     pub mint_fee: u64,
     pub bump: u8,
-    pub reserved: [u8; 128],
+    pub fee_mint: Pubkey, // all-zero key means mint fees are paid in lamports
+    pub fee_schedule_enabled: u8,
+    pub treasury_enabled: u8,
+    pub treasury_bump: u8,
+    pub treasury_lamports_collected: u64, // lifetime lamport fees routed to the treasury
+    pub treasury_lamports_withdrawn: u64,
+    pub treasury_max_withdrawal: u64, // max amount of a single WithdrawTreasury, 0 for uncapped
+    pub fee_tier_count: u8,
+    pub fee_tiers: [FeeTier; 4], // MAX_FEE_TIERS, sorted by ascending min_level
+    pub paused: u8,
+    pub multisig_enabled: u8, // platform-level instructions need `AdminMultisig` approvals
+    pub config_delay: i64,    // seconds a queued config change waits before it can execute
+    pub version: u8,          // layout version, 0 for accounts created before versioning
+    pub treasury_tokens_collected: u64, // lifetime fee mint units routed to the treasury
+    pub treasury_tokens_withdrawn: u64,
+    pub role_count: u16,       // granted role PDAs, ClosePlatform closes them all
+    pub collection_count: u32, // collection PDAs, ClosePlatform closes them all
+    pub collection_change_count: u16, // queued collection changes, likewise
+    pub last_generation: u64, // last `User.generation` handed out, by CreateUser or UnlinkUserWallet
+    pub seed_key: Pubkey,     // administrator that created the platform, seeds its PDA
+    #[padding]
+    pub reserved: [u8; 1],
 }
 
 impl Platform {
     pub const DISCRIMINATOR: [u8; 8] = *b"platform";
     pub const INIT_SPACE: usize = core::mem::size_of::<Self>();
+    pub const CURRENT_VERSION: u8 = 2;
+    /// Size of version 0 and 1 accounts, which end with the reserved byte right after
+    /// `last_generation`
+    pub const V1_SPACE: usize = core::mem::offset_of!(Self, seed_key) + 1;
 
     pub fn new(authority: Pubkey, bump: u8) -> Self {
         Self {
@@ -28,26 +77,170 @@ impl Platform {
             total_mints: 0,
             mint_fee: 0,
             bump,
-            reserved: [0; 128],
+            fee_mint: Pubkey::default(),
+            fee_schedule_enabled: 0,
+            treasury_enabled: 0,
+            treasury_bump: 0,
+            treasury_lamports_collected: 0,
+            treasury_lamports_withdrawn: 0,
+            treasury_max_withdrawal: 0,
+            fee_tier_count: 0,
+            fee_tiers: [FeeTier::zeroed(); MAX_FEE_TIERS],
+            paused: 0,
+            multisig_enabled: 0,
+            config_delay: 0,
+            version: Self::CURRENT_VERSION,
+            treasury_tokens_collected: 0,
+            treasury_tokens_withdrawn: 0,
+            role_count: 0,
+            collection_count: 0,
+            collection_change_count: 0,
+            last_generation: 0,
+            seed_key: authority,
+            reserved: [0; 1],
         }
     }
 
-    /// Deserialize and validate discriminator for immutable access
-    pub fn try_from_bytes(data: &[u8]) -> Result<&Self, ProgramError> {
-        let state = try_from_bytes::<Self>(data).map_err(|_| ProgramError::InvalidAccountData)?;
-        if state.discriminator != Self::DISCRIMINATOR {
+    /// Layout version of raw platform account data, checking its size matches it
+    pub fn version_of(data: &[u8]) -> Result<u8, ProgramError> {
+        if data.len() < Self::V1_SPACE || data[..8] != Self::DISCRIMINATOR {
             return Err(ProgramError::InvalidAccountData);
         }
-        Ok(state)
-    }
 
-    /// Deserialize and validate discriminator for mutable access
-    pub fn try_from_bytes_mut(data: &mut [u8]) -> Result<&mut Self, ProgramError> {
-        let state =
-            try_from_bytes_mut::<Self>(data).map_err(|_| ProgramError::InvalidAccountData)?;
-        if state.discriminator != Self::DISCRIMINATOR {
+        let version = data[core::mem::offset_of!(Self, version)];
+        let space = match version {
+            0 | 1 => Self::V1_SPACE,
+            Self::CURRENT_VERSION => Self::INIT_SPACE,
+            _ => return Err(UniPinoNftErr::UnsupportedVersion.into()),
+        };
+        if data.len() != space {
             return Err(ProgramError::InvalidAccountData);
         }
-        Ok(state)
+        Ok(version)
+    }
+
+    /// Copy of raw platform account data in any supported layout; platforms from before
+    /// `seed_key` are seeded by their administrator, which could not change then
+    pub fn read(data: &[u8]) -> Result<Self, ProgramError> {
+        let version = Self::version_of(data)?;
+
+        let mut platform = Self::zeroed();
+        if version < 2 {
+            let fields = core::mem::offset_of!(Self, seed_key);
+            bytes_of_mut(&mut platform)[..fields].copy_from_slice(&data[..fields]);
+            platform.seed_key = platform.administrator;
+        } else {
+            bytes_of_mut(&mut platform).copy_from_slice(data);
+        }
+        Ok(platform)
+    }
+
+    /// Whether mint fees are collected in an SPL token instead of lamports
+    pub fn has_fee_mint(&self) -> bool {
+        self.fee_mint != Pubkey::default()
+    }
+
+    /// Whether fees are split according to the platform `FeeSchedule`
+    pub fn has_fee_schedule(&self) -> bool {
+        self.fee_schedule_enabled != 0
+    }
+
+    /// Whether fees are routed to the platform treasury PDA instead of `fee_receiver`
+    pub fn has_treasury(&self) -> bool {
+        self.treasury_enabled != 0
+    }
+
+    /// Whether the treasury holds fees that were not withdrawn yet, in lamports or in
+    /// units of the fee mint
+    pub fn has_undrained_treasury(&self) -> bool {
+        self.treasury_lamports_collected > self.treasury_lamports_withdrawn
+            || self.treasury_tokens_collected > self.treasury_tokens_withdrawn
+    }
+
+    /// Whether the administrator is backed by an `AdminMultisig` signer set
+    pub fn has_multisig(&self) -> bool {
+        self.multisig_enabled != 0
+    }
+
+    /// Fail unless `key` is the platform administrator
+    pub fn ensure_administrator(&self, key: &Pubkey) -> Result<(), ProgramError> {
+        if self.administrator != key.as_ref() {
+            return Err(UniPinoNftErr::NotAdministrator.into());
+        }
+        Ok(())
+    }
+
+    /// Fail if any of the `flags` operations is currently paused
+    pub fn ensure_not_paused(&self, flags: u8) -> Result<(), ProgramError> {
+        if self.paused & flags != 0 {
+            return Err(UniPinoNftErr::PlatformPaused.into());
+        }
+        Ok(())
+    }
+
+    /// Configured fee tiers, lowest `min_level` first
+    pub fn fee_tiers(&self) -> &[FeeTier] {
+        &self.fee_tiers[..(self.fee_tier_count as usize).min(MAX_FEE_TIERS)]
+    }
+
+    /// Discount for a user of `tier_level`, from the highest tier they reach
+    pub fn fee_discount_bps(&self, tier_level: u8) -> u16 {
+        self.fee_tiers()
+            .iter()
+            .rev()
+            .find(|tier| tier.min_level <= tier_level.into())
+            .map_or(0, |tier| tier.discount_bps)
+    }
+}
+
+impl ProgramState for Platform {
+    const DISCRIMINATOR: [u8; 8] = Self::DISCRIMINATOR;
+    const CURRENT_VERSION: u8 = Self::CURRENT_VERSION;
+
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    // platforms from before `seed_key` stay shorter until they are migrated
+    fn validate(data: &[u8]) -> ProgramResult {
+        Self::version_of(data).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytemuck::bytes_of;
+
+    use super::*;
+
+    #[test]
+    fn legacy_platform_reads_as_seeded_by_its_administrator() {
+        let mut platform = Platform::new([5; 32], 255);
+        platform.version = 1;
+        platform.seed_key = Pubkey::default();
+        let data = &bytes_of(&platform)[..Platform::V1_SPACE];
+
+        let read = Platform::read(data).unwrap();
+        assert_eq!(read.seed_key, [5; 32]);
+        assert_eq!({ read.last_generation }, 0);
+        assert_eq!(Platform::version_of(data), Ok(1));
+
+        // a current platform cut to the legacy size is not valid
+        platform.version = Platform::CURRENT_VERSION;
+        let data = &bytes_of(&platform)[..Platform::V1_SPACE];
+        assert_eq!(
+            Platform::validate(data),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn rotated_platform_keeps_its_seed_key() {
+        let mut platform = Platform::new([5; 32], 255);
+        platform.administrator = [6; 32];
+
+        let read = Platform::read(bytes_of(&platform)).unwrap();
+        assert_eq!(read.administrator, [6; 32]);
+        assert_eq!(read.seed_key, [5; 32]);
     }
 }
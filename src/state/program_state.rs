@@ -0,0 +1,113 @@
+use bytemuck::{Pod, bytes_of, try_from_bytes, try_from_bytes_mut};
+use pinocchio::ProgramResult;
+use pinocchio::account_info::AccountInfo;
+use pinocchio::program_error::ProgramError;
+
+use crate::error::UniPinoNftErr;
+use crate::instructions::ID;
+
+/// Zero-copy state stored in an account owned by this program, starting with an
+/// 8 byte discriminator
+pub trait ProgramState: Pod {
+    const DISCRIMINATOR: [u8; 8];
+    const INIT_SPACE: usize = core::mem::size_of::<Self>();
+    /// Latest layout version, 0 for layouts without a version byte
+    const CURRENT_VERSION: u8 = 0;
+    /// Error for an account that is missing or not owned by this program
+    const UNINITIALIZED: UniPinoNftErr = UniPinoNftErr::UninitPda;
+
+    /// Layout version of this state, 0 for layouts without a version byte
+    fn version(&self) -> u8 {
+        0
+    }
+
+    /// Fail unless the state has this type's discriminator and a supported version
+    fn check(&self) -> ProgramResult {
+        if bytes_of(self)[..8] != Self::DISCRIMINATOR {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if self.version() > Self::CURRENT_VERSION {
+            return Err(UniPinoNftErr::UnsupportedVersion.into());
+        }
+        Ok(())
+    }
+
+    /// Deserialize and validate discriminator for immutable access
+    fn try_from_bytes(data: &[u8]) -> Result<&Self, ProgramError> {
+        let state = try_from_bytes::<Self>(data).map_err(|_| ProgramError::InvalidAccountData)?;
+        state.check()?;
+        Ok(state)
+    }
+
+    /// Deserialize and validate discriminator for mutable access
+    fn try_from_bytes_mut(data: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        let state =
+            try_from_bytes_mut::<Self>(data).map_err(|_| ProgramError::InvalidAccountData)?;
+        state.check()?;
+        Ok(state)
+    }
+
+    /// Check raw account data holds this state, in any supported layout
+    fn validate(data: &[u8]) -> ProgramResult {
+        Self::try_from_bytes(data).map(|_| ())
+    }
+
+    /// Check `info` is an initialized account of this program holding this state
+    fn check_account(info: &AccountInfo) -> ProgramResult {
+        if !info.is_owned_by(&ID) || info.lamports() == 0 {
+            return Err(Self::UNINITIALIZED.into());
+        }
+        Self::validate(info.try_borrow_data()?.as_ref())
+    }
+
+    /// Write the state into `info`, an account just created with `INIT_SPACE` bytes
+    fn init(&self, info: &AccountInfo) -> ProgramResult {
+        let mut data = info.try_borrow_mut_data()?;
+        if data.len() != Self::INIT_SPACE {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if data[..8] == Self::DISCRIMINATOR {
+            return Err(UniPinoNftErr::ReInitPda.into());
+        }
+
+        data.copy_from_slice(bytes_of(self));
+        Ok(())
+    }
+}
+
+/// Implement `ProgramState` for a state struct and expose its `DISCRIMINATOR`,
+/// `INIT_SPACE` and, for versioned layouts reading their `version` field,
+/// `CURRENT_VERSION` as inherent constants
+macro_rules! program_state {
+    (
+        $state:ident {
+            discriminator: $discriminator:expr
+            $(, version: $version:literal)?
+            $(, uninitialized: $uninitialized:ident)?
+            $(,)?
+        }
+    ) => {
+        impl $state {
+            pub const DISCRIMINATOR: [u8; 8] = $discriminator;
+            pub const INIT_SPACE: usize = core::mem::size_of::<Self>();
+            $(pub const CURRENT_VERSION: u8 = $version;)?
+        }
+
+        impl $crate::state::program_state::ProgramState for $state {
+            const DISCRIMINATOR: [u8; 8] = $discriminator;
+            $(
+                const CURRENT_VERSION: u8 = $version;
+
+                fn version(&self) -> u8 {
+                    self.version
+                }
+            )?
+            $(
+                const UNINITIALIZED: $crate::error::UniPinoNftErr =
+                    $crate::error::UniPinoNftErr::$uninitialized;
+            )?
+        }
+    };
+}
+
+pub(crate) use program_state;
@@ -0,0 +1,58 @@
+use bytemuck::{Pod, Zeroable};
+use pinocchio::pubkey::Pubkey;
+use shank::ShankAccount;
+
+use crate::state::program_state::program_state;
+
+pub const ROLE_MINTER: u8 = 1;
+pub const ROLE_METADATA_EDITOR: u8 = 2;
+pub const ROLE_FEE_MANAGER: u8 = 3;
+/// Minter role of a partner program, which signs as its minter authority PDA
+pub const ROLE_PROGRAM_MINTER: u8 = 4;
+
+/// Role grant, PDA seeds `["role", platform, role_id, grantee]`
+#[repr(C, packed)]
+#[derive(Copy, Clone, Pod, Zeroable, ShankAccount)]
+pub struct Role {
+    pub discriminator: [u8; 8],
+    pub platform: Pubkey, // platform PDA the role is granted on
+    pub grantee: Pubkey,
+    pub role_id: u8,
+    pub bump: u8,
+    pub authority_bump: u8, // minter authority PDA bump of a `ROLE_PROGRAM_MINTER` grantee
+    #[padding]
+    pub reserved: [u8; 31],
+}
+
+program_state! {
+    Role {
+        discriminator: *b"rolegrnt",
+    }
+}
+
+impl Role {
+    pub fn new(
+        platform_pda: Pubkey,
+        grantee: Pubkey,
+        role_id: u8,
+        bump: u8,
+        authority_bump: u8,
+    ) -> Self {
+        Self {
+            discriminator: Self::DISCRIMINATOR,
+            platform: platform_pda,
+            grantee,
+            role_id,
+            bump,
+            authority_bump,
+            reserved: [0; 31],
+        }
+    }
+
+    pub fn is_valid_id(role_id: u8) -> bool {
+        matches!(
+            role_id,
+            ROLE_MINTER | ROLE_METADATA_EDITOR | ROLE_FEE_MANAGER | ROLE_PROGRAM_MINTER
+        )
+    }
+}
@@ -0,0 +1,83 @@
+use bytemuck::{Pod, Zeroable};
+use pinocchio::pubkey::Pubkey;
+use shank::ShankAccount;
+
+use crate::state::program_state::program_state;
+
+/// Session key of a user, PDA seeds `["session", user, key]`
+#[repr(C, packed)]
+#[derive(Copy, Clone, Pod, Zeroable, ShankAccount)]
+pub struct SessionKey {
+    pub discriminator: [u8; 8],
+    pub user: Pubkey, // user PDA the key acts for
+    pub key: Pubkey,
+    pub allowed_instructions: u64, // bit `n` allows the instruction with discriminator `n`
+    pub expiry_slot: u64,          // last slot the key is accepted in
+    pub bump: u8,
+    pub generation: u64, // `User.generation` of the user the key was created for
+    #[padding]
+    pub reserved: [u8; 24],
+}
+
+program_state! {
+    SessionKey {
+        discriminator: *b"sessnkey",
+    }
+}
+
+impl SessionKey {
+    pub fn new(
+        user_pda: Pubkey,
+        key: Pubkey,
+        allowed_instructions: u64,
+        expiry_slot: u64,
+        bump: u8,
+        generation: u64,
+    ) -> Self {
+        Self {
+            discriminator: Self::DISCRIMINATOR,
+            user: user_pda,
+            key,
+            allowed_instructions,
+            expiry_slot,
+            bump,
+            generation,
+            reserved: [0; 24],
+        }
+    }
+
+    /// Whether the key may sign the instruction with `discriminator` at `slot`
+    pub fn allows(&self, discriminator: u8, slot: u64) -> bool {
+        let allowed = self
+            .allowed_instructions
+            .checked_shr(discriminator as u32)
+            .is_some_and(|mask| mask & 1 != 0);
+        allowed && slot <= self.expiry_slot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn each_bit_allows_one_discriminator() {
+        let session = SessionKey::new([1; 32], [2; 32], 1 << 4 | 1 << 63, 100, 255, 0);
+
+        assert!(session.allows(4, 0));
+        assert!(session.allows(63, 100));
+        assert!(!session.allows(3, 0));
+        assert!(!session.allows(5, 0));
+        // discriminators past the mask are never allowed, instead of wrapping around
+        assert!(!session.allows(64, 0));
+        assert!(!session.allows(68, 0));
+        assert!(!session.allows(u8::MAX, 0));
+    }
+
+    #[test]
+    fn key_expires_after_its_last_slot() {
+        let session = SessionKey::new([1; 32], [2; 32], u64::MAX, 100, 255, 0);
+        assert!(session.allows(4, 100));
+        assert!(!session.allows(4, 101));
+    }
+}
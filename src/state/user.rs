@@ -1,9 +1,12 @@
-use bytemuck::{Pod, Zeroable, try_from_bytes, try_from_bytes_mut};
-use pinocchio::program_error::ProgramError;
+use bytemuck::{Pod, Zeroable};
 use pinocchio::pubkey::Pubkey;
+use shank::ShankAccount;
 
+use crate::state::program_state::program_state;
+
+/// User wallet, PDA seeds `["user_wallet", user_uuid as decimal string, platform, platform bump]`
 #[repr(C, packed)]
-#[derive(Copy, Clone, Pod, Zeroable)]
+#[derive(Copy, Clone, Pod, Zeroable, ShankAccount)]
 pub struct User {
     pub discriminator: [u8; 8],
     pub user_uuid: u128,
@@ -11,14 +14,27 @@ pub struct User {
     pub nft_count: u32,
     pub collection_count: u32,
     pub bump: u8,
-    pub reserved: [u8; 64],
+    pub version: u8,       // layout version, 0 for accounts created before versioning
+    pub authority: Pubkey, // linked owner wallet, all-zero key when none is linked
+    pub nonce: u64,        // next nonce accepted in a signed intent of `authority`
+    pub session_count: u16, // live session key PDAs, CloseUser needs them revoked
+    pub claim_count: u16,  // allowlist claim PDAs, CloseUser closes them
+    pub generation: u64, // drawn from `Platform.last_generation`, changes on re-creation and wallet unlinks
+    pub tier_level: u8,  // fee tier level set by `SetUserTier`, 0 for none
+    #[padding]
+    pub reserved: [u8; 10],
 }
 
-impl User {
-    pub const DISCRIMINATOR: [u8; 8] = *b"usermeta";
-    pub const INIT_SPACE: usize = core::mem::size_of::<Self>();
+program_state! {
+    User {
+        discriminator: *b"usermeta",
+        version: 1,
+        uninitialized: UserInactive,
+    }
+}
 
-    pub fn new(platform_pda: Pubkey, user_uuid: u128, user_bump: u8) -> Self {
+impl User {
+    pub fn new(platform_pda: Pubkey, user_uuid: u128, user_bump: u8, generation: u64) -> Self {
         Self {
             discriminator: Self::DISCRIMINATOR,
             user_uuid,
@@ -26,26 +42,27 @@ impl User {
             nft_count: 0,
             collection_count: 0,
             bump: user_bump,
-            reserved: [0; 64],
+            version: Self::CURRENT_VERSION,
+            authority: Pubkey::default(),
+            nonce: 0,
+            session_count: 0,
+            claim_count: 0,
+            generation,
+            tier_level: 0,
+            reserved: [0; 10],
         }
     }
 
-    /// Deserialize and validate discriminator for immutable access
-    pub fn try_from_bytes(data: &[u8]) -> Result<&Self, ProgramError> {
-        let state = try_from_bytes::<Self>(data).map_err(|_| ProgramError::InvalidAccountData)?;
-        if state.discriminator != Self::DISCRIMINATOR {
-            return Err(ProgramError::InvalidAccountData);
-        }
-        Ok(state)
+    /// Whether an owner wallet is linked to this user
+    pub fn has_authority(&self) -> bool {
+        self.authority != Pubkey::default()
     }
 
-    /// Deserialize and validate discriminator for mutable access
-    pub fn try_from_bytes_mut(data: &mut [u8]) -> Result<&mut Self, ProgramError> {
-        let state =
-            try_from_bytes_mut::<Self>(data).map_err(|_| ProgramError::InvalidAccountData)?;
-        if state.discriminator != Self::DISCRIMINATOR {
-            return Err(ProgramError::InvalidAccountData);
-        }
-        Ok(state)
+    /// Drop the linked wallet and move to a fresh `generation`, so the session keys and
+    /// intents made under the wallet stop working; their keys leave the session count
+    pub fn unlink_wallet(&mut self, generation: u64) {
+        self.authority = Pubkey::default();
+        self.generation = generation;
+        self.session_count = 0;
     }
 }
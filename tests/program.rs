@@ -0,0 +1,585 @@
+//! Runs whole instructions against the program built by `cargo build-sbf` in LiteSVM,
+//! next to the Token-2022 and associated token programs it bundles. Each test returns
+//! early when `target/deploy/UniPinoNft.so` has not been built.
+
+use std::path::Path;
+
+use UniPinoNft::instructions::{
+    CollectionAllowlistArgs, CollectionConfigArgs, ID, MintNftArgs, OwnershipProof,
+    VerifyOwnershipArgs, allowlist::*, collection::*, delegate::*, migrate::*, nft::*,
+    ownership::*, platform::*, transfer::*, user::*,
+};
+use UniPinoNft::state::nft_meta::NftMeta;
+use UniPinoNft::state::user::User;
+use bytemuck::{bytes_of, pod_read_unaligned};
+use litesvm::LiteSVM;
+use litesvm::types::TransactionMetadata;
+use solana_address::Address;
+use solana_instruction::{AccountMeta, Instruction};
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+use solana_transaction::Transaction;
+
+const SYSTEM_PROGRAM: Address = Address::new_from_array([0; 32]);
+
+/// Attribute every NFT is minted with
+const ATTRIBUTE: [u8; 32] = *b"rarity:common\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0";
+
+fn program_id() -> Address {
+    Address::new_from_array(ID)
+}
+
+fn token_2022() -> Address {
+    Address::new_from_array(pinocchio_token_2022::ID)
+}
+
+fn associated_token_program() -> Address {
+    Address::new_from_array(ASSOCIATED_TOKEN_PROGRAM_ID)
+}
+
+fn pda(seeds: &[&[u8]]) -> Address {
+    Address::find_program_address(seeds, &program_id()).0
+}
+
+/// Token-2022 associated token account of `owner` for `mint`
+fn token_account(owner: &Address, mint: &Address) -> Address {
+    Address::find_program_address(
+        &[owner.as_ref(), token_2022().as_ref(), mint.as_ref()],
+        &associated_token_program(),
+    )
+    .0
+}
+
+/// Platform of a funded administrator in a fresh LiteSVM
+struct Fixture {
+    svm: LiteSVM,
+    administrator: Keypair,
+    platform: Address,
+}
+
+/// Collection of the platform minted into
+struct Collection {
+    name: [u8; 64],
+    address: Address,
+}
+
+/// Accounts of a minted NFT
+struct Nft {
+    user: Address,
+    mint: Address,
+    metadata: Address,
+    token_account: Address,
+}
+
+impl Fixture {
+    fn new() -> Option<Self> {
+        let program = Path::new(env!("CARGO_MANIFEST_DIR")).join("target/deploy/UniPinoNft.so");
+        if !program.exists() {
+            eprintln!("skipped, run `cargo build-sbf` first");
+            return None;
+        }
+
+        let mut svm = LiteSVM::new();
+        svm.add_program_from_file(program_id(), program)
+            .expect("load program");
+
+        let administrator = Keypair::new();
+        svm.airdrop(&administrator.pubkey(), 10_000_000_000)
+            .expect("airdrop");
+        let platform = pda(&[PLATFORM_TOKEN, administrator.pubkey().as_ref()]);
+
+        let mut fixture = Self {
+            svm,
+            administrator,
+            platform,
+        };
+        fixture
+            .send(
+                *InitPlatform::DISCRIMINATOR,
+                vec![
+                    AccountMeta::new(fixture.administrator.pubkey(), true),
+                    AccountMeta::new(platform, false),
+                    AccountMeta::new_readonly(SYSTEM_PROGRAM, false),
+                ],
+                &[],
+            )
+            .expect("init platform");
+        Some(fixture)
+    }
+
+    /// Send one instruction of the program signed by the administrator, failing with the
+    /// error and the program logs
+    fn send(
+        &mut self,
+        discriminator: u8,
+        accounts: Vec<AccountMeta>,
+        args: &[u8],
+    ) -> Result<TransactionMetadata, String> {
+        let mut data = vec![discriminator];
+        data.extend_from_slice(args);
+        self.send_instruction(Instruction {
+            program_id: program_id(),
+            accounts,
+            data,
+        })
+    }
+
+    fn send_instruction(
+        &mut self,
+        instruction: Instruction,
+    ) -> Result<TransactionMetadata, String> {
+        self.svm.expire_blockhash();
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&self.administrator.pubkey()),
+            &[&self.administrator],
+            self.svm.latest_blockhash(),
+        );
+        self.svm
+            .send_transaction(transaction)
+            .map_err(|failed| format!("{:?}\n{}", failed.err, failed.meta.logs.join("\n")))
+    }
+
+    fn user(&self, user_uuid: u128) -> Address {
+        let platform_bump = Address::find_program_address(
+            &[PLATFORM_TOKEN, self.administrator.pubkey().as_ref()],
+            &program_id(),
+        )
+        .1;
+        pda(&[
+            USER_TOKEN,
+            user_uuid.to_string().as_bytes(),
+            self.platform.as_ref(),
+            &[platform_bump],
+        ])
+    }
+
+    fn create_user(&mut self, user_uuid: u128) -> Address {
+        let user = self.user(user_uuid);
+        self.send(
+            *CreateUser::DISCRIMINATOR,
+            vec![
+                AccountMeta::new(self.administrator.pubkey(), true),
+                AccountMeta::new(self.platform, false),
+                AccountMeta::new(user, false),
+                AccountMeta::new_readonly(SYSTEM_PROGRAM, false),
+            ],
+            &user_uuid.to_le_bytes(),
+        )
+        .expect("create user");
+        user
+    }
+
+    /// Mint an NFT outside any collection for a new user
+    fn mint_nft(&mut self, user_uuid: u128) -> Nft {
+        let user = self.create_user(user_uuid);
+        self.mint(user_uuid, user, None).expect("mint nft")
+    }
+
+    /// Mint an NFT for an existing user, into `collection` with an empty allowlist proof
+    /// when given
+    fn mint(
+        &mut self,
+        user_uuid: u128,
+        user: Address,
+        collection: Option<&Collection>,
+    ) -> Result<Nft, String> {
+        let mint = pda(&[
+            &user_uuid.to_le_bytes(),
+            user.as_ref(),
+            token_2022().as_ref(),
+        ]);
+        let metadata = pda(&[b"metadata", mint.as_ref(), token_2022().as_ref()]);
+        let token_account = token_account(&user, &mint);
+
+        let mut args = MintNftArgs {
+            user_uuid,
+            asset_name: [0; 256],
+            desc: [0; 256],
+            uri: [0; 256],
+            collection: collection.map_or([0; 64], |collection| collection.name),
+            attribute: ATTRIBUTE,
+        };
+        args.asset_name[..5].copy_from_slice(b"Sword");
+        args.uri[..19].copy_from_slice(b"https://example.com");
+
+        let mut accounts = vec![
+            AccountMeta::new(self.administrator.pubkey(), true),
+            AccountMeta::new(self.platform, false),
+            AccountMeta::new(user, false),
+            AccountMeta::new(mint, false),
+            AccountMeta::new(metadata, false),
+            AccountMeta::new(token_account, false),
+            AccountMeta::new(self.administrator.pubkey(), false),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM, false),
+            AccountMeta::new_readonly(token_2022(), false),
+            AccountMeta::new_readonly(associated_token_program(), false),
+        ];
+        if let Some(collection) = collection {
+            accounts.push(AccountMeta::new(collection.address, false));
+            accounts.push(AccountMeta::new(
+                pda(&[CLAIM_TOKEN, collection.address.as_ref(), user.as_ref()]),
+                false,
+            ));
+        }
+        self.send(*MintNft::DISCRIMINATOR, accounts, bytes_of(&args))?;
+
+        Ok(Nft {
+            user,
+            mint,
+            metadata,
+            token_account,
+        })
+    }
+
+    /// Create a collection whose allowlist holds only `user`, allowing it `user_limit`
+    /// mints in phase 1
+    fn allowlisted_collection(&mut self, user: &Address, user_limit: u16) -> Collection {
+        let mut name = [0; 64];
+        name[..5].copy_from_slice(b"Drops");
+        let address = pda(&[
+            COLLECTION_TOKEN,
+            self.platform.as_ref(),
+            &name[..32],
+            &name[32..],
+        ]);
+
+        let config_args = CollectionConfigArgs {
+            name,
+            mint_fee: 0,
+            has_mint_fee: 0,
+        };
+        self.send(
+            *SetCollectionConfig::DISCRIMINATOR,
+            vec![
+                AccountMeta::new_readonly(self.administrator.pubkey(), true),
+                AccountMeta::new(self.platform, false),
+                AccountMeta::new(address, false),
+                AccountMeta::new_readonly(SYSTEM_PROGRAM, false),
+            ],
+            bytes_of(&config_args),
+        )
+        .expect("set collection config");
+
+        // a single leaf is the root, proven by an empty proof
+        let allowlist_args = CollectionAllowlistArgs {
+            name,
+            allowlist_root: leaf(&user.to_bytes()),
+            phase: 1,
+            phase_user_limit: user_limit,
+            phase_supply: 0,
+        };
+        self.send(
+            *SetCollectionAllowlist::DISCRIMINATOR,
+            vec![
+                AccountMeta::new_readonly(self.administrator.pubkey(), true),
+                AccountMeta::new_readonly(self.platform, false),
+                AccountMeta::new(address, false),
+            ],
+            bytes_of(&allowlist_args),
+        )
+        .expect("set collection allowlist");
+
+        Collection { name, address }
+    }
+
+    /// Verify `holder` owns the token account holding the NFT of `nft.mint` for `user`,
+    /// with `attribute` unless it is all-zero
+    fn verify_ownership(
+        &mut self,
+        user: Address,
+        nft: &Nft,
+        holder: Address,
+        attribute: [u8; 32],
+    ) -> Result<TransactionMetadata, String> {
+        let token_account = token_account(&holder, &nft.mint);
+        let args = VerifyOwnershipArgs {
+            holder: holder.to_bytes(),
+            collection: [0; 64],
+            attribute,
+        };
+        self.send(
+            *VerifyOwnership::DISCRIMINATOR,
+            vec![
+                AccountMeta::new_readonly(self.platform, false),
+                AccountMeta::new_readonly(user, false),
+                AccountMeta::new_readonly(nft.mint, false),
+                AccountMeta::new_readonly(nft.metadata, false),
+                AccountMeta::new_readonly(token_account, false),
+            ],
+            bytes_of(&args),
+        )
+    }
+
+    /// Create the associated token account of `owner` for `mint`
+    fn create_token_account(&mut self, owner: &Address, mint: &Address) -> Address {
+        let token_account = token_account(owner, mint);
+        self.send_instruction(Instruction {
+            program_id: associated_token_program(),
+            accounts: vec![
+                AccountMeta::new(self.administrator.pubkey(), true),
+                AccountMeta::new(token_account, false),
+                AccountMeta::new_readonly(*owner, false),
+                AccountMeta::new_readonly(*mint, false),
+                AccountMeta::new_readonly(SYSTEM_PROGRAM, false),
+                AccountMeta::new_readonly(token_2022(), false),
+            ],
+            // `CreateIdempotent`
+            data: vec![1],
+        })
+        .expect("create token account");
+        token_account
+    }
+
+    fn transfer(&mut self, nft: &Nft, to: Address) -> Result<TransactionMetadata, String> {
+        let to_token_account = token_account(&to, &nft.mint);
+        self.send(
+            *TransferNftInternal::DISCRIMINATOR,
+            vec![
+                AccountMeta::new_readonly(self.administrator.pubkey(), true),
+                AccountMeta::new_readonly(self.platform, false),
+                AccountMeta::new(nft.user, false),
+                AccountMeta::new(to, false),
+                AccountMeta::new_readonly(nft.mint, false),
+                AccountMeta::new(nft.metadata, false),
+                AccountMeta::new(nft.token_account, false),
+                AccountMeta::new(to_token_account, false),
+                AccountMeta::new_readonly(token_2022(), false),
+            ],
+            &[],
+        )
+    }
+
+    /// Burn the NFT of `nft.mint` held by `user`
+    fn burn(&mut self, nft: &Nft, user: Address) -> Result<TransactionMetadata, String> {
+        self.send(
+            *BurnNft::DISCRIMINATOR,
+            vec![
+                AccountMeta::new(self.administrator.pubkey(), true),
+                AccountMeta::new(self.platform, false),
+                AccountMeta::new(user, false),
+                AccountMeta::new(nft.mint, false),
+                AccountMeta::new(nft.metadata, false),
+                AccountMeta::new(token_account(&user, &nft.mint), false),
+                AccountMeta::new_readonly(token_2022(), false),
+            ],
+            &[],
+        )
+    }
+
+    /// Approve `delegate` on the NFT, or revoke the delegate when there is none
+    fn update_delegate(
+        &mut self,
+        nft: &Nft,
+        delegate: Option<Address>,
+    ) -> Result<TransactionMetadata, String> {
+        let mut accounts = vec![
+            AccountMeta::new_readonly(self.administrator.pubkey(), true),
+            AccountMeta::new_readonly(self.platform, false),
+            AccountMeta::new_readonly(nft.user, false),
+            AccountMeta::new_readonly(nft.mint, false),
+            AccountMeta::new(nft.metadata, false),
+            AccountMeta::new(nft.token_account, false),
+        ];
+        let discriminator = match delegate {
+            Some(delegate) => {
+                accounts.push(AccountMeta::new_readonly(delegate, false));
+                *ApproveNftDelegate::DISCRIMINATOR
+            }
+            None => *RevokeNftDelegate::DISCRIMINATOR,
+        };
+        accounts.push(AccountMeta::new_readonly(token_2022(), false));
+        self.send(discriminator, accounts, &[])
+    }
+
+    /// Migrate `account` to the current layout, followed by `remaining`
+    fn migrate(
+        &mut self,
+        account: Address,
+        remaining: &[Address],
+    ) -> Result<TransactionMetadata, String> {
+        let mut accounts = vec![
+            AccountMeta::new(self.administrator.pubkey(), true),
+            AccountMeta::new_readonly(self.platform, false),
+            AccountMeta::new(account, false),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM, false),
+        ];
+        accounts.extend(
+            remaining
+                .iter()
+                .map(|address| AccountMeta::new_readonly(*address, false)),
+        );
+        self.send(*MigrateAccount::DISCRIMINATOR, accounts, &[])
+    }
+
+    fn metadata(&self, nft: &Nft) -> NftMeta {
+        let account = self.svm.get_account(&nft.metadata).expect("metadata");
+        pod_read_unaligned(&account.data)
+    }
+
+    fn nft_count(&self, user: &Address) -> u32 {
+        let account = self.svm.get_account(user).expect("user");
+        let user: User = pod_read_unaligned(&account.data[..size_of::<User>()]);
+        user.nft_count
+    }
+
+    /// Token amount held by a Token-2022 account
+    fn token_amount(&self, token_account: &Address) -> u64 {
+        let account = self.svm.get_account(token_account).expect("token account");
+        u64::from_le_bytes(account.data[64..72].try_into().unwrap())
+    }
+
+    /// Delegate of a Token-2022 account and the amount it may move
+    fn token_delegate(&self, token_account: &Address) -> Option<(Address, u64)> {
+        let account = self.svm.get_account(token_account).expect("token account");
+        let data = &account.data;
+        (data[72..76] == [1, 0, 0, 0]).then(|| {
+            (
+                Address::try_from(&data[76..108]).unwrap(),
+                u64::from_le_bytes(data[121..129].try_into().unwrap()),
+            )
+        })
+    }
+}
+
+#[test]
+fn mint_then_verify_ownership() {
+    let Some(mut fixture) = Fixture::new() else {
+        return;
+    };
+
+    let nft = fixture.mint_nft(42);
+    assert_eq!(fixture.token_amount(&nft.token_account), 1);
+
+    let result = fixture
+        .verify_ownership(nft.user, &nft, nft.user, ATTRIBUTE)
+        .expect("verify ownership");
+    let proof: OwnershipProof = pod_read_unaligned(&result.return_data.data);
+    assert_eq!(proof.platform, fixture.platform.to_bytes());
+    assert_eq!({ proof.user_uuid }, 42);
+    assert_eq!(proof.mint, nft.mint.to_bytes());
+    assert_eq!(proof.holder, nft.user.to_bytes());
+
+    // other attributes do not match
+    let mut attribute = ATTRIBUTE;
+    attribute[7..13].copy_from_slice(b"unique");
+    assert!(
+        fixture
+            .verify_ownership(nft.user, &nft, nft.user, attribute)
+            .is_err()
+    );
+
+    // the administrator does not hold the NFT
+    let administrator = fixture.administrator.pubkey();
+    assert!(
+        fixture
+            .verify_ownership(nft.user, &nft, administrator, [0; 32])
+            .is_err()
+    );
+}
+
+#[test]
+fn transfer_moves_holder_and_count() {
+    let Some(mut fixture) = Fixture::new() else {
+        return;
+    };
+
+    let nft = fixture.mint_nft(1);
+    let receiver = fixture.create_user(2);
+    let receiver_token_account = fixture.create_token_account(&receiver, &nft.mint);
+
+    fixture.transfer(&nft, receiver).expect("transfer");
+    assert_eq!(fixture.token_amount(&nft.token_account), 0);
+    assert_eq!(fixture.token_amount(&receiver_token_account), 1);
+    assert_eq!(fixture.nft_count(&nft.user), 0);
+    assert_eq!(fixture.nft_count(&receiver), 1);
+
+    assert_eq!(fixture.metadata(&nft).holder, receiver.to_bytes());
+
+    // only the receiver holds the NFT now
+    assert!(fixture.burn(&nft, nft.user).is_err());
+    fixture
+        .verify_ownership(receiver, &nft, receiver, [0; 32])
+        .expect("verify ownership");
+    assert!(
+        fixture
+            .verify_ownership(nft.user, &nft, nft.user, [0; 32])
+            .is_err()
+    );
+    fixture.burn(&nft, receiver).expect("burn");
+    assert!(
+        fixture
+            .svm
+            .get_account(&nft.mint)
+            .is_none_or(|mint| mint.lamports == 0)
+    );
+    assert_eq!(fixture.nft_count(&receiver), 0);
+}
+
+#[test]
+fn migrating_metadata_needs_its_holder() {
+    let Some(mut fixture) = Fixture::new() else {
+        return;
+    };
+
+    let nft = fixture.mint_nft(1);
+    let other = fixture.mint_nft(2);
+
+    assert!(fixture.migrate(nft.metadata, &[]).is_err());
+    assert!(
+        fixture
+            .migrate(nft.metadata, &[other.user, nft.mint])
+            .is_err()
+    );
+    assert!(
+        fixture
+            .migrate(nft.metadata, &[nft.user, other.mint])
+            .is_err()
+    );
+    fixture
+        .migrate(nft.metadata, &[nft.user, nft.mint])
+        .expect("migrate metadata");
+}
+
+#[test]
+fn approve_delegate_on_minted_nft() {
+    let Some(mut fixture) = Fixture::new() else {
+        return;
+    };
+
+    let nft = fixture.mint_nft(7);
+    let delegate = Keypair::new().pubkey();
+
+    fixture
+        .update_delegate(&nft, Some(delegate))
+        .expect("approve delegate");
+    assert_eq!(
+        fixture.token_delegate(&nft.token_account),
+        Some((delegate, 1))
+    );
+    assert_eq!(fixture.metadata(&nft).delegate, delegate.to_bytes());
+
+    fixture
+        .update_delegate(&nft, None)
+        .expect("revoke delegate");
+    assert_eq!(fixture.token_delegate(&nft.token_account), None);
+    assert_eq!(fixture.metadata(&nft).delegate, [0; 32]);
+}
+
+#[test]
+fn allowlist_limits_mints_after_burns() {
+    let Some(mut fixture) = Fixture::new() else {
+        return;
+    };
+
+    // the user holds one NFT at a time, so its second allowlist mint follows a burn
+    let user = fixture.create_user(3);
+    let collection = fixture.allowlisted_collection(&user, 2);
+    for _ in 0..2 {
+        let nft = fixture
+            .mint(3, user, Some(&collection))
+            .expect("allowlist mint");
+        fixture.burn(&nft, user).expect("burn");
+    }
+    assert!(fixture.mint(3, user, Some(&collection)).is_err());
+}